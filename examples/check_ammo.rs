@@ -38,7 +38,7 @@ fn check_group_for_ammo(group: &esp_extractor::Group, depth: usize) {
                     }
 
                     // 查找所有子记录
-                    for subrecord in &record.subrecords {
+                    for subrecord in record.subrecords() {
                         println!(
                             "  子记录: {} (size={})",
                             subrecord.record_type,
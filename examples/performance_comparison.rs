@@ -5,8 +5,11 @@
 //! 2. load_auto 方式：使用 LoadedPlugin::load_auto()
 //!
 //! 目标：验证并修复 load_auto 对本地化插件重复加载 ESP 文件的问题
+//!
+//! 最后附带解析耗时基准：分别测量完整解析与只解析字符串记录的耗时，
+//! 以及最大顶级组（通常是 CELL/WRLD）占整个文件的比例，用于跟踪嵌套组并行解析的收益。
 
-use esp_extractor::{Plugin, LoadedPlugin};
+use esp_extractor::{Plugin, LoadedPlugin, ParseOptions};
 use std::path::PathBuf;
 use std::time::Instant;
 
@@ -155,6 +158,44 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("  🎉 load_auto 已优化，无重复加载问题！");
     }
 
+    // ============================================================
+    // 解析耗时基准
+    // ============================================================
+
+    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("⏱️  解析耗时基准 ({} 个线程)", rayon::current_num_threads());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    let mut benchmarks = vec![("完整解析", ParseOptions::default())];
+    match ParseOptions::strings_only() {
+        Ok(options) => benchmarks.push(("只解析字符串记录", options)),
+        Err(e) => println!("  ⚠️  无法创建字符串解析选项: {}", e),
+    }
+
+    for (name, options) in &benchmarks {
+        let mut times = Vec::new();
+        let mut largest_group = None;
+
+        for _ in 0..TEST_ROUNDS {
+            let start = Instant::now();
+            let plugin = Plugin::load_with_options(file_path.clone(), options)?;
+            times.push(start.elapsed());
+
+            largest_group = plugin.groups.iter()
+                .max_by_key(|group| group.size)
+                .map(|group| (group.get_label_string(), group.size));
+        }
+
+        let avg = times.iter().sum::<std::time::Duration>() / times.len() as u32;
+        println!("  {}:", name);
+        println!("    平均耗时: {:.3} 秒", avg.as_secs_f64());
+        println!("    最快: {:.3} 秒", times.iter().min().unwrap().as_secs_f64());
+        println!("    吞吐量: {:.1} MB/秒", file_size as f64 / 1024.0 / 1024.0 / avg.as_secs_f64());
+        if let Some((label, size)) = largest_group {
+            println!("    最大顶级组: {} ({:.1}% 的文件)", label, size as f64 / file_size as f64 * 100.0);
+        }
+    }
+
     println!("\n=================================================");
     println!("            测试完成！");
     println!("=================================================\n");
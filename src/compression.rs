@@ -0,0 +1,282 @@
+/// 记录压缩模块
+///
+/// 提供 zlib 压缩参数（级别 + 策略）的检测与复现，以及写入时的压缩策略配置。
+///
+/// zlib 流头部的 FLEVEL 字段记录了压缩级别的大致范围，第一个 deflate 块的类型
+/// 可以区分存储块/固定哈夫曼块/动态哈夫曼块。写入时只按头部推断参数（`header_settings`），
+/// 不做重新压缩比较。
+///
+/// `detect_compression` 在候选参数中逐一重新压缩并逐字节比较，开销较大，仅用于诊断。
+/// 本模块使用 miniz 压缩，zlib / Creation Kit 生成的数据几乎无法逐字节复现，
+/// 这类数据报告为 `DetectedCompression::Unknown`。
+use miniz_oxide::deflate::core::{
+    compress_to_output, create_comp_flags_from_zip_params, CompressorOxide, TDEFLFlush, TDEFLStatus,
+};
+use serde::Serialize;
+
+/// 默认压缩级别（与 zlib 默认值一致）
+pub const DEFAULT_COMPRESSION_LEVEL: u8 = 6;
+
+/// deflate 压缩策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+pub enum CompressionStrategy {
+    /// 默认策略
+    #[default]
+    Default,
+    /// 只使用长度不小于 5 的匹配
+    Filtered,
+    /// 只做哈夫曼编码，不查找匹配
+    HuffmanOnly,
+    /// 只查找距离为 1 的匹配（游程编码）
+    Rle,
+    /// 只使用固定哈夫曼块
+    Fixed,
+}
+
+impl CompressionStrategy {
+    fn to_miniz(self) -> i32 {
+        match self {
+            CompressionStrategy::Default => 0,
+            CompressionStrategy::Filtered => 1,
+            CompressionStrategy::HuffmanOnly => 2,
+            CompressionStrategy::Rle => 3,
+            CompressionStrategy::Fixed => 4,
+        }
+    }
+}
+
+/// zlib 压缩参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct CompressionSettings {
+    /// 压缩级别（0-10，10 为 miniz 的最高级别）
+    pub level: u8,
+    /// 压缩策略
+    pub strategy: CompressionStrategy,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        CompressionSettings {
+            level: DEFAULT_COMPRESSION_LEVEL,
+            strategy: CompressionStrategy::Default,
+        }
+    }
+}
+
+/// 压缩参数检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DetectedCompression {
+    /// 以该参数重新压缩能逐字节复现原始数据（原始数据由 miniz 生成）
+    Exact(CompressionSettings),
+    /// 有效的 zlib 流，但无法复现（通常由 zlib / Creation Kit 生成），实际参数未知；
+    /// 附带按头部推断的参数
+    Unknown(CompressionSettings),
+}
+
+impl DetectedCompression {
+    /// 重新压缩时使用的参数
+    pub fn settings(&self) -> CompressionSettings {
+        match self {
+            DetectedCompression::Exact(settings) | DetectedCompression::Unknown(settings) => *settings,
+        }
+    }
+
+    /// 是否精确匹配
+    pub fn is_exact(&self) -> bool {
+        matches!(self, DetectedCompression::Exact(_))
+    }
+}
+
+/// 写入时的记录压缩策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum CompressionPolicy {
+    /// 保持原状：未修改的记录原样写出，修改过的压缩记录以按原始 zlib 头部推断的参数重新压缩
+    #[default]
+    Preserve,
+    /// 压缩所有解压后大小超过阈值（字节）的记录，其余记录不压缩
+    Always {
+        /// 大小阈值（字节）
+        threshold: usize,
+    },
+    /// 解压所有记录并清除压缩标志
+    Never,
+}
+
+/// 以指定参数进行 zlib 压缩（含 zlib 头部和 Adler-32 校验）
+pub fn compress(data: &[u8], settings: CompressionSettings) -> Vec<u8> {
+    let flags = create_comp_flags_from_zip_params(
+        settings.level.min(10) as i32,
+        1,
+        settings.strategy.to_miniz(),
+    );
+    let mut compressor = CompressorOxide::new(flags);
+    let mut output = Vec::with_capacity(data.len() / 2 + 16);
+
+    let (status, _) = compress_to_output(&mut compressor, data, TDEFLFlush::Finish, |chunk| {
+        output.extend_from_slice(chunk);
+        true
+    });
+    debug_assert_eq!(status, TDEFLStatus::Done);
+
+    output
+}
+
+/// 按 zlib 流头部推断压缩参数（不做重新压缩，开销可忽略）
+///
+/// 数据不是有效的 zlib 流时返回 `None`。
+pub fn header_settings(stream: &[u8]) -> Option<CompressionSettings> {
+    header_candidates(stream)?.first().copied()
+}
+
+/// 检测 zlib 流的压缩参数
+///
+/// 在头部对应的候选参数中逐一重新压缩比较，最多约 12 次压缩，不应在写入路径上调用。
+///
+/// # 参数
+/// * `stream` - zlib 压缩流（不含记录数据开头的 4 字节解压大小）
+/// * `uncompressed` - 解压后的数据，用于验证候选参数
+///
+/// # 返回
+/// 数据不是有效的 zlib 流时返回 `None`；没有候选参数能逐字节复现时返回
+/// `DetectedCompression::Unknown`。
+pub fn detect_compression(stream: &[u8], uncompressed: &[u8]) -> Option<DetectedCompression> {
+    let candidates = header_candidates(stream)?;
+
+    candidates.iter()
+        .find(|settings| compress(uncompressed, **settings) == stream)
+        .map(|settings| DetectedCompression::Exact(*settings))
+        .or_else(|| candidates.first().map(|settings| DetectedCompression::Unknown(*settings)))
+}
+
+/// 校验 zlib 头部并列出候选参数
+fn header_candidates(stream: &[u8]) -> Option<Vec<CompressionSettings>> {
+    if stream.len() < 3 {
+        return None;
+    }
+
+    let cmf = stream[0];
+    let flg = stream[1];
+    if cmf & 0x0F != 8 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return None;
+    }
+
+    Some(candidate_settings(flg >> 6, stream[2] >> 1 & 0b11))
+}
+
+/// 按 FLEVEL 和第一个块的类型列出候选参数（最可能的排在最前）
+///
+/// FLEVEL 与级别的对应关系：zlib 为 0-1/2-5/6/7-9，miniz 为 1/2-3/4-8/9。
+/// 优先按 zlib（Creation Kit 使用）的对应关系排列。
+fn candidate_settings(flevel: u8, block_type: u8) -> Vec<CompressionSettings> {
+    let settings = |level: u8, strategy: CompressionStrategy| CompressionSettings { level, strategy };
+    let levels: &[u8] = match flevel {
+        0 => &[1],
+        1 => &[5, 4, 3, 2],
+        2 => &[6, 4, 5, 7, 8],
+        _ => &[9, 8, 7],
+    };
+
+    match block_type {
+        // 存储块：不压缩
+        0 => vec![settings(0, CompressionStrategy::Default)],
+        // 固定哈夫曼块
+        1 => levels.iter().map(|level| settings(*level, CompressionStrategy::Fixed))
+            .chain(levels.iter().map(|level| settings(*level, CompressionStrategy::Default)))
+            .collect(),
+        // 动态哈夫曼块
+        _ => levels.iter().map(|level| settings(*level, CompressionStrategy::Default))
+            .chain(levels.iter().map(|level| settings(*level, CompressionStrategy::Filtered)))
+            .chain([
+                settings(DEFAULT_COMPRESSION_LEVEL, CompressionStrategy::HuffmanOnly),
+                settings(DEFAULT_COMPRESSION_LEVEL, CompressionStrategy::Rle),
+            ])
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    /// Python `zlib.compress(data, 6)`（zlib 1.2.13）的输出，data 见 `zlib_fixture_data`
+    const ZLIB_FIXTURE: &[u8] = &[
+        0x78, 0x9C, 0x6D, 0xD0, 0x39, 0x0E, 0x82, 0x60, 0x18, 0x06, 0xE1, 0x5F, 0x45, 0x71, 0x41, 0xC1,
+        0xFD, 0x1A, 0x80, 0x2B, 0x3D, 0x98, 0x90, 0xD0, 0x19, 0x6F, 0x40, 0x0D, 0x09, 0x8D, 0xD7, 0x57,
+        0x93, 0xEF, 0x6D, 0x64, 0xA6, 0x7D, 0xBA, 0x29, 0xF2, 0x32, 0x0F, 0x5C, 0xD9, 0xB5, 0xCD, 0xF3,
+        0xDD, 0x76, 0x75, 0x1C, 0xBB, 0xC7, 0xAB, 0xAA, 0x3C, 0xF7, 0xAB, 0xF8, 0xB3, 0xC4, 0x6C, 0x00,
+        0x96, 0x9A, 0x0D, 0xC1, 0x4E, 0x66, 0x23, 0xB0, 0xB3, 0x99, 0x07, 0x76, 0x31, 0x1B, 0x83, 0x5D,
+        0xCD, 0x26, 0x60, 0x37, 0x33, 0x1F, 0xEC, 0x6E, 0x36, 0x05, 0xCB, 0xCC, 0x66, 0x7D, 0x4B, 0xF4,
+        0x65, 0x0E, 0xA6, 0x2F, 0x0B, 0x30, 0x7D, 0x09, 0xC0, 0xF4, 0x65, 0x09, 0xA6, 0x2F, 0x2B, 0x30,
+        0x7D, 0x09, 0xC1, 0xF4, 0x25, 0x02, 0xD3, 0x97, 0x35, 0x98, 0xBE, 0x6C, 0xC0, 0xF4, 0x65, 0xDB,
+        0xB7, 0x54, 0x5F, 0x76, 0x60, 0xFA, 0xB2, 0x07, 0xD3, 0x97, 0x03, 0x98, 0xBE, 0x1C, 0xBF, 0xF6,
+        0x01, 0x38, 0xF2, 0x9A, 0x87,
+    ];
+
+    /// 24 个 EDID + FULL 子记录
+    fn zlib_fixture_data() -> Vec<u8> {
+        (0..24u8).flat_map(|i| {
+            let mut subrecords = b"EDID\x0c\x00".to_vec();
+            subrecords.extend_from_slice(format!("IronSword{:02}\0", i).as_bytes());
+            subrecords.extend_from_slice(b"FULL\x04\x00");
+            subrecords.extend_from_slice(&[i, 0, 0, 0]);
+            subrecords
+        }).collect()
+    }
+
+    fn sample_data() -> Vec<u8> {
+        (0..4096u32).flat_map(|i| ((i * 7) % 251).to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let data = sample_data();
+        for strategy in [
+            CompressionStrategy::Default,
+            CompressionStrategy::Filtered,
+            CompressionStrategy::HuffmanOnly,
+            CompressionStrategy::Rle,
+            CompressionStrategy::Fixed,
+        ] {
+            let compressed = compress(&data, CompressionSettings { level: 9, strategy });
+            let mut decompressed = Vec::new();
+            ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut decompressed).unwrap();
+            assert_eq!(decompressed, data);
+        }
+    }
+
+    #[test]
+    fn test_detect_compression() {
+        let data = sample_data();
+        for settings in [
+            CompressionSettings { level: 9, strategy: CompressionStrategy::Default },
+            CompressionSettings { level: 1, strategy: CompressionStrategy::Default },
+            CompressionSettings { level: 6, strategy: CompressionStrategy::Fixed },
+            CompressionSettings { level: 6, strategy: CompressionStrategy::Rle },
+            CompressionSettings { level: 0, strategy: CompressionStrategy::Default },
+        ] {
+            let stream = compress(&data, settings);
+            let detected = detect_compression(&stream, &data).unwrap();
+            assert!(detected.is_exact(), "{:?}", settings);
+            assert_eq!(compress(&data, detected.settings()), stream);
+            assert_eq!(header_settings(&stream).map(|s| s.level == 0), Some(settings.level == 0));
+        }
+
+        assert!(detect_compression(b"not zlib", &data).is_none());
+        assert!(header_settings(b"not zlib").is_none());
+    }
+
+    #[test]
+    fn test_detect_zlib_output_as_unknown() {
+        let data = zlib_fixture_data();
+        let mut decompressed = Vec::new();
+        ZlibDecoder::new(ZLIB_FIXTURE).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, data);
+
+        // zlib 的输出无法由 miniz 复现，报告为未知，附带按头部推断的默认级别
+        let expected = CompressionSettings { level: 6, strategy: CompressionStrategy::Default };
+        assert_eq!(detect_compression(ZLIB_FIXTURE, &data), Some(DetectedCompression::Unknown(expected)));
+        assert_eq!(header_settings(ZLIB_FIXTURE), Some(expected));
+    }
+}
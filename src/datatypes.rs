@@ -88,6 +88,14 @@ impl RawString {
     }
 }
 
+/// 生成 Z字符串数据（UTF-8，以 null 结尾）
+pub(crate) fn zstring(text: &str) -> Vec<u8> {
+    let mut data = Vec::with_capacity(text.len() + 1);
+    data.extend_from_slice(text.as_bytes());
+    data.push(0);
+    data
+}
+
 // 记录标志位定义
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy)]
@@ -125,4 +133,378 @@ bitflags::bitflags! {
         const UNKNOWN_40000000 = 0x40000000;  // 未知标志位 0x40000000
         const UNKNOWN_80000000 = 0x80000000;  // 未知标志位 0x80000000
     }
-} 
\ No newline at end of file
+} 
+/// 4 字节签名（记录类型、子记录类型、顶级组标签）
+///
+/// 以原始字节保存，比较和哈希不需要分配内存。显示和序列化时每个字节映射为
+/// 一个字符（Latin-1），因此包含非 ASCII 字节的签名也能原样往返。
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Signature(pub [u8; 4]);
+
+impl Signature {
+    /// 组
+    pub const GRUP: Signature = Signature(*b"GRUP");
+    /// 插件头部记录
+    pub const TES4: Signature = Signature(*b"TES4");
+    /// 超大子记录前缀
+    pub const XXXX: Signature = Signature(*b"XXXX");
+
+    /// 从原始字节创建
+    pub const fn new(bytes: [u8; 4]) -> Self {
+        Signature(bytes)
+    }
+
+    /// 从名称创建，可用于常量
+    ///
+    /// # Panics
+    /// 名称不是恰好 4 个字节时 panic（在常量中使用时为编译错误）。
+    /// 需要处理外部输入时使用 `str::parse`。
+    pub const fn from_name(name: &str) -> Self {
+        let name = name.as_bytes();
+        assert!(name.len() == 4, "签名必须恰好是 4 个字节");
+        Signature([name[0], name[1], name[2], name[3]])
+    }
+
+    /// 原始字节
+    pub const fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    /// 按小端序解释为 u32（非顶级组的标签为 FormID 或坐标）
+    pub const fn to_u32(self) -> u32 {
+        u32::from_le_bytes(self.0)
+    }
+
+    /// 从名称解析：每个字符对应一个字节（必须恰好 4 个字符且都不超过 U+00FF）
+    fn parse_name(name: &str) -> Option<[u8; 4]> {
+        let mut bytes = [0u8; 4];
+        let mut chars = name.chars();
+        for byte in bytes.iter_mut() {
+            *byte = u8::try_from(u32::from(chars.next()?)).ok()?;
+        }
+        chars.next().is_none().then_some(bytes)
+    }
+}
+
+impl From<[u8; 4]> for Signature {
+    fn from(bytes: [u8; 4]) -> Self {
+        Signature(bytes)
+    }
+}
+
+impl From<Signature> for [u8; 4] {
+    fn from(signature: Signature) -> Self {
+        signature.0
+    }
+}
+
+impl std::str::FromStr for Signature {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::parse_name(name)
+            .map(Signature)
+            .ok_or_else(|| format!("无效的签名: {:?}（需要 4 个字符）", name))
+    }
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+        self.0.iter().try_for_each(|byte| f.write_char(char::from(*byte)))
+    }
+}
+
+impl std::fmt::Debug for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Signature({:?})", self.to_string())
+    }
+}
+
+impl PartialEq<str> for Signature {
+    fn eq(&self, other: &str) -> bool {
+        Self::parse_name(other) == Some(self.0)
+    }
+}
+
+impl PartialEq<&str> for Signature {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<String> for Signature {
+    fn eq(&self, other: &String) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<[u8; 4]> for Signature {
+    fn eq(&self, other: &[u8; 4]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<&[u8; 4]> for Signature {
+    fn eq(&self, other: &&[u8; 4]) -> bool {
+        self.0 == **other
+    }
+}
+
+impl serde::Serialize for Signature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Signature {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// 记录或组在源文件中的字节范围（含 24 字节头部）
+///
+/// 由 `Plugin::load()` 在解析时记录，写入时未修改的组直接从内存映射复制。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// 源文件标识（每次加载文件分配一个新值，用于区分来自其他插件的记录）
+    pub source_id: u64,
+    /// 在源文件中的起始偏移
+    pub offset: u64,
+    /// 字节长度
+    pub len: u64,
+}
+
+impl SourceSpan {
+    /// 分配新的源文件标识
+    pub(crate) fn next_source_id() -> u64 {
+        static NEXT_SOURCE_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+        NEXT_SOURCE_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// 共享字节数据的底层存储（内存映射文件、整个文件缓冲区或解压后的记录数据）
+pub type ByteSource = std::sync::Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// 记录与子记录的数据
+///
+/// 从文件解析的数据是底层存储中的一段切片，克隆只增加引用计数；
+/// 新建或修改后的数据独占一个 `Vec<u8>`。通过 `Deref` 当作 `&[u8]` 使用。
+#[derive(Clone)]
+pub struct SharedBytes(BytesRepr);
+
+#[derive(Clone)]
+enum BytesRepr {
+    Owned(Vec<u8>),
+    Shared {
+        source: ByteSource,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl SharedBytes {
+    /// 创建空数据
+    pub fn new() -> Self {
+        SharedBytes(BytesRepr::Owned(Vec::new()))
+    }
+
+    /// 引用底层存储中的一段数据（不复制）
+    ///
+    /// # Panics
+    /// 范围超出底层存储时 panic
+    pub fn from_source(source: ByteSource, range: std::ops::Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= (*source).as_ref().len(),
+            "SharedBytes 范围越界: {:?}",
+            range
+        );
+        SharedBytes(BytesRepr::Shared { source, start: range.start, end: range.end })
+    }
+
+    /// 取子范围（共享数据不复制，独占数据复制）
+    ///
+    /// # Panics
+    /// 范围超出数据长度时 panic
+    pub fn slice(&self, range: std::ops::Range<usize>) -> Self {
+        match &self.0 {
+            BytesRepr::Owned(data) => SharedBytes(BytesRepr::Owned(data[range].to_vec())),
+            BytesRepr::Shared { source, start, end } => {
+                assert!(range.start <= range.end && start + range.end <= *end, "SharedBytes 范围越界: {:?}", range);
+                SharedBytes(BytesRepr::Shared {
+                    source: source.clone(),
+                    start: start + range.start,
+                    end: start + range.end,
+                })
+            }
+        }
+    }
+
+    /// 转为共享存储，之后的 `slice` 和克隆不再复制数据
+    pub fn into_shared(self) -> Self {
+        match self.0 {
+            BytesRepr::Owned(data) => {
+                let end = data.len();
+                SharedBytes(BytesRepr::Shared { source: std::sync::Arc::new(data), start: 0, end })
+            }
+            shared => SharedBytes(shared),
+        }
+    }
+
+    /// 是否引用共享存储
+    pub fn is_shared(&self) -> bool {
+        matches!(self.0, BytesRepr::Shared { .. })
+    }
+
+    /// 获取可修改的数据（共享数据先复制为独占数据）
+    pub fn to_mut(&mut self) -> &mut Vec<u8> {
+        if let BytesRepr::Shared { .. } = self.0 {
+            self.0 = BytesRepr::Owned(self.as_slice().to_vec());
+        }
+        match &mut self.0 {
+            BytesRepr::Owned(data) => data,
+            BytesRepr::Shared { .. } => unreachable!(),
+        }
+    }
+
+    /// 转为 `Vec<u8>`（共享数据会被复制）
+    pub fn into_vec(self) -> Vec<u8> {
+        match self.0 {
+            BytesRepr::Owned(data) => data,
+            shared => SharedBytes(shared).as_slice().to_vec(),
+        }
+    }
+
+    /// 获取数据切片
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.0 {
+            BytesRepr::Owned(data) => data,
+            BytesRepr::Shared { source, start, end } => &(**source).as_ref()[*start..*end],
+        }
+    }
+}
+
+impl Default for SharedBytes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::ops::Deref for SharedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl std::fmt::Debug for SharedBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl From<Vec<u8>> for SharedBytes {
+    fn from(data: Vec<u8>) -> Self {
+        SharedBytes(BytesRepr::Owned(data))
+    }
+}
+
+impl From<&[u8]> for SharedBytes {
+    fn from(data: &[u8]) -> Self {
+        SharedBytes(BytesRepr::Owned(data.to_vec()))
+    }
+}
+
+impl PartialEq for SharedBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for SharedBytes {}
+
+impl PartialEq<[u8]> for SharedBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl PartialEq<&[u8]> for SharedBytes {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl PartialEq<Vec<u8>> for SharedBytes {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for SharedBytes {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+/// 解析时的数据来源（游标所读的数据即 `bytes` 的前缀）
+pub(crate) struct ParseSource {
+    /// 源文件标识（仅内存映射文件有值，用于增量保存）
+    pub(crate) source_id: Option<u64>,
+    /// 底层存储
+    pub(crate) bytes: ByteSource,
+    /// 是否延迟解压和拆分子记录
+    pub(crate) lazy: bool,
+    /// 只解析这些类型的记录（`None` 表示全部）
+    pub(crate) record_types: Option<std::collections::HashSet<Signature>>,
+}
+
+impl ParseSource {
+    /// 完整解析（不过滤、不延迟）
+    pub(crate) fn new(source_id: Option<u64>, bytes: ByteSource) -> Self {
+        ParseSource { source_id, bytes, lazy: false, record_types: None }
+    }
+
+    /// 是否需要解析该类型的记录
+    pub(crate) fn wants_record(&self, record_type: Signature) -> bool {
+        self.record_types.as_ref().is_none_or(|types| types.contains(&record_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_conversions() {
+        const WEAP: Signature = Signature::from_name("WEAP");
+        assert_eq!(WEAP, Signature::new(*b"WEAP"));
+        assert_eq!(WEAP, "WEAP");
+        assert_ne!(WEAP, "WEAPON");
+        assert_eq!(WEAP.to_string(), "WEAP");
+        assert_eq!("NPC_".parse::<Signature>().unwrap(), Signature::new(*b"NPC_"));
+        assert!("NPC".parse::<Signature>().is_err());
+
+        // 非 ASCII 字节经显示/序列化后原样还原
+        let odd = Signature::new([0x41, 0xE9, 0x00, 0xFF]);
+        assert_eq!(odd.to_string().parse::<Signature>().unwrap(), odd);
+        let json = serde_json::to_string(&odd).unwrap();
+        assert_eq!(serde_json::from_str::<Signature>(&json).unwrap(), odd);
+        assert_eq!(serde_json::to_string(&WEAP).unwrap(), "\"WEAP\"");
+    }
+
+    #[test]
+    #[should_panic(expected = "签名必须恰好是 4 个字节")]
+    fn test_signature_from_name_rejects_invalid_length() {
+        Signature::from_name("WEA");
+    }
+}
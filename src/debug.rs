@@ -1,3 +1,4 @@
+use crate::datatypes::Signature;
 use crate::plugin::Plugin;
 use crate::record::Record;
 use crate::group::{Group, GroupChild};
@@ -64,7 +65,7 @@ impl EspDebugger {
         
         writeln!(output, "{}GRUP {{", prefix)?;
         writeln!(output, "{}  大小: {} bytes", prefix, group.size)?;
-        writeln!(output, "{}  标签: {:?} ('{}')", prefix, group.label, String::from_utf8_lossy(&group.label))?;
+        writeln!(output, "{}  标签: {:?} ('{}')", prefix, group.label.as_bytes(), group.label)?;
         writeln!(output, "{}  类型: {:?} ({})", prefix, group.group_type, group.group_type.to_i32())?;
         writeln!(output, "{}  时间戳: {}", prefix, group.timestamp)?;
         writeln!(output, "{}  版本控制: {}", prefix, group.version_control_info)?;
@@ -92,7 +93,7 @@ impl EspDebugger {
         let prefix = "  ".repeat(indent);
         
         writeln!(output, "{}{} {{", prefix, record.record_type)?;
-        writeln!(output, "{}  原始类型字节: {:?}", prefix, record.record_type_bytes)?;
+        writeln!(output, "{}  原始类型字节: {:?}", prefix, record.record_type.as_bytes())?;
         writeln!(output, "{}  数据大小: {} bytes", prefix, record.data_size)?;
         writeln!(output, "{}  标志位: 0x{:08X}", prefix, record.flags)?;
         writeln!(output, "{}  FormID: 0x{:08X}", prefix, record.form_id)?;
@@ -100,9 +101,9 @@ impl EspDebugger {
         writeln!(output, "{}  版本控制: {}", prefix, record.version_control_info)?;
         writeln!(output, "{}  内部版本: {}", prefix, record.internal_version)?;
         writeln!(output, "{}  未知字段: {}", prefix, record.unknown)?;
-        writeln!(output, "{}  子记录数: {}", prefix, record.subrecords.len())?;
+        writeln!(output, "{}  子记录数: {}", prefix, record.subrecords().len())?;
         
-        let total_subrecord_size = Self::calculate_subrecord_size(&record.subrecords);
+        let total_subrecord_size = Self::calculate_subrecord_size(record.subrecords());
         writeln!(output, "{}  计算的子记录总大小: {} bytes", prefix, total_subrecord_size)?;
         
         if total_subrecord_size != record.data_size as usize {
@@ -110,7 +111,7 @@ impl EspDebugger {
                 (total_subrecord_size as i32) - (record.data_size as i32))?;
         }
         
-        for (i, subrecord) in record.subrecords.iter().enumerate() {
+        for (i, subrecord) in record.subrecords().iter().enumerate() {
             writeln!(output, "{}  子记录 {}:", prefix, i)?;
             Self::dump_subrecord(subrecord, output, indent + 2)?;
         }
@@ -131,7 +132,7 @@ impl EspDebugger {
         let prefix = "  ".repeat(indent);
         
         writeln!(output, "{}{} {{", prefix, subrecord.record_type)?;
-        writeln!(output, "{}  原始类型字节: {:?}", prefix, subrecord.record_type_bytes)?;
+        writeln!(output, "{}  原始类型字节: {:?}", prefix, subrecord.record_type.as_bytes())?;
         writeln!(output, "{}  大小: {} bytes", prefix, subrecord.size)?;
         writeln!(output, "{}  实际数据长度: {} bytes", prefix, subrecord.data.len())?;
         
@@ -157,7 +158,7 @@ impl EspDebugger {
                 hex_data.join(" "), 
                 if subrecord.data.len() > 32 { "..." } else { "" })?;
                 
-            if Self::is_likely_string_subrecord(&subrecord.record_type) {
+            if Self::is_likely_string_subrecord(subrecord.record_type) {
                 let text_content = String::from_utf8_lossy(&subrecord.data);
                 let clean_text = text_content.trim_end_matches('\0');
                 if !clean_text.is_empty() {
@@ -169,9 +170,9 @@ impl EspDebugger {
     }
     
     /// 判断是否可能是字符串类型的子记录
-    fn is_likely_string_subrecord(record_type: &str) -> bool {
-        matches!(record_type, "EDID" | "FULL" | "DESC" | "FNAM" | "DNAM" | "NNAM" | 
-                              "CNAM" | "NAM1" | "RNAM" | "ITXT" | "SHRT")
+    fn is_likely_string_subrecord(record_type: Signature) -> bool {
+        matches!(record_type.as_bytes(), b"EDID" | b"FULL" | b"DESC" | b"FNAM" | b"DNAM" | b"NNAM" |
+                                         b"CNAM" | b"NAM1" | b"RNAM" | b"ITXT" | b"SHRT")
     }
     
     /// 对比两个文件的结构
@@ -261,8 +262,8 @@ impl EspDebugger {
         if original.flags != rebuilt.flags {
             writeln!(output, "  ⚠ {} 标志位不匹配: 0x{:08X} vs 0x{:08X}", name, original.flags, rebuilt.flags)?;
         }
-        if original.subrecords.len() != rebuilt.subrecords.len() {
-            writeln!(output, "  ⚠ {} 子记录数量不匹配: {} vs {}", name, original.subrecords.len(), rebuilt.subrecords.len())?;
+        if original.subrecords().len() != rebuilt.subrecords().len() {
+            writeln!(output, "  ⚠ {} 子记录数量不匹配: {} vs {}", name, original.subrecords().len(), rebuilt.subrecords().len())?;
         }
         Ok(())
     }
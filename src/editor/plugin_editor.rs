@@ -3,9 +3,9 @@
 /// 提供有状态的插件编辑接口，支持批量修改和延迟保存。
 /// 遵循"修改-保存分离"原则，所有修改操作仅在内存中进行。
 use std::path::Path;
-use crate::plugin::Plugin;
+use crate::plugin::{Plugin, WriteOptions};
 use crate::string_types::ExtractedString;
-use crate::io::{EspWriter, RawEspData};
+use crate::io::EspWriter;
 use super::delta::{TranslationDelta, RecordChange, RecordId};
 
 /// 插件编辑器 - 管理插件的修改状态
@@ -172,6 +172,8 @@ impl PluginEditor {
 
     /// 保存到文件（需要显式调用）
     ///
+    /// 插件数据以流方式交给 writer，支持流式写入的 writer 无需在内存中构建完整文件。
+    ///
     /// # 参数
     /// * `writer` - ESP 文件写入器
     /// * `path` - 目标文件路径
@@ -180,15 +182,10 @@ impl PluginEditor {
         writer: &dyn EspWriter,
         path: &Path,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // 使用 Plugin 的写入方法生成数据
-        let mut output = Vec::new();
-        self.plugin.write_to_buffer(&mut output)?;
-
-        // 通过 writer 写入
-        let data = RawEspData { bytes: output };
-        writer.write(&data, path)?;
-
-        Ok(())
+        writer.write_with(path, &mut |output| {
+            self.plugin.write_to(output, &WriteOptions::default())?;
+            Ok(())
+        })
     }
 
     /// 保存到原路径
@@ -233,24 +230,6 @@ impl PluginEditor {
     }
 }
 
-// 扩展 Plugin 以支持 PluginEditor
-// 这些方法是临时的，用于支持 PluginEditor，后续重构会移除或改进
-impl Plugin {
-    /// 将数据写入缓冲区（内部辅助方法）
-    pub(crate) fn write_to_buffer(
-        &self,
-        output: &mut Vec<u8>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.write_record(&self.header, output)?;
-
-        for group in &self.groups {
-            self.write_group(group, output)?;
-        }
-
-        Ok(())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     // use super::*;
@@ -0,0 +1,181 @@
+//! FormID 字段定义模块
+//!
+//! 描述各记录类型中哪些子记录包含 FormID，用于主文件索引重映射、引用检查等操作。
+//! 定义数据来自内置的 formid_fields.json，`"*"` 条目适用于所有记录类型。
+//! `"unsupported"` 标记可能包含 FormID、但位置取决于其他字段的子记录（如 CTDA、VMAD），
+//! 这些字段不会被重写，修改主文件列表前需要检查。空偏移数组 `[]` 表示确认不含 FormID 的字段；
+//! 未列出的记录类型视为未知，其字段（`"*"` 中已定义的除外）同样按无法重写处理。
+
+use crate::datatypes::Signature;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// 子记录中 FormID 的布局
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormIdLayout {
+    /// 整个子记录是 FormID 数组（如 KWDA）
+    Array,
+    /// 固定字节偏移处的 FormID
+    Offsets(Vec<usize>),
+    /// 可能包含 FormID，但布局不固定，无法定位和重写
+    Unsupported,
+}
+
+impl FormIdLayout {
+    /// 计算给定数据长度下所有 FormID 的字节偏移（越界的偏移会被忽略）
+    pub fn offsets(&self, data_len: usize) -> Vec<usize> {
+        match self {
+            FormIdLayout::Array => (0..data_len / 4).map(|i| i * 4).collect(),
+            FormIdLayout::Offsets(offsets) => offsets
+                .iter()
+                .copied()
+                .filter(|offset| offset + 4 <= data_len)
+                .collect(),
+            FormIdLayout::Unsupported => Vec::new(),
+        }
+    }
+}
+
+/// JSON 中的原始布局表示："array"、"unsupported" 或偏移数组
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawLayout {
+    Offsets(Vec<usize>),
+    Keyword(String),
+}
+
+/// FormID 字段定义表
+#[derive(Debug, Clone, Default)]
+pub struct FormIdFields {
+    /// 记录类型 -> 子记录类型 -> 布局
+    fields: HashMap<Signature, HashMap<Signature, FormIdLayout>>,
+    /// 适用于所有记录类型的定义（`"*"` 条目）
+    common: HashMap<Signature, FormIdLayout>,
+}
+
+impl FormIdFields {
+    /// 从 JSON 字符串加载定义
+    pub fn from_json(json_data: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let raw: HashMap<String, HashMap<String, RawLayout>> = serde_json::from_str(json_data)?;
+
+        let mut fields = HashMap::new();
+        let mut common = HashMap::new();
+        for (record_type, subrecords) in raw {
+            let mut layouts = HashMap::new();
+            for (subrecord_type, layout) in subrecords {
+                let layout = match layout {
+                    RawLayout::Offsets(offsets) => FormIdLayout::Offsets(offsets),
+                    RawLayout::Keyword(keyword) if keyword == "array" => FormIdLayout::Array,
+                    RawLayout::Keyword(keyword) if keyword == "unsupported" => FormIdLayout::Unsupported,
+                    RawLayout::Keyword(keyword) => {
+                        return Err(format!(
+                            "无效的 FormID 布局 {}.{}: {}",
+                            record_type, subrecord_type, keyword
+                        ).into());
+                    }
+                };
+                layouts.insert(subrecord_type.parse::<Signature>()?, layout);
+            }
+            if record_type == "*" {
+                common = layouts;
+            } else {
+                fields.insert(record_type.parse::<Signature>()?, layouts);
+            }
+        }
+
+        Ok(FormIdFields { fields, common })
+    }
+
+    /// 加载内置的 formid_fields.json
+    pub fn from_embedded_data() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_json(include_str!("../data/formid_fields.json"))
+    }
+
+    /// 获取全局共享的内置定义
+    pub fn embedded() -> &'static FormIdFields {
+        static FIELDS: OnceLock<FormIdFields> = OnceLock::new();
+        FIELDS.get_or_init(|| {
+            Self::from_embedded_data().expect("内置 formid_fields.json 格式错误")
+        })
+    }
+
+    /// 查询子记录的 FormID 布局
+    ///
+    /// 优先使用记录类型专属定义，其次使用 `"*"` 通用定义。
+    pub fn get_layout(&self, record_type: Signature, subrecord_type: Signature) -> Option<&FormIdLayout> {
+        self.fields
+            .get(&record_type)
+            .and_then(|layouts| layouts.get(&subrecord_type))
+            .or_else(|| self.common.get(&subrecord_type))
+    }
+
+    /// 计算子记录数据中所有 FormID 的字节偏移
+    pub fn form_id_offsets(&self, record_type: Signature, subrecord_type: Signature, data_len: usize) -> Vec<usize> {
+        self.get_layout(record_type, subrecord_type)
+            .map(|layout| layout.offsets(data_len))
+            .unwrap_or_default()
+    }
+
+    /// 子记录是否可能包含无法定位的 FormID
+    ///
+    /// 标记为 `"unsupported"` 的字段，以及定义表中未列出的记录类型里没有通用定义的字段，都返回 `true`。
+    pub fn is_unsupported(&self, record_type: Signature, subrecord_type: Signature) -> bool {
+        match self.get_layout(record_type, subrecord_type) {
+            Some(layout) => *layout == FormIdLayout::Unsupported,
+            None => !self.fields.contains_key(&record_type),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_fields() {
+        let fields = FormIdFields::from_embedded_data().unwrap();
+
+        // 通用定义
+        assert_eq!(fields.get_layout(Signature::from_name("WEAP"), Signature::from_name("KWDA")), Some(&FormIdLayout::Array));
+        assert_eq!(fields.get_layout(Signature::from_name("NPC_"), Signature::from_name("CNTO")), Some(&FormIdLayout::Offsets(vec![0])));
+        // 专属定义
+        assert_eq!(fields.get_layout(Signature::from_name("REFR"), Signature::from_name("NAME")), Some(&FormIdLayout::Offsets(vec![0])));
+        // 非 FormID 字段
+        assert!(fields.form_id_offsets(Signature::from_name("WEAP"), Signature::from_name("FULL"), 4).is_empty());
+        assert!(!fields.is_unsupported(Signature::from_name("WEAP"), Signature::from_name("FULL")));
+        assert!(fields.get_layout(Signature::from_name("MISC"), Signature::from_name("NAME")).is_none());
+        assert!(!fields.is_unsupported(Signature::from_name("MISC"), Signature::from_name("NAME")));
+        // 配方、装备组和法术
+        assert_eq!(fields.form_id_offsets(Signature::from_name("COBJ"), Signature::from_name("CNAM"), 4), vec![0]);
+        assert_eq!(fields.form_id_offsets(Signature::from_name("OTFT"), Signature::from_name("INAM"), 8), vec![0, 4]);
+        assert_eq!(fields.form_id_offsets(Signature::from_name("ENCH"), Signature::from_name("ENIT"), 36), vec![28, 32]);
+        assert!(!fields.is_unsupported(Signature::from_name("GMST"), Signature::from_name("DATA")));
+        // 对话、任务别名和 AI 包
+        assert_eq!(fields.form_id_offsets(Signature::from_name("INFO"), Signature::from_name("TRDT"), 24), vec![16]);
+        assert_eq!(fields.form_id_offsets(Signature::from_name("QUST"), Signature::from_name("ALFC"), 4), vec![0]);
+        assert_eq!(fields.form_id_offsets(Signature::from_name("PACK"), Signature::from_name("PKCU"), 12), vec![4]);
+        // 布局不固定的字段
+        assert!(fields.is_unsupported(Signature::from_name("PERK"), Signature::from_name("CTDA")));
+        assert!(fields.is_unsupported(Signature::from_name("QUST"), Signature::from_name("VMAD")));
+        assert!(fields.is_unsupported(Signature::from_name("PERK"), Signature::from_name("EPFD")));
+        assert!(fields.form_id_offsets(Signature::from_name("INFO"), Signature::from_name("CTDA"), 32).is_empty());
+        assert!(!fields.is_unsupported(Signature::from_name("MISC"), Signature::from_name("DATA")));
+        // 未列出的记录类型：除通用定义外的字段都视为无法重写
+        assert!(fields.is_unsupported(Signature::from_name("RACE"), Signature::from_name("DATA")));
+        assert!(fields.is_unsupported(Signature::from_name("NAVM"), Signature::from_name("NVNM")));
+        assert!(!fields.is_unsupported(Signature::from_name("RACE"), Signature::from_name("EDID")));
+        assert!(!fields.is_unsupported(Signature::from_name("RACE"), Signature::from_name("KWDA")));
+    }
+
+    #[test]
+    fn test_layout_offsets() {
+        assert_eq!(FormIdLayout::Array.offsets(12), vec![0, 4, 8]);
+        assert_eq!(FormIdLayout::Offsets(vec![0, 4]).offsets(6), vec![0]);
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        assert!(FormIdFields::from_json(r#"{"WEAP": {"KWDA": "list"}}"#).is_err());
+    }
+}
@@ -1,9 +1,17 @@
-use crate::datatypes::{read_u16, read_u32, read_i32};
+use crate::datatypes::{read_u16, read_u32, read_i32, ParseSource, Signature, SourceSpan};
 use crate::record::Record;
+use rayon::prelude::*;
+use std::collections::HashMap;
 use std::io::{Read, Cursor};
 
+/// 组大小超过此值（字节）时，子元素分块并行解析
+const PARALLEL_GROUP_THRESHOLD: u32 = 1024 * 1024;
+
+/// 并行解析时每块的目标大小（字节）
+const PARALLEL_CHUNK_SIZE: u64 = 256 * 1024;
+
 /// 组类型 (映射自 Python 版本的 GroupType)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GroupType {
     /// 顶级记录组 (Type 0)
     Normal,
@@ -66,13 +74,31 @@ impl From<i32> for GroupType {
     }
 }
 
+/// 顶级组的标准顺序（Skyrim SE）
+///
+/// 新建或插入顶级组时按此顺序排列，未列出的类型追加到末尾。
+pub const TOP_LEVEL_GROUP_ORDER: &[&str] = &[
+    "GMST", "KYWD", "LCRT", "AACT", "TXST", "GLOB", "CLAS", "FACT", "HDPT", "HAIR",
+    "EYES", "RACE", "SOUN", "ASPC", "MGEF", "SCPT", "LTEX", "ENCH", "SPEL", "SCRL",
+    "ACTI", "TACT", "ARMO", "BOOK", "CONT", "DOOR", "INGR", "LIGH", "MISC", "APPA",
+    "STAT", "SCOL", "MSTT", "PWAT", "GRAS", "TREE", "CLDC", "FLOR", "FURN", "WEAP",
+    "AMMO", "NPC_", "LVLN", "KEYM", "ALCH", "IDLM", "COBJ", "PROJ", "HAZD", "SLGM",
+    "LVLI", "WTHR", "CLMT", "SPGD", "RFCT", "REGN", "NAVI", "CELL", "WRLD", "DIAL",
+    "QUST", "IDLE", "PACK", "CSTY", "LSCR", "LVSP", "ANIO", "WATR", "EFSH", "EXPL",
+    "DEBR", "IMGS", "IMAD", "FLST", "PERK", "BPTD", "ADDN", "AVIF", "CAMS", "CPTH",
+    "VTYP", "MATT", "IPCT", "IPDS", "ARMA", "ECZN", "LCTN", "MESG", "RGDL", "DOBJ",
+    "LGTM", "MUSC", "FSTP", "FSTS", "SMBN", "SMQN", "SMEN", "DLBR", "MUST", "DLVW",
+    "WOOP", "SHOU", "EQUP", "RELA", "SCEN", "ASTP", "OTFT", "ARTO", "MATO", "MOVT",
+    "SNDR", "DUAL", "SNCT", "SOPM", "COLL", "CLFM", "REVB", "LENS", "VOLI",
+];
+
 /// 组结构
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Group {
     /// 组大小(包含头部24字节)
     pub size: u32,
-    /// 标签
-    pub label: [u8; 4],
+    /// 标签（顶级组为记录类型，其他组为 FormID 或坐标的原始字节）
+    pub label: Signature,
     /// 组类型
     pub group_type: GroupType,
     /// 时间戳
@@ -83,10 +109,12 @@ pub struct Group {
     pub unknown: u32,
     /// 子元素
     pub children: Vec<GroupChild>,
+    /// 在源文件中的字节范围（仅从内存映射加载的组有值）
+    pub source_span: Option<SourceSpan>,
 }
 
 /// 组子元素
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum GroupChild {
     /// 子组
     Group(Box<Group>),
@@ -95,8 +123,38 @@ pub enum GroupChild {
 }
 
 impl Group {
+    /// 创建空组
+    ///
+    /// # 参数
+    /// * `label` - 组标签（顶级组为记录类型，其他组为 FormID 或坐标）
+    /// * `group_type` - 组类型
+    pub fn new(label: impl Into<Signature>, group_type: GroupType) -> Self {
+        Group {
+            size: 24,
+            label: label.into(),
+            group_type,
+            timestamp: 0,
+            version_control_info: 0,
+            unknown: 0,
+            children: Vec::new(),
+            source_span: None,
+        }
+    }
+
     /// 解析组
     pub fn parse(cursor: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_in_source(cursor, None)
+    }
+
+    /// 从共享数据源解析组（见 `Record::parse_in_source`）
+    ///
+    /// `cursor` 所读的数据必须是 `source.bytes` 的前缀。
+    pub(crate) fn parse_in_source(
+        cursor: &mut Cursor<&[u8]>,
+        source: Option<&ParseSource>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let start = cursor.position();
+
         // 检查是否有足够的数据读取头部
         if cursor.position() + 24 > cursor.get_ref().len() as u64 {
             return Err("Insufficient data for group header".into());
@@ -122,8 +180,9 @@ impl Group {
             return Err(format!("组大小太小: {} bytes (最小应为24字节)", size).into());
         }
         
-        let mut label = [0u8; 4];
-        cursor.read_exact(&mut label)?;
+        let mut label_bytes = [0u8; 4];
+        cursor.read_exact(&mut label_bytes)?;
+        let label = Signature(label_bytes);
         let group_type = GroupType::from(read_i32(cursor)?);
         let timestamp = read_u16(cursor)?;
         let version_control_info = read_u16(cursor)?;
@@ -141,9 +200,43 @@ impl Group {
         let data_start = cursor.position();
         let data_end = data_start + data_size as u64;
         
-        // 解析子元素
+        // 解析子元素：大组先扫描直接子元素的边界，分块并行解析后按顺序拼接
+        let data = *cursor.get_ref();
+        let children = if size >= PARALLEL_GROUP_THRESHOLD {
+            Self::parse_children_parallel(data, data_start, data_end, source)?
+        } else {
+            Self::parse_children(data, data_start, data_end, source)?
+        };
+        cursor.set_position(data_end);
+        
+        Ok(Group {
+            size,
+            label,
+            group_type,
+            timestamp,
+            version_control_info,
+            unknown,
+            children,
+            source_span: source.and_then(|source| source.source_id).map(|source_id| SourceSpan {
+                source_id,
+                offset: start,
+                len: size as u64,
+            }),
+        })
+    }
+    
+    /// 顺序解析 `data[start..end]` 范围内的子元素
+    fn parse_children(
+        data: &[u8],
+        start: u64,
+        end: u64,
+        source: Option<&ParseSource>,
+    ) -> Result<Vec<GroupChild>, Box<dyn std::error::Error>> {
+        let mut cursor = Cursor::new(&data[..end as usize]);
+        cursor.set_position(start);
+
         let mut children = Vec::new();
-        while cursor.position() < data_end {
+        while cursor.position() < end {
             // 预读取4字节判断类型
             let peek_pos = cursor.position();
             let mut peek_bytes = [0u8; 4];
@@ -152,29 +245,92 @@ impl Group {
             
             if &peek_bytes == b"GRUP" {
                 // 是子组
-                let child_group = Group::parse(cursor)?;
-                children.push(GroupChild::Group(Box::new(child_group)));
+                let child_group = Group::parse_in_source(&mut cursor, source)?;
+                // 按记录类型过滤时，不保留没有任何匹配记录的子组（如单元的引用子组）
+                let filtered = source.is_some_and(|source| source.record_types.is_some());
+                if !(filtered && child_group.children.is_empty()) {
+                    children.push(GroupChild::Group(Box::new(child_group)));
+                }
+            } else if source.is_some_and(|source| !source.wants_record(Signature(peek_bytes))) {
+                // 不需要的记录：只读取头部中的大小，跳过数据
+                cursor.set_position(peek_pos + 4);
+                let record_size = 24 + read_u32(&mut cursor)? as u64;
+                if peek_pos + record_size > end {
+                    return Err(format!("记录 {} 超出组范围", String::from_utf8_lossy(&peek_bytes)).into());
+                }
+                cursor.set_position(peek_pos + record_size);
             } else {
                 // 是记录
-                let record = Record::parse(cursor)?;
+                let record = Record::parse_in_source(&mut cursor, source)?;
                 children.push(GroupChild::Record(record));
             }
         }
-        
-        Ok(Group {
-            size,
-            label,
-            group_type,
-            timestamp,
-            version_control_info,
-            unknown,
-            children,
-        })
+
+        Ok(children)
+    }
+
+    /// 并行解析 `data[start..end]` 范围内的子元素
+    ///
+    /// 先按头部中的大小扫描直接子元素的边界，把相邻子元素合并为约
+    /// `PARALLEL_CHUNK_SIZE` 字节的块，各块独立解析后按原顺序拼接。
+    /// 子组本身足够大时会在 `parse_in_source` 中继续拆分，
+    /// 因此世界空间子项、单元块/子块、单元子项各层都能并行。
+    fn parse_children_parallel(
+        data: &[u8],
+        start: u64,
+        end: u64,
+        source: Option<&ParseSource>,
+    ) -> Result<Vec<GroupChild>, Box<dyn std::error::Error>> {
+        let chunks = Self::scan_child_chunks(data, start, end)?;
+        if chunks.len() < 2 {
+            return Self::parse_children(data, start, end, source);
+        }
+
+        let parsed: Result<Vec<Vec<GroupChild>>, String> = chunks
+            .par_iter()
+            .map(|&(chunk_start, chunk_end)| {
+                Self::parse_children(data, chunk_start, chunk_end, source).map_err(|e| e.to_string())
+            })
+            .collect();
+
+        Ok(parsed?.into_iter().flatten().collect())
+    }
+
+    /// 扫描直接子元素的边界，返回按顺序排列的块范围
+    fn scan_child_chunks(data: &[u8], start: u64, end: u64) -> Result<Vec<(u64, u64)>, Box<dyn std::error::Error>> {
+        let mut chunks = Vec::new();
+        let mut chunk_start = start;
+        let mut pos = start;
+
+        while pos < end {
+            let header = data.get(pos as usize..pos as usize + 8)
+                .filter(|_| pos + 8 <= end)
+                .ok_or_else(|| format!("在位置 {} 子元素头部不完整", pos))?;
+            let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+            // 组大小包含头部，记录大小只是数据部分
+            let child_size = if &header[..4] == b"GRUP" { size } else { 24 + size };
+            if child_size < 24 || pos + child_size > end {
+                return Err(format!("在位置 {} 子元素 {} 超出组范围",
+                    pos, String::from_utf8_lossy(&header[..4])).into());
+            }
+
+            pos += child_size;
+            if pos - chunk_start >= PARALLEL_CHUNK_SIZE {
+                chunks.push((chunk_start, pos));
+                chunk_start = pos;
+            }
+        }
+
+        if chunk_start < end {
+            chunks.push((chunk_start, end));
+        }
+
+        Ok(chunks)
     }
     
     /// 获取组标签
-    pub fn get_label(&self) -> &[u8; 4] {
-        &self.label
+    pub fn get_label(&self) -> Signature {
+        self.label
     }
     
     /// 获取组类型
@@ -203,8 +359,227 @@ impl Group {
         }
     }
     
+    /// 按 FormID 递归查找记录的可变引用
+    pub fn find_record_mut(&mut self, form_id: u32) -> Option<&mut Record> {
+        for child in &mut self.children {
+            match child {
+                GroupChild::Group(group) => {
+                    if let Some(record) = group.find_record_mut(form_id) {
+                        return Some(record);
+                    }
+                }
+                GroupChild::Record(record) => {
+                    if record.form_id == form_id {
+                        return Some(record);
+                    }
+                }
+            }
+        }
+        None
+    }
+
     /// 获取组标签字符串
     pub fn get_label_string(&self) -> String {
-        String::from_utf8_lossy(&self.label).into_owned()
+        self.label.to_string()
+    }
+
+    /// 获取父记录的 FormID
+    ///
+    /// 世界空间/单元/对话主题子组的标签是其父记录（WRLD/CELL/DIAL）的 FormID，
+    /// 这类子组在文件中紧跟父记录之后。其他组返回 `None`。
+    pub fn parent_form_id(&self) -> Option<u32> {
+        match self.group_type {
+            GroupType::WorldChildren | GroupType::CellChildren | GroupType::TopicChildren => {
+                Some(self.label.to_u32())
+            }
+            _ => None,
+        }
+    }
+
+    /// 合并子元素
+    ///
+    /// 记录按 FormID 匹配，已存在时原位替换；子组按标签和类型匹配并递归合并，
+    /// 不存在时插入（记录子组插入到父记录之后，其他追加到末尾）。
+    ///
+    /// # 返回
+    /// 被替换的旧记录
+    pub fn merge_child(&mut self, child: GroupChild) -> Vec<Record> {
+        self.merge_children(vec![child])
+    }
+
+    /// 批量合并子元素
+    ///
+    /// 匹配规则同 `merge_child`，但每个目标组只建立一次索引。
+    ///
+    /// # 返回
+    /// 被替换的旧记录
+    pub fn merge_children(&mut self, children: Vec<GroupChild>) -> Vec<Record> {
+        let mut replaced = Vec::new();
+        self.merge_children_into(children, &mut replaced);
+        replaced
+    }
+
+    /// 按条件保留记录，并移除因此变空的子组
+    ///
+    /// 子组中仍有内容时，其父记录（WRLD/CELL/DIAL）会被一并保留，以维持组结构有效。
+    pub fn retain_records<F: FnMut(&Record) -> bool>(&mut self, mut keep: F) {
+        self.retain_records_inner(&mut keep);
+    }
+
+    /// 递归保留记录
+    fn retain_records_inner(&mut self, keep: &mut dyn FnMut(&Record) -> bool) {
+        for child in &mut self.children {
+            if let GroupChild::Group(group) = child {
+                group.retain_records_inner(keep);
+            }
+        }
+        self.children.retain(|child| !matches!(child, GroupChild::Group(g) if g.children.is_empty()));
+
+        let parents: std::collections::HashSet<u32> = self.children.iter()
+            .filter_map(|child| match child {
+                GroupChild::Group(group) => group.parent_form_id(),
+                GroupChild::Record(_) => None,
+            })
+            .collect();
+        self.children.retain(|child| match child {
+            GroupChild::Record(record) => parents.contains(&record.form_id) || keep(record),
+            GroupChild::Group(_) => true,
+        });
+    }
+
+    /// 按条件复制组（只复制满足条件的记录及其父组链）
+    ///
+    /// 与 `retain_records` 规则相同，但不需要先克隆整个组，适合从大型组中提取少量记录。
+    /// 没有任何内容时返回 `None`。
+    pub fn clone_filtered<F: FnMut(&Record) -> bool>(&self, mut keep: F) -> Option<Group> {
+        self.clone_filtered_inner(&mut keep)
+    }
+
+    /// 递归按条件复制组
+    fn clone_filtered_inner(&self, keep: &mut dyn FnMut(&Record) -> bool) -> Option<Group> {
+        let nested: Vec<Option<Group>> = self.children.iter()
+            .map(|child| match child {
+                GroupChild::Group(group) => group.clone_filtered_inner(keep),
+                GroupChild::Record(_) => None,
+            })
+            .collect();
+
+        let parents: std::collections::HashSet<u32> = nested.iter()
+            .flatten()
+            .filter_map(|group| group.parent_form_id())
+            .collect();
+
+        let mut children = Vec::new();
+        for (child, nested) in self.children.iter().zip(nested) {
+            match child {
+                GroupChild::Group(_) => {
+                    if let Some(group) = nested {
+                        children.push(GroupChild::Group(Box::new(group)));
+                    }
+                }
+                GroupChild::Record(record) => {
+                    if parents.contains(&record.form_id) || keep(record) {
+                        children.push(GroupChild::Record(record.clone()));
+                    }
+                }
+            }
+        }
+
+        if children.is_empty() {
+            return None;
+        }
+
+        Some(Group {
+            size: self.size,
+            label: self.label,
+            group_type: self.group_type.clone(),
+            timestamp: self.timestamp,
+            version_control_info: self.version_control_info,
+            unknown: self.unknown,
+            children,
+            source_span: None,
+        })
+    }
+
+    /// 批量合并子元素，收集被替换的记录
+    ///
+    /// 目标组只建立一次 FormID 和子组索引，避免每个子元素都线性扫描。需要插入到父记录
+    /// 之后的新子组先暂存，最后一次性插入，使已建立的索引位置保持有效。
+    fn merge_children_into(&mut self, children: Vec<GroupChild>, replaced: &mut Vec<Record>) {
+        let mut records: HashMap<u32, usize> = HashMap::new();
+        let mut groups: HashMap<(Signature, GroupType), usize> = HashMap::new();
+        for (index, child) in self.children.iter().enumerate() {
+            match child {
+                GroupChild::Record(r) => {
+                    records.entry(r.form_id).or_insert(index);
+                }
+                GroupChild::Group(g) => {
+                    groups.entry((g.label, g.group_type.clone())).or_insert(index);
+                }
+            }
+        }
+
+        // 待插入到父记录之后的新子组：(父记录位置, 子组)
+        let mut pending: Vec<(usize, Box<Group>)> = Vec::new();
+        let mut pending_keys: HashMap<(Signature, GroupType), usize> = HashMap::new();
+
+        for child in children {
+            match child {
+                GroupChild::Record(record) => match records.get(&record.form_id) {
+                    Some(&index) => {
+                        let old = std::mem::replace(&mut self.children[index], GroupChild::Record(record));
+                        if let GroupChild::Record(old) = old {
+                            replaced.push(old);
+                        }
+                    }
+                    None => {
+                        records.insert(record.form_id, self.children.len());
+                        self.children.push(GroupChild::Record(record));
+                    }
+                },
+                GroupChild::Group(group) => {
+                    let key = (group.label, group.group_type.clone());
+
+                    if let Some(&index) = groups.get(&key) {
+                        if let GroupChild::Group(existing) = &mut self.children[index] {
+                            existing.merge_children_into(group.children, replaced);
+                        }
+                        continue;
+                    }
+                    if let Some(&slot) = pending_keys.get(&key) {
+                        pending[slot].1.merge_children_into(group.children, replaced);
+                        continue;
+                    }
+
+                    let parent_index = group.parent_form_id().and_then(|parent| records.get(&parent).copied());
+                    match parent_index {
+                        Some(index) => {
+                            pending_keys.insert(key, pending.len());
+                            pending.push((index, group));
+                        }
+                        None => {
+                            groups.insert(key, self.children.len());
+                            self.children.push(GroupChild::Group(group));
+                        }
+                    }
+                }
+            }
+        }
+
+        if pending.is_empty() {
+            return;
+        }
+
+        // 稳定排序：同一父记录下的子组保持到达顺序
+        pending.sort_by_key(|(index, _)| *index);
+        let mut pending = pending.into_iter().peekable();
+        let existing = std::mem::take(&mut self.children);
+        self.children.reserve(existing.len() + pending.len());
+        for (index, child) in existing.into_iter().enumerate() {
+            self.children.push(child);
+            while let Some((_, group)) = pending.next_if(|(parent, _)| *parent == index) {
+                self.children.push(GroupChild::Group(group));
+            }
+        }
     }
 } 
\ No newline at end of file
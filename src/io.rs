@@ -23,7 +23,7 @@ pub mod string_file_io;
 
 // === 导出 trait 定义 ===
 pub use traits::{
-    EspReader, EspWriter, RawEspData, StringFileReader, StringFileSetReader, StringFileWriter,
+    EspReader, EspWriteFn, EspWriter, RawEspData, StringFileReader, StringFileSetReader, StringFileWriter,
 };
 
 // === 导出默认实现 ===
@@ -1,8 +1,10 @@
 /// ESP 文件 IO 实现
 ///
 /// 提供基于文件系统的默认 ESP 文件读写实现
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
-use super::traits::{EspReader, EspWriter, RawEspData};
+use super::traits::{EspReader, EspWriteFn, EspWriter, RawEspData};
 
 /// 默认的 ESP 文件读取器（基于 std::fs）
 #[derive(Debug, Clone, Default)]
@@ -26,9 +28,45 @@ impl EspWriter for DefaultEspWriter {
             std::fs::create_dir_all(parent)?;
         }
 
-        std::fs::write(path, &data.bytes)?;
+        write_file_atomically(path, &mut |output| {
+            output.write_all(&data.bytes)?;
+            Ok(())
+        })
+    }
+
+    fn write_with(&self, path: &Path, write_fn: &mut EspWriteFn) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        write_file_atomically(path, write_fn)
+    }
+}
+
+/// 先写入同目录下的临时文件，成功后再替换目标文件
+///
+/// 目标文件可能正被内存映射（覆盖保存从该文件加载的插件），直接截断会破坏映射中的数据。
+/// 写入失败时删除临时文件，目标文件保持不变。
+pub(crate) fn write_file_atomically(path: &Path, write_fn: &mut EspWriteFn) -> Result<(), Box<dyn std::error::Error>> {
+    let file_name = path.file_name().ok_or_else(|| format!("无效的输出路径: {}", path.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        write_fn(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&temp_path, path)?;
         Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
     }
+    result
 }
 
 #[cfg(test)]
@@ -95,4 +133,45 @@ mod tests {
         // 清理
         std::fs::remove_dir_all(temp_dir.join("test_esp_nested")).unwrap();
     }
+
+    #[test]
+    fn test_default_esp_writer_streaming() {
+        let temp_dir = std::env::temp_dir().join("test_esp_streaming");
+        let test_file = temp_dir.join("stream.esp");
+
+        let writer = DefaultEspWriter;
+        writer.write_with(&test_file, &mut |output| {
+            output.write_all(b"TES4")?;
+            output.write_all(&[0, 0, 0, 0])?;
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(std::fs::read(&test_file).unwrap(), b"TES4\x00\x00\x00\x00");
+
+        // 清理
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_with_default_buffers_into_write() {
+        use std::cell::RefCell;
+
+        /// 只实现 write() 的写入器
+        struct CapturingWriter(RefCell<Vec<u8>>);
+
+        impl EspWriter for CapturingWriter {
+            fn write(&self, data: &RawEspData, _path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+                self.0.borrow_mut().extend_from_slice(&data.bytes);
+                Ok(())
+            }
+        }
+
+        let writer = CapturingWriter(RefCell::new(Vec::new()));
+        writer.write_with(Path::new("unused.esp"), &mut |output| {
+            output.write_all(&[1, 2, 3])?;
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(*writer.0.borrow(), vec![1, 2, 3]);
+    }
 }
@@ -2,6 +2,7 @@
 ///
 /// 该模块定义了文件读写的抽象接口，支持依赖注入和测试 mock。
 /// 遵循依赖倒置原则（DIP），面向接口编程。
+use std::io::Write;
 use std::path::Path;
 use crate::string_file::{StringFile, StringFileSet};
 
@@ -39,6 +40,9 @@ pub trait EspReader {
     fn read(&self, path: &Path) -> Result<RawEspData, Box<dyn std::error::Error>>;
 }
 
+/// ESP 数据流写入回调（由调用方提供，将序列化数据写入给定的输出流）
+pub type EspWriteFn<'a> = dyn FnMut(&mut dyn Write) -> Result<(), Box<dyn std::error::Error>> + 'a;
+
 /// ESP 文件写入 trait
 ///
 /// # 职责
@@ -51,6 +55,20 @@ pub trait EspWriter {
     /// * `data` - 要写入的原始数据
     /// * `path` - 目标文件路径
     fn write(&self, data: &RawEspData, path: &Path) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// 以流方式写入 ESP 文件数据
+    ///
+    /// writer 打开输出流（文件、网络连接等）后调用 `write_fn` 写入数据。
+    /// 默认实现先写入内存再调用 `write()`；支持流式输出的 writer 应覆盖此方法。
+    ///
+    /// # 参数
+    /// * `path` - 目标文件路径
+    /// * `write_fn` - 数据写入回调
+    fn write_with(&self, path: &Path, write_fn: &mut EspWriteFn) -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = Vec::new();
+        write_fn(&mut bytes)?;
+        self.write(&RawEspData { bytes }, path)
+    }
 }
 
 /// STRING 文件读取 trait
@@ -69,6 +69,12 @@ pub mod bsa;
 // 字符串路由模块（v0.6.0 新增 - P2.3）
 pub mod string_routes;
 
+// FormID 字段定义模块
+pub mod form_id_fields;
+
+// 记录压缩模块
+pub mod compression;
+
 // 调试模块（仅在debug模式下可用）
 #[cfg(debug_assertions)]
 pub mod debug;
@@ -76,15 +82,23 @@ pub mod debug;
 // === 公共接口导出 ===
 
 // 主要结构体
-pub use plugin::{Plugin, PluginStats};
+pub use plugin::{
+    Plugin, PluginStats, EslAnalysis, EslVerdict, FormIdMapping, FormIdReference, UncoveredField, MasterError,
+    MergeReport, MergeMapEntry, MergeConflict, ForwardReport, PluginBuilder, PluginHeader,
+    WriteOptions, WriteReport, CompressionAction, CompressionDecision, ParseOptions,
+    PluginCache, PluginIndex, FileFingerprint, IndexedRecord, IndexedString, StringValue,
+    StringCheckReport, MissingString, MisplacedString, OrphanString, StringApplyReport,
+    AlignmentReport, AlignedString, AlignMethod,
+};
 pub use record::Record;
-pub use group::{Group, GroupChild, GroupType};
+pub use group::{Group, GroupChild, GroupType, TOP_LEVEL_GROUP_ORDER};
 pub use subrecord::Subrecord;
-pub use string_types::ExtractedString;
-pub use string_file::{StringFile, StringFileType, StringEntry, StringFileSet, StringFileStats};
+pub use string_types::{ExtractedString, MultiLanguageString};
+pub use string_file::{StringFile, StringFileType, StringEntry, StringFileSet, StringFileStats, RebuildOptions, MultiLanguageStringSet,
+    StringSetComparison, StringDiff, PlaceholderMismatch, TermBase, TermBaseOptions, TermEntry};
 
 // 数据类型和工具
-pub use datatypes::{RecordFlags, RawString};
+pub use datatypes::{RecordFlags, RawString, SharedBytes, Signature, SourceSpan};
 pub use utils::{is_valid_string, EspError};
 pub use special_records::SpecialRecordHandler;
 
@@ -98,7 +112,7 @@ pub use io::{
 pub use editor::{PluginEditor, TranslationDelta, RecordChange, RecordId};
 
 // 本地化插件支持导出（v0.4.0 新增）
-pub use localized_context::LocalizedPluginContext;
+pub use localized_context::{LocalizedPluginContext, MultiLanguagePluginContext};
 
 // 智能加载器导出（v0.4.0 新增）
 pub use plugin_loader::LoadedPlugin;
@@ -106,6 +120,12 @@ pub use plugin_loader::LoadedPlugin;
 // 字符串路由导出（v0.6.0 新增 - P2.3）
 pub use string_routes::{StringRouter, DefaultStringRouter};
 
+// FormID 字段定义导出
+pub use form_id_fields::{FormIdFields, FormIdLayout};
+
+// 记录压缩导出
+pub use compression::{CompressionPolicy, CompressionSettings, CompressionStrategy};
+
 // 调试工具（仅debug模式）
 #[cfg(debug_assertions)]
 pub use debug::EspDebugger;
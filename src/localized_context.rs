@@ -2,9 +2,10 @@
 ///
 /// 该模块提供本地化插件（带 STRING 文件）的便捷处理接口。
 /// 将 Plugin 和 StringFileSet 组合在一起，遵循组合模式。
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use crate::Plugin;
-use crate::StringFileSet;
+use crate::{ExtractedString, ParseOptions, Plugin, StringApplyReport};
+use crate::{MultiLanguageString, MultiLanguageStringSet, StringFileSet};
 
 /// 本地化插件上下文
 ///
@@ -58,8 +59,20 @@ impl LocalizedPluginContext {
     /// - 如果 STRING 文件加载失败
     /// - 如果插件未设置 LOCALIZED 标志（警告但不报错）
     pub fn load(path: PathBuf, language: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_with_options(path, language, &ParseOptions::default())
+    }
+
+    /// 按解析选项加载本地化插件及其 STRING 文件
+    ///
+    /// 设置了 `ParseOptions::cache` 且缓存有效时，插件只解析头部记录，
+    /// 字符串提取使用缓存中的 StringID 和本次加载的 STRING 文件。
+    pub fn load_with_options(
+        path: PathBuf,
+        language: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // 加载插件
-        let mut plugin = Plugin::load(path.clone())?;
+        let mut plugin = Plugin::load_with_options(path.clone(), options)?;
 
         // 检查是否为本地化插件
         if !plugin.is_localized() {
@@ -152,11 +165,7 @@ impl LocalizedPluginContext {
             .ok_or("无法获取插件名称")?;
 
         // 尝试多个可能的 STRING 文件位置
-        let search_dirs = vec![
-            plugin_dir.to_path_buf(),               // 同目录
-            plugin_dir.join("Strings"),             // Strings子目录（常见于开发环境）
-            plugin_dir.join("strings"),             // strings子目录（小写）
-        ];
+        let search_dirs = string_search_dirs(plugin_dir);
 
         #[cfg(debug_assertions)]
         let mut search_attempts = Vec::new();  // 收集搜索记录
@@ -168,7 +177,7 @@ impl LocalizedPluginContext {
                 continue;
             }
 
-            match StringFileSet::load_from_directory(&dir, plugin_name, language) {
+            match StringFileSet::load_from_directory_mapped(&dir, plugin_name, language) {
                 Ok(set) if !set.files.is_empty() => {
                     #[cfg(debug_assertions)]
                     println!(
@@ -275,9 +284,171 @@ impl LocalizedPluginContext {
     }
 }
 
+/// STRING 文件的候选目录（按优先级）
+fn string_search_dirs(plugin_dir: &Path) -> Vec<PathBuf> {
+    vec![
+        plugin_dir.to_path_buf(),               // 同目录
+        plugin_dir.join("Strings"),             // Strings子目录（常见于开发环境）
+        plugin_dir.join("strings"),             // strings子目录（小写）
+    ]
+}
+
+/// 多语言本地化插件上下文
+///
+/// 与 `LocalizedPluginContext` 相同，但加载插件的所有语言（从 STRING 文件目录和 BSA 自动发现），
+/// 用于并列提取各语言文本。插件本身使用主语言（存在 english 时为 english，否则为第一个语言）。
+///
+/// # 示例
+/// ```rust,ignore
+/// use esp_extractor::MultiLanguagePluginContext;
+///
+/// let context = MultiLanguagePluginContext::load("Dragonborn.esm".into())?;
+/// println!("语言: {:?}", context.languages());
+///
+/// for row in context.extract_strings() {
+///     println!("{} {:?} {:?}", row.form_id, row.text("english"), row.text("chinese"));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultiLanguagePluginContext {
+    /// ESP/ESM/ESL 插件实例
+    plugin: Plugin,
+    /// 所有语言的 STRING 文件集合
+    string_sets: MultiLanguageStringSet,
+    /// 主语言
+    primary_language: String,
+}
+
+impl MultiLanguagePluginContext {
+    /// 加载本地化插件及其所有语言的 STRING 文件
+    pub fn load(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_with_options(path, &ParseOptions::default())
+    }
+
+    /// 按解析选项加载本地化插件及其所有语言的 STRING 文件
+    ///
+    /// 同一语言在多个位置存在时，按 同目录 > Strings > strings > BSA 的优先级选择。
+    pub fn load_with_options(
+        path: PathBuf,
+        options: &ParseOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut plugin = Plugin::load_with_options(path.clone(), options)?;
+
+        if !plugin.is_localized() {
+            eprintln!(
+                "警告: 插件 {} 未设置 LOCALIZED 标志，可能不包含 STRING 文件",
+                plugin.get_name()
+            );
+        }
+
+        let string_sets = Self::load_string_sets(&path)?;
+        let primary_language = if string_sets.sets.contains_key("english") {
+            "english".to_string()
+        } else {
+            string_sets.sets.keys().next().cloned().unwrap_or_default()
+        };
+
+        if let Some(set) = string_sets.get(&primary_language) {
+            plugin.set_string_files(set.clone());
+        }
+
+        Ok(Self {
+            plugin,
+            string_sets,
+            primary_language,
+        })
+    }
+
+    /// 从所有候选目录和 BSA 加载 STRING 文件（内部辅助方法）
+    fn load_string_sets(path: &Path) -> Result<MultiLanguageStringSet, Box<dyn std::error::Error>> {
+        let plugin_dir = path.parent().ok_or("无法获取插件目录")?;
+        let plugin_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("无法获取插件名称")?;
+
+        let mut string_sets = MultiLanguageStringSet::new(plugin_name.to_string());
+
+        for dir in string_search_dirs(plugin_dir) {
+            if !dir.exists() {
+                continue;
+            }
+            string_sets.merge_missing(MultiLanguageStringSet::load_from_directory(&dir, plugin_name)?);
+        }
+
+        match MultiLanguageStringSet::load_from_bsa(path, plugin_name) {
+            Ok(from_bsa) => string_sets.merge_missing(from_bsa),
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("提示: 未从 BSA 加载 STRING 文件: {}", _e);
+            }
+        }
+
+        if string_sets.is_empty() {
+            return Err("未找到任何 STRING 文件（文件系统和 BSA 都失败）".into());
+        }
+
+        #[cfg(debug_assertions)]
+        println!("✅ 已加载 {} 种语言的 STRING 文件: {:?}", string_sets.sets.len(), string_sets.languages());
+
+        Ok(string_sets)
+    }
+
+    /// 按语言并列提取字符串
+    pub fn extract_strings(&self) -> Vec<MultiLanguageString> {
+        self.plugin.extract_multilingual_strings(&self.string_sets)
+    }
+
+    /// 一次为多个目标语言应用翻译并生成 STRING 文件
+    ///
+    /// 未翻译的字符串使用 `source_language` 的文本填充，详见 `Plugin::apply_translations_multilingual`。
+    pub fn apply_translations(
+        &self,
+        translations: &HashMap<String, Vec<ExtractedString>>,
+        source_language: &str,
+        output_dir: &Path,
+    ) -> Result<Vec<StringApplyReport>, Box<dyn std::error::Error>> {
+        let source = self.string_sets
+            .get(source_language)
+            .ok_or_else(|| format!("未加载源语言 {} 的 STRING 文件", source_language))?;
+        self.plugin.apply_translations_multilingual(translations, source, output_dir)
+    }
+
+    /// 获取插件的不可变引用
+    pub fn plugin(&self) -> &Plugin {
+        &self.plugin
+    }
+
+    /// 获取所有语言的 STRING 文件集合
+    pub fn string_sets(&self) -> &MultiLanguageStringSet {
+        &self.string_sets
+    }
+
+    /// 已加载的语言列表
+    pub fn languages(&self) -> Vec<&str> {
+        self.string_sets.languages()
+    }
+
+    /// 主语言（插件自身的字符串查找使用该语言）
+    pub fn primary_language(&self) -> &str {
+        &self.primary_language
+    }
+
+    /// 解构上下文，获取所有权
+    ///
+    /// # 返回
+    /// 返回 (Plugin, MultiLanguageStringSet, 主语言) 元组
+    pub fn into_parts(self) -> (Plugin, MultiLanguageStringSet, String) {
+        (self.plugin, self.string_sets, self.primary_language)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::plugin::PluginBuilder;
+    use crate::subrecord::Subrecord;
+    use crate::{StringFile, StringFileType};
 
     #[test]
     fn test_localized_context_creation() {
@@ -293,4 +464,151 @@ mod tests {
         // assert!(context.plugin().is_localized());
         // assert!(!context.string_files().files.is_empty());
     }
+
+    /// 多语言测试插件：WEAP（FULL=1，DESC=3），MISC（FULL=2），NPC_（FULL=4，SHRT=5）
+    ///
+    /// - english 在插件目录，包含全部字符串
+    /// - German 在 Strings 子目录（大小写不同），DESC 缺少 `<mag>`，另有插件未引用的字符串 9
+    /// - chinese 在 Strings 子目录，只有字符串 1
+    ///
+    /// 返回插件路径
+    fn write_multi_language_fixture(dir: &Path) -> PathBuf {
+        let strings_dir = dir.join("Strings");
+        std::fs::create_dir(&strings_dir).unwrap();
+
+        let string_id = |id: u32| id.to_le_bytes().to_vec();
+        let mut builder = PluginBuilder::new("Multi.esp").localized(true);
+        let sword = builder.new_record("WEAP", vec![
+            Subrecord::new("EDID", b"TestSword\0".to_vec()),
+            Subrecord::new("FULL", string_id(1)),
+            Subrecord::new("DESC", string_id(3)),
+        ]);
+        builder.add_record(sword).unwrap();
+        let coin = builder.new_record("MISC", vec![Subrecord::new("FULL", string_id(2))]);
+        builder.add_record(coin).unwrap();
+        let captain = builder.new_record("NPC_", vec![
+            Subrecord::new("EDID", b"TestCaptain\0".to_vec()),
+            Subrecord::new("FULL", string_id(4)),
+            Subrecord::new("SHRT", string_id(5)),
+        ]);
+        builder.add_record(captain).unwrap();
+        let plugin_path = dir.join("Multi.esp");
+        builder.build().unwrap().write_to_file(plugin_path.clone()).unwrap();
+
+        let english_strings = vec![(1, "Sword"), (2, "Coin"), (4, "Guard Captain"), (5, "Captain")];
+        let german_strings = vec![(1, "Schwert"), (2, "Münze"), (4, "Hauptmann der Wache"), (5, "Hauptmann"), (9, "Nur auf Deutsch")];
+        for (dir, language, file_type, texts) in [
+            (dir.to_path_buf(), "english", StringFileType::STRINGS, english_strings),
+            (dir.to_path_buf(), "english", StringFileType::DLSTRINGS, vec![(3, "Deals <mag> damage.")]),
+            (strings_dir.clone(), "German", StringFileType::STRINGS, german_strings),
+            (strings_dir.clone(), "German", StringFileType::DLSTRINGS, vec![(3, "Verursacht Schaden.")]),
+            (strings_dir.clone(), "chinese", StringFileType::STRINGS, vec![(1, "剑")]),
+        ] {
+            let mut file = StringFile::empty("Multi".to_string(), language.to_string(), file_type);
+            for (id, text) in texts {
+                file.add_string(id, text.to_string()).unwrap();
+            }
+            file.write_to_file(dir.join(format!("Multi_{}.{}", language, file_type.to_extension()))).unwrap();
+        }
+
+        plugin_path
+    }
+
+    #[test]
+    fn test_multi_language_extraction() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let plugin_path = write_multi_language_fixture(temp_dir.path());
+
+        assert_eq!(
+            MultiLanguageStringSet::discover_languages(&temp_dir.path().join("Strings"), "multi"),
+            vec!["chinese".to_string(), "german".to_string()]
+        );
+
+        let context = MultiLanguagePluginContext::load(plugin_path).unwrap();
+        assert_eq!(context.languages(), vec!["chinese", "english", "german"]);
+        assert_eq!(context.primary_language(), "english");
+        assert!(context.plugin().extract_strings().iter().any(|s| s.text == "Sword"));
+
+        let rows = context.extract_strings();
+        assert_eq!(rows.len(), 5);
+
+        let sword = rows.iter().find(|row| row.string_id == 1).unwrap();
+        assert_eq!(sword.editor_id.as_deref(), Some("TestSword"));
+        assert_eq!(sword.text("english"), Some("Sword"));
+        assert_eq!(sword.text("German"), Some("Schwert"));
+        assert_eq!(sword.text("chinese"), Some("剑"));
+
+        let coin = rows.iter().find(|row| row.string_id == 2).unwrap();
+        assert_eq!(coin.texts.len(), 2);
+        assert_eq!(coin.text("chinese"), None);
+
+        let description = rows.iter().find(|row| row.string_id == 3).unwrap();
+        assert_eq!(description.file_type, StringFileType::DLSTRINGS);
+        assert_eq!(description.text("german"), Some("Verursacht Schaden."));
+    }
+
+    #[test]
+    fn test_build_term_base_from_languages() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let context = MultiLanguagePluginContext::load(write_multi_language_fixture(temp_dir.path())).unwrap();
+        let sets = context.string_sets();
+        let (english, german) = (sets.get("english").unwrap(), sets.get("german").unwrap());
+
+        // 只收录名称：FULL 字段，排除 SHRT 和 DLSTRINGS 中的 DESC
+        let names = context.plugin().build_term_base(english, german, &crate::TermBaseOptions::names());
+        let mut sources: Vec<&str> = names.entries.iter().map(|entry| entry.source.as_str()).collect();
+        sources.sort_unstable();
+        assert_eq!(sources, vec!["Coin", "Guard Captain", "Sword"]);
+        assert!(names.entries.iter().all(|entry| entry.file_type == StringFileType::STRINGS));
+        assert!(names.lookup("Captain").is_empty());
+        assert!(names.lookup("Deals <mag> damage.").is_empty());
+
+        assert_eq!(names.lookup("Sword"), vec!["Schwert"]);
+        let sword = names.entries.iter().find(|entry| entry.string_id == 1).unwrap();
+        assert_eq!(sword.record_type.as_deref(), Some("WEAP"));
+        assert_eq!(sword.editor_id.as_deref(), Some("TestSword"));
+
+        // 默认选项收录所有被引用的字段，插件未引用的字符串 9 不收录
+        let all = context.plugin().build_term_base(english, german, &crate::TermBaseOptions::default());
+        assert_eq!(all.len(), 5);
+        assert_eq!(all.lookup("Captain"), vec!["Hauptmann"]);
+        assert_eq!(all.lookup("Deals <mag> damage."), vec!["Verursacht Schaden."]);
+        assert!(all.entries.iter().all(|entry| entry.string_id != 9));
+    }
+
+    #[test]
+    fn test_compare_languages_and_export_pending() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let context = MultiLanguagePluginContext::load(write_multi_language_fixture(temp_dir.path())).unwrap();
+        let sets = context.string_sets();
+        let english = sets.get("english").unwrap();
+
+        // 中文缺少 2、3、4、5，全部导出为待翻译条目，文本为英文原文
+        let comparison = english.compare_with(sets.get("chinese").unwrap());
+        assert_eq!(comparison.missing_in_target.len(), 4);
+        let pending = context.plugin().extract_pending_strings(&comparison);
+        assert_eq!(pending.len(), 4);
+        let coin = pending.iter().find(|s| s.record_type == "MISC").unwrap();
+        assert_eq!(coin.text, "Coin");
+        assert!(pending.iter().any(|s| s.subrecord_type == "DESC" && s.text == "Deals <mag> damage."));
+
+        // 德语：DESC 缺少 <mag>，9 只存在于德语中
+        let comparison = english.compare_with(sets.get("german").unwrap());
+        assert!(comparison.untranslated.is_empty() && comparison.missing_in_target.is_empty());
+        assert_eq!(comparison.placeholder_mismatches.len(), 1);
+        let mismatch = &comparison.placeholder_mismatches[0];
+        assert_eq!((mismatch.file_type, mismatch.string_id), (StringFileType::DLSTRINGS, 3));
+        assert_eq!(mismatch.missing, vec!["<mag>".to_string()]);
+        assert!(mismatch.extra.is_empty());
+        assert_eq!(comparison.missing_in_source.len(), 1);
+        assert_eq!(comparison.missing_in_source[0].string_id, 9);
+        assert_eq!(comparison.missing_in_source[0].target_text.as_deref(), Some("Nur auf Deutsch"));
+
+        // 只有占位符不一致的字段需要重新翻译；源语言缺失的 9 不被插件引用，不导出
+        let pending = context.plugin().extract_pending_strings(&comparison);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].editor_id.as_deref(), Some("TestSword"));
+        assert_eq!(pending[0].subrecord_type, "DESC");
+        assert_eq!(pending[0].text, "Deals <mag> damage.");
+    }
 }
@@ -5,8 +5,10 @@
 #[cfg(feature = "cli")]
 use clap::Parser;
 use std::path::PathBuf;
-use esp_extractor::{Plugin, ExtractedString, SUPPORTED_EXTENSIONS, LoadedPlugin};
-use esp_extractor::StringFile;
+use esp_extractor::{Plugin, ExtractedString, SUPPORTED_EXTENSIONS, LoadedPlugin, EslVerdict, ParseOptions};
+use esp_extractor::{LocalizedPluginContext, MultiLanguagePluginContext, MultiLanguageString, RebuildOptions, StringFile, StringFileType};
+use esp_extractor::TermBaseOptions;
+use esp_extractor::utils::create_backup;
 use esp_extractor::group::{Group, GroupChild};
 
 #[cfg(debug_assertions)]
@@ -70,11 +72,97 @@ struct Cli {
     /// 重编号所有 FormID，从 0x800 开始，最多支持 2048 个记录
     #[arg(long)]
     eslify: bool,
+
+    /// 预览模式：配合 --eslify 使用，仅输出 ESL 资格分析和重编号映射，不写入插件
+    /// 如果指定了 --output，分析报告将以 JSON 格式写入该文件
+    #[arg(long)]
+    dry_run: bool,
+
+    /// 合并插件：将输入插件与指定的插件（按顺序）合并为一个插件
+    /// 合并插件写入 --output（默认 Merged.esp），合并映射写入同名 .merge_map.json 文件
+    #[arg(long, num_args = 1..)]
+    merge_with: Vec<PathBuf>,
+
+    /// 配合 --merge-with 使用：记录中有无法重映射的 FormID 字段（CTDA、VMAD 等）时仍然合并，
+    /// 这些字段保持原值并在报告中逐条列出
+    #[arg(long, requires = "merge_with")]
+    allow_uncovered: bool,
+
+    /// 补丁模式：配合翻译应用选项使用，生成以原插件为主文件的独立翻译覆盖补丁，不修改原插件
+    #[arg(long)]
+    patch: bool,
+
+    /// 补丁模式：为翻译补丁设置 ESL 标志
+    #[arg(long)]
+    patch_esl: bool,
+
+    /// 检查本地化插件的 STRING 文件完整性（缺失、孤立、文件类型错误），参数为语言（默认 english）
+    /// 如果指定了 --output，检查报告将以 JSON 格式写入该文件
+    #[arg(long, value_name = "LANGUAGE", num_args = 0..=1, default_missing_value = "english")]
+    check_strings: Option<String>,
+
+    /// 修复模式：配合 --check-strings 使用，移动文件类型错误的字符串并写回 STRING 文件
+    #[arg(long)]
+    fix: bool,
+
+    /// 配合 --fix 使用：同时删除孤立字符串
+    /// （字符串路由未覆盖所有本地化字段，孤立字符串中可能有仍在使用的文本，请先检查报告）
+    #[arg(long, requires = "fix")]
+    delete_orphans: bool,
+
+    /// 从 JSON 生成字符串文件（--parse-strings 的逆操作）：--input 为 JSON 文件，
+    /// --output 为字符串文件（文件类型由扩展名决定，如 MyMod_chinese.DLSTRINGS）
+    #[arg(long)]
+    build_strings: bool,
+
+    /// 配合 --build-strings 使用：将 JSON 合并到已有的字符串文件上
+    #[arg(long, value_name = "STRING_FILE")]
+    merge_onto: Option<PathBuf>,
+
+    /// 配合 --build-strings 使用：新建或修改的字符串使用的编码（如 windows-1252，默认 utf-8）
+    #[arg(long)]
+    encoding: Option<String>,
+
+    /// 多语言提取：加载本地化插件的所有语言（STRING 文件目录和 BSA），每个字符串一行、每种语言一列
+    #[arg(long)]
+    all_languages: bool,
+
+    /// 多语言应用：一次为多个语言生成 STRING 文件，参数为 语言=翻译JSON文件（可多个）
+    /// STRING 文件写入 --output 目录（默认插件所在目录）下的 strings/，应用报告写入 apply_report.json
+    #[arg(long, value_name = "LANGUAGE=FILE", num_args = 1..)]
+    apply_languages: Vec<String>,
+
+    /// 配合 --apply-languages / --compare-language 使用：未翻译字符串的来源语言
+    #[arg(long, default_value = "english")]
+    source_language: String,
+
+    /// 对比本地化插件的目标语言与源语言（--source-language），找出未翻译、缺失和占位符不一致的字符串
+    /// 如果指定了 --output，仍需翻译的字符串将以提取格式 JSON 写入该文件
+    #[arg(long, value_name = "LANGUAGE")]
+    compare_language: Option<String>,
+
+    /// 生成双语术语库：按 ID 对齐源语言（--source-language）与目标语言的官方 STRING 文件（目录或 BSA）
+    /// 结果写入 --output（.tsv 为术语表，其余为 JSON；默认 <插件>.termbase.json）
+    #[arg(long, value_name = "LANGUAGE")]
+    term_base: Option<String>,
+
+    /// 配合 --term-base 使用：只收录 STRINGS 中的简短 FULL 名称（物品、地点、法术等）
+    #[arg(long)]
+    names_only: bool,
+
+    /// 对齐原版插件（--input）与其翻译副本，生成双语语料
+    /// 可直接应用的翻译 JSON 写入 --output（默认 <插件>.aligned.json），对齐报告写入同名 .alignment.json 文件
+    #[arg(long, value_name = "TRANSLATED_PLUGIN")]
+    align_with: Option<PathBuf>,
 }
 
 #[cfg(feature = "cli")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+
+    if cli.build_strings {
+        return handle_string_file_building(&cli);
+    }
     
     validate_input(&cli.input)?;
     validate_partial_options(&cli)?;
@@ -104,10 +192,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return handle_translation_jsonstr(&cli, translation_json);
     }
 
+    if !cli.apply_languages.is_empty() {
+        return handle_multilingual_apply(&cli);
+    }
+
+    if let Some(language) = &cli.compare_language {
+        return handle_language_comparison(&cli, language);
+    }
+
+    if let Some(translated) = &cli.align_with {
+        return handle_alignment(&cli, translated);
+    }
+
+    if let Some(language) = &cli.term_base {
+        return handle_term_base(&cli, language);
+    }
+
     if cli.eslify {
         return handle_eslify(&cli);
     }
 
+    if !cli.merge_with.is_empty() {
+        return handle_merge(&cli);
+    }
+
+    if let Some(language) = &cli.check_strings {
+        return handle_string_check(&cli, language);
+    }
+
     // 默认模式：根据文件类型自动选择处理方式
     let extension = cli.input.extension()
         .and_then(|ext| ext.to_str())
@@ -194,6 +306,45 @@ fn handle_eslify(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // 分析 ESL 资格（扫描同目录下依赖此插件的插件）
+    let plugin_dir = cli.input.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let analysis = plugin.analyze_esl(Some(plugin_dir.unwrap_or(std::path::Path::new("."))))?;
+
+    if !cli.quiet {
+        println!("{}", analysis);
+    }
+
+    if cli.dry_run {
+        if !cli.quiet {
+            println!("重编号预览:");
+            for mapping in &analysis.mapping {
+                println!("  [{}] {:08X} -> {:08X} {}",
+                    mapping.record_type,
+                    mapping.old_form_id,
+                    mapping.new_form_id,
+                    mapping.editor_id.as_deref().unwrap_or("")
+                );
+            }
+        }
+
+        if let Some(ref output) = cli.output {
+            let json_output = serde_json::to_string_pretty(&analysis)
+                .map_err(|e| format!("序列化JSON失败: {}", e))?;
+            std::fs::write(output, json_output)
+                .map_err(|e| format!("写入文件失败: {}", e))?;
+
+            if !cli.quiet {
+                println!("分析报告已写入: {:?}", output);
+            }
+        }
+
+        return Ok(());
+    }
+
+    if analysis.verdict == EslVerdict::Ineligible {
+        return Err(format!("插件无法转换为 ESL：新记录数 {} 超过 2048 个限制", analysis.new_record_count).into());
+    }
+
     // 执行 FormID 重编号
     if !cli.quiet {
         println!("正在重编号 FormID...");
@@ -228,7 +379,7 @@ fn handle_eslify(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
         println!("✓ ESL 转换完成！");
         println!("  输出文件: {:?}", output_path);
         println!("\n提示：");
-        println!("  1. 请确保插件记录数不超过 2048 个");
+        println!("  1. 可使用 --eslify --dry-run 预览重编号映射");
         println!("  2. 使用 Creation Kit 或 xEdit 验证插件完整性");
         println!("  3. 如果需要，手动设置插件的 LightMaster 标志");
     }
@@ -236,6 +387,175 @@ fn handle_eslify(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// 处理从 JSON 生成字符串文件
+fn handle_string_file_building(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    if !cli.input.exists() {
+        return Err(format!("输入文件不存在: {:?}", cli.input).into());
+    }
+    let output = cli.output.as_ref().ok_or("--build-strings 需要通过 --output 指定字符串文件路径")?;
+
+    let file_type = output.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(StringFileType::from_extension)
+        .ok_or("输出文件扩展名必须是 STRINGS、ILSTRINGS 或 DLSTRINGS")?;
+    let stem = output.file_stem().and_then(|stem| stem.to_str()).unwrap_or("output");
+    let (plugin_name, language) = stem.rsplit_once('_').unwrap_or((stem, "english"));
+
+    let json = std::fs::read_to_string(&cli.input)
+        .map_err(|e| format!("读取JSON文件失败: {}", e))?;
+
+    let string_file = if let Some(ref base) = cli.merge_onto {
+        let mut string_file = StringFile::new(base.clone())?;
+        if string_file.file_type != file_type {
+            return Err(format!("合并目标 {:?} 的文件类型与输出文件 {:?} 不一致", base, output).into());
+        }
+        let (updated, added) = string_file.merge_json(&json)?;
+        if !cli.quiet {
+            println!("合并到 {:?}: 更新 {} 个，新增 {} 个字符串", base, updated, added);
+        }
+        string_file
+    } else {
+        StringFile::from_json(&json, plugin_name.to_string(), language.to_string(), file_type)?
+    };
+
+    let options = RebuildOptions { encoding: cli.encoding.clone(), ..RebuildOptions::default() };
+    let data = string_file.rebuild_with(&options)?;
+
+    if output.exists() {
+        let backup_path = create_backup(output)?;
+        if !cli.quiet {
+            println!("已创建备份: {:?}", backup_path);
+        }
+    }
+    std::fs::write(output, &data)
+        .map_err(|e| format!("写入文件失败: {}", e))?;
+
+    if !cli.quiet {
+        println!("生成完成:");
+        println!("  文件类型: {:?}", file_type);
+        println!("  字符串数量: {}", string_file.count());
+        println!("  结果已写入: {:?}", output);
+    }
+
+    Ok(())
+}
+
+/// 处理 STRING 文件完整性检查
+fn handle_string_check(cli: &Cli, language: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !cli.quiet {
+        println!("正在检查 STRING 文件完整性...");
+        println!("输入文件: {:?}", cli.input);
+    }
+
+    let options = ParseOptions { lazy: true, ..ParseOptions::default() };
+    let context = LocalizedPluginContext::load_with_options(cli.input.clone(), language, &options)?;
+    let (plugin, mut string_files, _) = context.into_parts();
+
+    let report = if cli.fix {
+        plugin.fix_strings(&mut string_files, cli.delete_orphans)?
+    } else {
+        plugin.check_strings(&string_files)
+    };
+
+    if !cli.quiet {
+        println!("{}", report);
+    }
+
+    if let Some(ref output) = cli.output {
+        let json_output = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("序列化JSON失败: {}", e))?;
+        std::fs::write(output, json_output)
+            .map_err(|e| format!("写入文件失败: {}", e))?;
+
+        if !cli.quiet {
+            println!("检查报告已写入: {:?}", output);
+        }
+    }
+
+    if cli.fix {
+        if report.misplaced.is_empty() && (!report.orphans_deleted || report.orphans.is_empty()) {
+            return Ok(());
+        }
+
+        // 新建的文件（以及从 BSA 加载的文件）写入已有 STRING 文件所在的目录
+        let string_dir = string_files.files.values()
+            .filter(|file| !file.path.to_string_lossy().starts_with("<memory>"))
+            .find_map(|file| file.path.parent().map(|dir| dir.to_path_buf()))
+            .unwrap_or_else(|| cli.input.parent().unwrap_or(std::path::Path::new(".")).join("Strings"));
+        std::fs::create_dir_all(&string_dir)?;
+
+        for (file_type, file) in &string_files.files {
+            let path = if file.path.to_string_lossy().starts_with("<memory>") {
+                string_dir.join(format!("{}_{}.{}", string_files.plugin_name, string_files.language, file_type.to_extension()))
+            } else {
+                file.path.clone()
+            };
+
+            if path.exists() {
+                let backup_path = create_backup(&path)?;
+                if !cli.quiet {
+                    println!("已创建备份: {:?}", backup_path);
+                }
+            }
+            file.write_to_file(path.clone())?;
+
+            if !cli.quiet {
+                println!("已写入: {:?}", path);
+            }
+        }
+        return Ok(());
+    }
+
+    if !report.is_clean() {
+        return Err(format!("发现 {} 个 STRING 文件问题（可使用 --fix 移动文件类型错误的字符串，--delete-orphans 删除孤立字符串）", report.issue_count()).into());
+    }
+
+    Ok(())
+}
+
+/// 处理插件合并
+fn handle_merge(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let mut plugins = vec![Plugin::load(cli.input.clone())?];
+    for path in &cli.merge_with {
+        validate_input(path)?;
+        plugins.push(Plugin::load(path.clone())?);
+    }
+
+    let output_path = cli.output.clone().unwrap_or_else(|| {
+        cli.input.parent()
+            .unwrap_or(std::path::Path::new("."))
+            .join("Merged.esp")
+    });
+
+    if !cli.quiet {
+        println!("正在合并 {} 个插件...", plugins.len());
+    }
+
+    let (merged, report) = Plugin::merge(plugins, output_path.clone(), cli.allow_uncovered)?;
+
+    // 写出前显示报告；静默模式下仍输出警告
+    if !cli.quiet {
+        println!("{}", report);
+    } else {
+        for warning in &report.warnings {
+            eprintln!("⚠️ {}", warning);
+        }
+    }
+
+    merged.write_to_file(output_path.clone())?;
+
+    let merge_map_path = output_path.with_extension("merge_map.json");
+    report.save_merge_map(&merge_map_path)?;
+
+    if !cli.quiet {
+        println!("✓ 合并完成！");
+        println!("  输出文件: {:?}", output_path);
+        println!("  合并映射: {:?}", merge_map_path);
+    }
+
+    Ok(())
+}
+
 /// 处理翻译应用（从字符串）
 fn handle_translation_jsonstr(cli: &Cli, translation_json: &str) -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(debug_assertions)]
@@ -285,6 +605,131 @@ fn handle_translation_stdin(cli: &Cli) -> Result<(), Box<dyn std::error::Error>>
     apply_translations(cli, translations)
 }
 
+/// 处理多语言翻译应用
+fn handle_multilingual_apply(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let mut translations = std::collections::HashMap::new();
+    for arg in &cli.apply_languages {
+        let (language, file) = arg.split_once('=')
+            .ok_or_else(|| format!("--apply-languages 参数格式应为 语言=文件: {}", arg))?;
+        let json_str = std::fs::read_to_string(file)
+            .map_err(|e| format!("读取翻译文件 {} 失败: {}", file, e))?;
+        translations.insert(language.to_lowercase(), parse_translation_json(&json_str)?);
+    }
+
+    if !cli.quiet {
+        println!("正在加载插件: {:?}", cli.input);
+    }
+
+    let context = MultiLanguagePluginContext::load_with_options(cli.input.clone(), &ParseOptions::strings_only()?)?;
+    let output_dir = cli.output.clone()
+        .unwrap_or_else(|| cli.input.parent().unwrap_or(std::path::Path::new(".")).to_path_buf());
+
+    let reports = context.apply_translations(&translations, &cli.source_language, &output_dir)?;
+
+    if !cli.quiet {
+        for report in &reports {
+            println!("{}", report);
+        }
+    }
+
+    let report_path = output_dir.join("apply_report.json");
+    std::fs::write(&report_path, serde_json::to_string_pretty(&reports)?)
+        .map_err(|e| format!("写入报告失败: {}", e))?;
+
+    if !cli.quiet {
+        println!("应用报告已写入: {:?}", report_path);
+    }
+
+    Ok(())
+}
+
+/// 处理目标语言与源语言的对比
+fn handle_language_comparison(cli: &Cli, language: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let context = MultiLanguagePluginContext::load_with_options(cli.input.clone(), &ParseOptions::strings_only()?)?;
+    let sets = context.string_sets();
+    let source = sets.get(&cli.source_language)
+        .ok_or_else(|| format!("未加载源语言 {} 的 STRING 文件", cli.source_language))?;
+    let target = sets.get(language)
+        .ok_or_else(|| format!("未加载目标语言 {} 的 STRING 文件（已有: {}）", language, context.languages().join(", ")))?;
+
+    let comparison = source.compare_with(target);
+
+    if !cli.quiet {
+        print!("{}", comparison);
+    }
+
+    if let Some(ref output) = cli.output {
+        let pending = context.plugin().extract_pending_strings(&comparison);
+        save_strings_to_file(&pending, output)?;
+        if !cli.quiet {
+            println!("{} 个待翻译字符串已写入: {:?}", pending.len(), output);
+        }
+    }
+
+    Ok(())
+}
+
+/// 处理原版与译版插件对齐
+fn handle_alignment(cli: &Cli, translated: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    validate_input(translated)?;
+    let options = ParseOptions::strings_only()?;
+
+    let original = LoadedPlugin::load_auto_with_options(cli.input.clone(), Some("english"), &options)
+        .map_err(|e| format!("解析原版插件失败: {}", e))?;
+    let translated = LoadedPlugin::load_auto_with_options(translated.clone(), Some("english"), &options)
+        .map_err(|e| format!("解析译版插件失败: {}", e))?;
+
+    let report = original.plugin().align_with(translated.plugin());
+
+    if !cli.quiet {
+        print!("{}", report);
+    }
+
+    let output_path = cli.output.clone()
+        .unwrap_or_else(|| cli.input.with_extension("aligned.json"));
+    let translations = report.to_translations();
+    save_strings_to_file(&translations, &output_path)?;
+
+    let report_path = output_path.with_extension("alignment.json");
+    std::fs::write(&report_path, serde_json::to_string_pretty(&report)?)
+        .map_err(|e| format!("写入对齐报告失败: {}", e))?;
+
+    if !cli.quiet {
+        println!("{} 条翻译已写入: {:?}", translations.len(), output_path);
+        println!("对齐报告已写入: {:?}", report_path);
+    }
+
+    Ok(())
+}
+
+/// 处理双语术语库生成
+fn handle_term_base(cli: &Cli, language: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !cli.quiet {
+        println!("正在加载插件: {:?}", cli.input);
+    }
+
+    let context = MultiLanguagePluginContext::load_with_options(cli.input.clone(), &ParseOptions::strings_only()?)?;
+    let sets = context.string_sets();
+    let source = sets.get(&cli.source_language)
+        .ok_or_else(|| format!("未加载源语言 {} 的 STRING 文件", cli.source_language))?;
+    let target = sets.get(language)
+        .ok_or_else(|| format!("未加载目标语言 {} 的 STRING 文件（已有: {}）", language, context.languages().join(", ")))?;
+
+    let options = if cli.names_only { TermBaseOptions::names() } else { TermBaseOptions::default() };
+    let term_base = context.plugin().build_term_base(source, target, &options);
+
+    let output_path = cli.output.clone()
+        .unwrap_or_else(|| cli.input.with_extension("termbase.json"));
+    term_base.save(&output_path)?;
+
+    if !cli.quiet {
+        println!("术语库: {} -> {}，{} 条", term_base.source_language, term_base.target_language, term_base.len());
+        println!("结果已写入: {:?}", output_path);
+    }
+
+    Ok(())
+}
+
 /// 解析翻译JSON
 fn parse_translation_json(json_str: &str) -> Result<Vec<ExtractedString>, Box<dyn std::error::Error>> {
     serde_json::from_str(json_str)
@@ -318,6 +763,10 @@ fn apply_translations(cli: &Cli, translations: Vec<ExtractedString>) -> Result<(
         }
     }
     
+    if cli.patch {
+        return create_translation_patch(cli, translations);
+    }
+
     let output_path = get_apply_output_path(cli);
     Plugin::apply_translations(cli.input.clone(), output_path.clone(), translations, None)
         .map_err(|e| format!("应用翻译失败: {}", e))?;
@@ -329,6 +778,28 @@ fn apply_translations(cli: &Cli, translations: Vec<ExtractedString>) -> Result<(
     Ok(())
 }
 
+/// 生成独立的翻译覆盖补丁
+fn create_translation_patch(cli: &Cli, translations: Vec<ExtractedString>) -> Result<(), Box<dyn std::error::Error>> {
+    let output_path = cli.output.clone().unwrap_or_else(|| {
+        let stem = cli.input.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+        cli.input.with_file_name(format!("{}_translation.esp", stem))
+    });
+
+    let plugin = Plugin::load(cli.input.clone())?;
+    let patch = plugin.create_translation_patch(translations, output_path.clone(), cli.patch_esl)
+        .map_err(|e| format!("生成翻译补丁失败: {}", e))?;
+    patch.write_to_file(output_path.clone())?;
+
+    if !cli.quiet {
+        println!("✓ 翻译补丁生成完成！");
+        println!("  输出文件: {:?}", output_path);
+        println!("  记录与组: {}", patch.count_records_and_groups());
+        println!("  主文件: {}", patch.masters.join(", "));
+    }
+
+    Ok(())
+}
+
 /// 处理字符串提取
 fn handle_string_extraction(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(debug_assertions)]
@@ -336,8 +807,15 @@ fn handle_string_extraction(cli: &Cli) -> Result<(), Box<dyn std::error::Error>>
         println!("正在解析插件: {:?}", cli.input);
     }
 
+    // 只提取字符串时跳过不含字符串的记录并延迟解析；统计需要完整解析
+    let options = if cli.stats { ParseOptions::default() } else { ParseOptions::strings_only()? };
+
+    if cli.all_languages && !cli.stats {
+        return handle_multilingual_extraction(cli, &options);
+    }
+
     // 使用新的 LoadedPlugin API，支持 BSA fallback
-    let loaded = LoadedPlugin::load_auto(cli.input.clone(), Some("english"))
+    let loaded = LoadedPlugin::load_auto_with_options(cli.input.clone(), Some("english"), &options)
         .map_err(|e| format!("解析插件失败: {}", e))?;
 
     if cli.stats {
@@ -359,6 +837,34 @@ fn handle_string_extraction(cli: &Cli) -> Result<(), Box<dyn std::error::Error>>
     Ok(())
 }
 
+/// 处理多语言并列提取
+fn handle_multilingual_extraction(cli: &Cli, options: &ParseOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let context = MultiLanguagePluginContext::load_with_options(cli.input.clone(), options)
+        .map_err(|e| format!("解析插件失败: {}", e))?;
+
+    let rows: Vec<MultiLanguageString> = context.extract_strings();
+    let output_path = cli.output.as_ref()
+        .map(|p| p.clone())
+        .unwrap_or_else(|| cli.input.with_extension("json"));
+
+    let json_output = serde_json::to_string_pretty(&rows)
+        .map_err(|e| format!("序列化JSON失败: {}", e))?;
+    std::fs::write(&output_path, &json_output)
+        .map_err(|e| format!("写入文件失败: {}", e))?;
+
+    if !cli.quiet {
+        println!("语言: {}（主语言: {}）", context.languages().join(", "), context.primary_language());
+        for language in context.languages() {
+            let count = rows.iter().filter(|row| row.texts.contains_key(language)).count();
+            println!("  {}: {}/{} 个字符串", language, count, rows.len());
+        }
+        println!("提取到 {} 个字符串", rows.len());
+        println!("结果已写入: {:?}", output_path);
+    }
+
+    Ok(())
+}
+
 /// 将字符串保存到文件
 fn save_strings_to_file(strings: &[ExtractedString], output_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     let json_output = serde_json::to_string_pretty(strings)
@@ -535,8 +1041,8 @@ fn handle_file_comparison(cli: &Cli, compare_file: &PathBuf) -> Result<(), Box<d
     // 对比每个GRUP的大小
     println!("\n=== GRUP大小对比 ===");
     for (i, (group1, group2)) in plugin1.groups.iter().zip(plugin2.groups.iter()).enumerate() {
-        let label1 = String::from_utf8_lossy(&group1.label);
-        let label2 = String::from_utf8_lossy(&group2.label);
+        let label1 = group1.label;
+        let label2 = group2.label;
         
         if group1.size != group2.size {
             println!("⚠️ GRUP {} ('{}' vs '{}'): {} vs {} (差异: {})", 
@@ -580,7 +1086,7 @@ fn analyze_group_difference(group1: &Group, group2: &Group, group_index: usize)
                     let diff = (g2.size as i64) - (g1.size as i64);
                     total_diff += diff;
                     println!("    子GRUP {} ('{}'): {} vs {} (差异: {})", 
-                        i, String::from_utf8_lossy(&g1.label), g1.size, g2.size, diff);
+                        i, g1.label, g1.size, g2.size, diff);
                 }
             }
             _ => {
@@ -618,7 +1124,7 @@ fn handle_string_file_parsing(cli: &Cli, string_file_path: &PathBuf) -> Result<(
     }
     
     // 将字符串转换为JSON格式输出
-    let entries: Vec<_> = string_file.entries.values().collect();
+    let entries: Vec<_> = string_file.iter().collect();
     let json_output = serde_json::to_string_pretty(&entries)
         .map_err(|e| format!("序列化JSON失败: {}", e))?;
     
@@ -638,7 +1144,7 @@ fn handle_string_file_parsing(cli: &Cli, string_file_path: &PathBuf) -> Result<(
         println!("  结果已写入: {:?}", output_path);
         
         // 显示前几个字符串样例
-        let sample_entries: Vec<_> = string_file.entries.values().take(3).collect();
+        let sample_entries: Vec<_> = string_file.iter().take(3).collect();
         if !sample_entries.is_empty() {
             println!("\n样例字符串:");
             for (i, entry) in sample_entries.iter().enumerate() {
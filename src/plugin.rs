@@ -4,9 +4,36 @@ mod translate;
 mod writer;
 mod stats;
 mod esl;
+mod references;
+mod masters;
+mod header;
+mod merge;
+mod patch;
+mod forward;
+mod builder;
+mod cache;
+mod string_check;
+mod string_apply;
+mod align;
+#[cfg(test)]
+mod test_support;
 
 pub use stats::PluginStats;
+pub use esl::{EslAnalysis, EslVerdict, FormIdMapping};
+pub use references::{FormIdReference, UncoveredField};
+pub use masters::MasterError;
+pub use merge::{MergeConflict, MergeMapEntry, MergeReport};
+pub use forward::ForwardReport;
+pub use builder::PluginBuilder;
+pub use align::{AlignMethod, AlignedString, AlignmentReport};
+pub use string_apply::StringApplyReport;
+pub use string_check::{MisplacedString, MissingString, OrphanString, StringCheckReport};
+pub use cache::{FileFingerprint, IndexedRecord, IndexedString, PluginCache, PluginIndex, StringValue};
+pub use header::PluginHeader;
+pub use parser::ParseOptions;
+pub use writer::{CompressionAction, CompressionDecision, WriteOptions, WriteReport};
 
+use crate::datatypes::Signature;
 use crate::group::Group;
 use crate::record::Record;
 use crate::string_file::{StringFileSet, StringFileType};
@@ -40,9 +67,14 @@ pub struct Plugin {
     /// 注意：此字段仅用于向后兼容 deprecated 的 `new()` 方法
     #[allow(dead_code)]
     language: String,
-    /// 内存映射文件（性能优化：零拷贝访问文件数据）
-    #[allow(dead_code)]
+    /// 内存映射文件（写入时未修改的组直接从映射复制）
     mmap: Option<Arc<Mmap>>,
+    /// 内存映射文件的源标识（与记录/组的 `source_span` 对应）
+    source_id: Option<u64>,
+    /// 加载时按解析选项跳过了部分组或记录（不能写回文件）
+    partial: bool,
+    /// 从索引缓存加载时的插件索引（此时 `groups` 为空）
+    index: Option<Arc<PluginIndex>>,
 }
 
 impl Plugin {
@@ -51,6 +83,13 @@ impl Plugin {
         self.path.file_name().unwrap().to_str().unwrap()
     }
 
+    /// 是否按解析选项跳过了部分组或记录（见 `ParseOptions`）
+    ///
+    /// 部分加载的插件只能用于读取，不能写回文件。
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
     /// 获取插件类型
     pub fn get_type(&self) -> &str {
         match self.path.extension().and_then(|ext| ext.to_str()) {
@@ -129,8 +168,8 @@ impl Plugin {
     /// - DESC/CNAM 子记录 → DLSTRINGS（描述文本/内容，通常是较长的文本）
     /// - 其他所有字符串子记录 (FULL/NNAM等) → STRINGS (默认)
     pub(crate) fn determine_string_file_type(
-        record_type: &str,
-        subrecord_type: &str,
+        record_type: Signature,
+        subrecord_type: Signature,
     ) -> StringFileType {
         // INFO 记录 → ILSTRINGS
         // INFO 记录包含对话信息，按照 Bethesda 约定存储在 ILSTRINGS 中
@@ -140,7 +179,7 @@ impl Plugin {
 
         // DESC 和 CNAM 子记录 → DLSTRINGS
         // 这些通常是较长的描述性文本或内容，按照 Bethesda 约定存储在 DLSTRINGS 中
-        if matches!(subrecord_type, "DESC" | "CNAM") {
+        if matches!(subrecord_type.as_bytes(), b"DESC" | b"CNAM") {
             return StringFileType::DLSTRINGS;
         }
 
@@ -156,7 +195,7 @@ mod tests {
 
     #[test]
     fn test_info_routes_to_ilstrings() {
-        let file_type = Plugin::determine_string_file_type("INFO", "NAM1");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("INFO"), Signature::from_name("NAM1"));
         assert_eq!(
             file_type,
             StringFileType::ILSTRINGS,
@@ -167,21 +206,21 @@ mod tests {
     #[test]
     fn test_desc_routes_to_dlstrings() {
         // 任何record的DESC都应该路由到DLSTRINGS
-        let file_type = Plugin::determine_string_file_type("PERK", "DESC");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("PERK"), Signature::from_name("DESC"));
         assert_eq!(
             file_type,
             StringFileType::DLSTRINGS,
             "PERK DESC应该路由到DLSTRINGS"
         );
 
-        let file_type = Plugin::determine_string_file_type("WEAP", "DESC");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("WEAP"), Signature::from_name("DESC"));
         assert_eq!(
             file_type,
             StringFileType::DLSTRINGS,
             "WEAP DESC应该路由到DLSTRINGS"
         );
 
-        let file_type = Plugin::determine_string_file_type("MESG", "DESC");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("MESG"), Signature::from_name("DESC"));
         assert_eq!(
             file_type,
             StringFileType::DLSTRINGS,
@@ -192,14 +231,14 @@ mod tests {
     #[test]
     fn test_cnam_routes_to_dlstrings() {
         // 任何record的CNAM都应该路由到DLSTRINGS
-        let file_type = Plugin::determine_string_file_type("QUST", "CNAM");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("QUST"), Signature::from_name("CNAM"));
         assert_eq!(
             file_type,
             StringFileType::DLSTRINGS,
             "QUST CNAM应该路由到DLSTRINGS"
         );
 
-        let file_type = Plugin::determine_string_file_type("BOOK", "CNAM");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("BOOK"), Signature::from_name("CNAM"));
         assert_eq!(
             file_type,
             StringFileType::DLSTRINGS,
@@ -210,21 +249,21 @@ mod tests {
     #[test]
     fn test_full_routes_to_strings() {
         // FULL应该路由到STRINGS
-        let file_type = Plugin::determine_string_file_type("WEAP", "FULL");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("WEAP"), Signature::from_name("FULL"));
         assert_eq!(
             file_type,
             StringFileType::STRINGS,
             "WEAP FULL应该路由到STRINGS"
         );
 
-        let file_type = Plugin::determine_string_file_type("PERK", "FULL");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("PERK"), Signature::from_name("FULL"));
         assert_eq!(
             file_type,
             StringFileType::STRINGS,
             "PERK FULL应该路由到STRINGS"
         );
 
-        let file_type = Plugin::determine_string_file_type("DIAL", "FULL");
+        let file_type = Plugin::determine_string_file_type(Signature::from_name("DIAL"), Signature::from_name("FULL"));
         assert_eq!(
             file_type,
             StringFileType::STRINGS,
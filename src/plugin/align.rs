@@ -0,0 +1,237 @@
+use super::Plugin;
+use crate::string_types::ExtractedString;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AlignMethod {
+    /// 唯一键完全一致（EDID、FormID、字段类型和索引）
+    UniqueKey,
+    /// EDID 不同，按 FormID、字段类型和索引匹配
+    FormId,
+}
+
+/// 原版与译版插件中对应的一对字符串
+#[derive(Debug, Clone, Serialize)]
+pub struct AlignedString {
+    /// 原版插件中的 EDID
+    pub editor_id: Option<String>,
+    /// 完整FormID（以原版插件命名）
+    pub form_id: String,
+    /// 记录类型
+    pub record_type: String,
+    /// 子记录类型
+    pub subrecord_type: String,
+    /// 子记录索引
+    pub index: i32,
+    /// 原文
+    pub source: String,
+    /// 译文
+    pub target: String,
+    /// 对齐方式
+    pub method: AlignMethod,
+}
+
+/// 插件对齐报告（双语语料）
+#[derive(Debug, Clone, Serialize)]
+pub struct AlignmentReport {
+    /// 原版插件名称
+    pub source_plugin: String,
+    /// 译版插件名称
+    pub target_plugin: String,
+    /// 已对齐的字符串（按原版插件中的提取顺序）
+    pub pairs: Vec<AlignedString>,
+    /// 原版插件中未对齐的字符串
+    pub unmatched_source: Vec<ExtractedString>,
+    /// 译版插件中未对齐的字符串（FormID 已换算为原版插件命名）
+    pub unmatched_target: Vec<ExtractedString>,
+}
+
+impl AlignmentReport {
+    /// 按 FormID 回退匹配的数量（EDID 在译版中被修改）
+    pub fn fallback_count(&self) -> usize {
+        self.pairs.iter().filter(|pair| pair.method == AlignMethod::FormId).count()
+    }
+
+    /// 转换为可直接应用到原版插件的翻译 JSON 格式（`text` 为译文）
+    ///
+    /// 原文与译文相同的字符串不包含在结果中。
+    pub fn to_translations(&self) -> Vec<ExtractedString> {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.source != pair.target)
+            .map(|pair| ExtractedString::new(
+                pair.editor_id.clone(),
+                pair.form_id.clone(),
+                pair.record_type.clone(),
+                pair.subrecord_type.clone(),
+                pair.target.clone(),
+                pair.index,
+            ))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for AlignmentReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== 插件对齐报告 ===")?;
+        writeln!(f, "原版: {} / 译版: {}", self.source_plugin, self.target_plugin)?;
+        writeln!(f, "已对齐: {} (按 FormID 回退 {})", self.pairs.len(), self.fallback_count())?;
+        writeln!(f, "原版未对齐: {}", self.unmatched_source.len())?;
+        for string in &self.unmatched_source {
+            writeln!(f, "  - {}", string.get_unique_key())?;
+        }
+        writeln!(f, "译版未对齐: {}", self.unmatched_target.len())?;
+        for string in &self.unmatched_target {
+            writeln!(f, "  + {}", string.get_unique_key())?;
+        }
+        Ok(())
+    }
+}
+
+/// 回退匹配键：FormID + 字段类型 + 索引（不含 EDID）
+fn fallback_key(string: &ExtractedString) -> String {
+    format!("{}|{}|{}", string.form_id, string.get_string_type(), string.index)
+}
+
+impl Plugin {
+    /// 将本插件（原版）与其翻译副本对齐，生成原文/译文对
+    ///
+    /// 两个插件分别调用 `extract_strings()`，先按唯一键匹配，EDID 被修改的字符串再按
+    /// FormID、字段类型和索引匹配。译版插件文件名不同时，其自身记录的 FormID 按原版插件名换算。
+    pub fn align_with(&self, translated: &Plugin) -> AlignmentReport {
+        let source_name = self.get_name().to_string();
+        let target_suffix = format!("|{}", translated.get_name());
+
+        let source_strings = self.extract_strings();
+        let mut target_strings = translated.extract_strings();
+        for string in &mut target_strings {
+            if let Some(form_id) = string.form_id.strip_suffix(&target_suffix) {
+                string.form_id = format!("{}|{}", form_id, source_name);
+            }
+        }
+
+        let mut by_key: HashMap<String, usize> = HashMap::new();
+        let mut by_form_id: HashMap<String, usize> = HashMap::new();
+        for (i, string) in target_strings.iter().enumerate() {
+            by_key.entry(string.get_unique_key()).or_insert(i);
+            by_form_id.entry(fallback_key(string)).or_insert(i);
+        }
+
+        let mut used = vec![false; target_strings.len()];
+        let mut matches: Vec<Option<(usize, AlignMethod)>> = vec![None; source_strings.len()];
+
+        // 第一轮：唯一键
+        for (i, string) in source_strings.iter().enumerate() {
+            if let Some(&j) = by_key.get(&string.get_unique_key()) {
+                if !used[j] {
+                    used[j] = true;
+                    matches[i] = Some((j, AlignMethod::UniqueKey));
+                }
+            }
+        }
+
+        // 第二轮：FormID 回退
+        for (i, string) in source_strings.iter().enumerate() {
+            if matches[i].is_some() {
+                continue;
+            }
+            if let Some(&j) = by_form_id.get(&fallback_key(string)) {
+                if !used[j] {
+                    used[j] = true;
+                    matches[i] = Some((j, AlignMethod::FormId));
+                }
+            }
+        }
+
+        let mut report = AlignmentReport {
+            source_plugin: source_name,
+            target_plugin: translated.get_name().to_string(),
+            pairs: Vec::new(),
+            unmatched_source: Vec::new(),
+            unmatched_target: Vec::new(),
+        };
+
+        for (string, matched) in source_strings.into_iter().zip(matches) {
+            match matched {
+                Some((j, method)) => report.pairs.push(AlignedString {
+                    editor_id: string.editor_id,
+                    form_id: string.form_id,
+                    record_type: string.record_type,
+                    subrecord_type: string.subrecord_type,
+                    index: string.index,
+                    source: string.text,
+                    target: target_strings[j].text.clone(),
+                    method,
+                }),
+                None => report.unmatched_source.push(string),
+            }
+        }
+
+        report.unmatched_target = target_strings
+            .into_iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(string, _)| string)
+            .collect();
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::PluginBuilder;
+    use crate::subrecord::Subrecord;
+
+    fn weapon(builder: &mut PluginBuilder, edid: &str, name: &str) {
+        let record = builder.new_record("WEAP", vec![
+            Subrecord::new("EDID", format!("{}\0", edid).into_bytes()),
+            Subrecord::new("FULL", format!("{}\0", name).into_bytes()),
+        ]);
+        builder.add_record(record).unwrap();
+    }
+
+    #[test]
+    fn test_align_with_translated_copy() {
+        let mut original = PluginBuilder::new("MyMod.esp");
+        weapon(&mut original, "Sword", "Iron Sword");
+        weapon(&mut original, "Axe", "Iron Axe");
+        weapon(&mut original, "Bow", "Long Bow");
+        let original = original.build().unwrap();
+
+        // 译版文件名不同，第二条记录的 EDID 被修改，第三条记录被替换为另一类型的记录
+        let mut translated = PluginBuilder::new("MyMod_CN.esp");
+        weapon(&mut translated, "Sword", "铁剑");
+        weapon(&mut translated, "AxeCN", "铁斧");
+        let extra = translated.new_record("MISC", vec![
+            Subrecord::new("EDID", b"Extra\0".to_vec()),
+            Subrecord::new("FULL", "额外\0".as_bytes().to_vec()),
+        ]);
+        translated.add_record(extra).unwrap();
+        let translated = translated.build().unwrap();
+
+        let report = original.align_with(&translated);
+
+        assert_eq!(report.pairs.len(), 2);
+        assert_eq!(report.pairs[0].source, "Iron Sword");
+        assert_eq!(report.pairs[0].target, "铁剑");
+        assert_eq!(report.pairs[0].method, AlignMethod::UniqueKey);
+        assert_eq!(report.pairs[1].target, "铁斧");
+        assert_eq!(report.pairs[1].method, AlignMethod::FormId);
+        assert_eq!(report.fallback_count(), 1);
+
+        assert_eq!(report.unmatched_source.len(), 1);
+        assert_eq!(report.unmatched_source[0].text, "Long Bow");
+        assert_eq!(report.unmatched_target.len(), 1);
+        assert_eq!(report.unmatched_target[0].text, "额外");
+        assert!(report.unmatched_target[0].form_id.ends_with("|MyMod.esp"));
+
+        let translations = report.to_translations();
+        assert_eq!(translations.len(), 2);
+        assert_eq!(translations[1].editor_id.as_deref(), Some("Axe"));
+        assert_eq!(translations[1].text, "铁斧");
+    }
+}
@@ -0,0 +1,425 @@
+use super::forward::CHILD_RECORD_TYPES;
+use super::header::PluginHeader;
+use super::merge::merge_top_level_group;
+use super::Plugin;
+use crate::datatypes::RecordFlags;
+use crate::group::{Group, GroupChild, GroupType};
+use crate::record::Record;
+use crate::subrecord::Subrecord;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// 插件构建器
+///
+/// 无需解析文件即可创建有效的插件：自动生成 TES4 头部（HEDR 版本、记录数、下一个对象 ID），
+/// 按标准顺序创建顶级组，为新记录分配 FormID，并为单元/世界空间/对话子记录计算组位置。
+///
+/// 主文件需在创建新记录之前添加，新记录的主文件索引等于主文件数量。
+///
+/// # 示例
+/// ```rust,ignore
+/// let mut builder = PluginBuilder::new("Test.esp").master("Skyrim.esm").author("me");
+/// let sword = builder.new_record("WEAP", vec![Subrecord::new("EDID", b"MySword\0".to_vec())]);
+/// builder.add_record(sword)?;
+/// let cell = builder.new_record("CELL", vec![]);
+/// let cell_id = builder.add_interior_cell(cell)?;
+/// let reference = builder.new_record("REFR", vec![]);
+/// builder.add_cell_child(cell_id, reference, false)?;
+/// let plugin = builder.build()?;
+/// plugin.write_to_file("Test.esp".into())?;
+/// ```
+#[derive(Debug)]
+pub struct PluginBuilder {
+    path: PathBuf,
+    masters: Vec<String>,
+    /// 头部信息（记录数和下一个对象 ID 在 `build()` 时同步）
+    header: PluginHeader,
+    groups: Vec<Group>,
+    /// 第一次分配 FormID 时的主文件数量（用于检测主文件添加顺序错误）
+    assigned_master_count: Option<usize>,
+    /// 室内单元 FormID
+    interior_cells: HashSet<u32>,
+    /// 室外单元 FormID -> (世界空间 FormID, X, Y)
+    exterior_cells: HashMap<u32, (u32, i32, i32)>,
+}
+
+impl PluginBuilder {
+    /// 创建构建器
+    ///
+    /// # 参数
+    /// * `path` - 插件路径（决定插件名称）
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        PluginBuilder {
+            path: path.into(),
+            masters: Vec::new(),
+            header: PluginHeader::default(),
+            groups: Vec::new(),
+            assigned_master_count: None,
+            interior_cells: HashSet::new(),
+            exterior_cells: HashMap::new(),
+        }
+    }
+
+    /// 添加主文件（重复的主文件会被忽略）
+    pub fn master(mut self, name: &str) -> Self {
+        if !self.masters.iter().any(|m| m.eq_ignore_ascii_case(name)) {
+            self.masters.push(name.to_string());
+        }
+        self
+    }
+
+    /// 设置作者（CNAM）
+    pub fn author(mut self, author: &str) -> Self {
+        self.header.author = Some(author.to_string());
+        self
+    }
+
+    /// 设置描述（SNAM）
+    pub fn description(mut self, description: &str) -> Self {
+        self.header.description = Some(description.to_string());
+        self
+    }
+
+    /// 设置 HEDR 文件版本
+    pub fn version(mut self, version: f32) -> Self {
+        self.header.version = version;
+        self
+    }
+
+    /// 设置 ESM 标志
+    pub fn esm(mut self, enabled: bool) -> Self {
+        self.header.esm = enabled;
+        self
+    }
+
+    /// 设置 ESL (LightMaster) 标志
+    pub fn light(mut self, enabled: bool) -> Self {
+        self.header.light = enabled;
+        self
+    }
+
+    /// 设置本地化标志
+    pub fn localized(mut self, enabled: bool) -> Self {
+        self.header.localized = enabled;
+        self
+    }
+
+    /// 分配下一个新记录 FormID
+    pub fn next_form_id(&mut self) -> u32 {
+        self.assigned_master_count.get_or_insert(self.masters.len());
+        let form_id = ((self.masters.len() as u32) << 24) | self.header.next_object_id;
+        self.header.next_object_id += 1;
+        form_id
+    }
+
+    /// 创建新记录（自动分配 FormID，尚未加入插件）
+    ///
+    /// # Panics
+    /// `record_type` 不是恰好 4 个字节时 panic（同 `Record::new`）。
+    pub fn new_record(&mut self, record_type: &str, subrecords: Vec<Subrecord>) -> Record {
+        let form_id = self.next_form_id();
+        Record::new(record_type, form_id, 0, subrecords)
+    }
+
+    /// 添加位于顶级组中的记录（新记录或覆盖记录）
+    ///
+    /// # 返回
+    /// 记录的 FormID
+    pub fn add_record(&mut self, record: Record) -> Result<u32, Box<dyn std::error::Error>> {
+        if CHILD_RECORD_TYPES.iter().any(|t| record.record_type == *t) {
+            return Err(format!("{} 记录需要父组结构，请使用对应的 add_* 方法", record.record_type).into());
+        }
+
+        let form_id = record.form_id;
+        let mut group = Group::new(record.record_type, GroupType::Normal);
+        group.children.push(GroupChild::Record(record));
+        merge_top_level_group(&mut self.groups, group);
+        Ok(form_id)
+    }
+
+    /// 添加室内单元
+    ///
+    /// 块编号为对象 ID 的个位数，子块编号为十位数。
+    pub fn add_interior_cell(&mut self, cell: Record) -> Result<u32, Box<dyn std::error::Error>> {
+        Self::expect_type(&cell, "CELL")?;
+        let form_id = cell.form_id;
+        self.interior_cells.insert(form_id);
+
+        let skeleton = self.cell_skeleton(form_id, GroupChild::Record(cell))?;
+        merge_top_level_group(&mut self.groups, skeleton);
+        Ok(form_id)
+    }
+
+    /// 添加室外单元
+    ///
+    /// 世界空间记录（新记录或覆盖记录）必须已添加。块坐标为 (x/32, y/32)，
+    /// 子块坐标为 (x/8, y/8)（向下取整）。单元缺少 XCLC 时自动添加坐标字段。
+    pub fn add_exterior_cell(
+        &mut self,
+        worldspace: u32,
+        x: i32,
+        y: i32,
+        mut cell: Record,
+    ) -> Result<u32, Box<dyn std::error::Error>> {
+        Self::expect_type(&cell, "CELL")?;
+        if !self.contains_record(b"WRLD", worldspace) {
+            return Err(format!("世界空间 {:08X} 不在插件中，请先添加 WRLD 记录", worldspace).into());
+        }
+
+        if cell.find_subrecord("XCLC").is_none() {
+            let mut data = Vec::with_capacity(12);
+            data.extend_from_slice(&x.to_le_bytes());
+            data.extend_from_slice(&y.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+            let subrecords = cell.subrecords_mut();
+            let position = subrecords.iter()
+                .rposition(|sr| matches!(sr.record_type.as_bytes(), b"EDID" | b"FULL" | b"DATA"))
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            subrecords.insert(position, Subrecord::new("XCLC", data));
+            cell.mark_modified();
+        }
+
+        let form_id = cell.form_id;
+        self.exterior_cells.insert(form_id, (worldspace, x, y));
+
+        let skeleton = self.cell_skeleton(form_id, GroupChild::Record(cell))?;
+        merge_top_level_group(&mut self.groups, skeleton);
+        Ok(form_id)
+    }
+
+    /// 添加单元子记录（REFR/ACHR 等）
+    ///
+    /// # 参数
+    /// * `cell` - 已添加的单元 FormID
+    /// * `record` - 子记录
+    /// * `persistent` - 是否放入持久子组（同时设置记录的 Persistent 标志）
+    pub fn add_cell_child(
+        &mut self,
+        cell: u32,
+        mut record: Record,
+        persistent: bool,
+    ) -> Result<u32, Box<dyn std::error::Error>> {
+        let (child_type, flag) = if persistent {
+            (GroupType::CellPersistentChildren, RecordFlags::PERSISTENT.bits())
+        } else {
+            (GroupType::CellTemporaryChildren, 0)
+        };
+        record.flags |= flag;
+        let form_id = record.form_id;
+
+        let mut typed_children = Group::new(cell.to_le_bytes(), child_type);
+        typed_children.children.push(GroupChild::Record(record));
+        let mut cell_children = Group::new(cell.to_le_bytes(), GroupType::CellChildren);
+        cell_children.children.push(GroupChild::Group(Box::new(typed_children)));
+
+        let skeleton = self.cell_skeleton(cell, GroupChild::Group(Box::new(cell_children)))?;
+        merge_top_level_group(&mut self.groups, skeleton);
+        Ok(form_id)
+    }
+
+    /// 添加对话主题下的 INFO 记录
+    ///
+    /// 对话主题（DIAL 记录）必须已添加。
+    pub fn add_topic_info(&mut self, topic: u32, info: Record) -> Result<u32, Box<dyn std::error::Error>> {
+        Self::expect_type(&info, "INFO")?;
+        if !self.contains_record(b"DIAL", topic) {
+            return Err(format!("对话主题 {:08X} 不在插件中，请先添加 DIAL 记录", topic).into());
+        }
+
+        let form_id = info.form_id;
+        let mut topic_children = Group::new(topic.to_le_bytes(), GroupType::TopicChildren);
+        topic_children.children.push(GroupChild::Record(info));
+        let mut top = Group::new(*b"DIAL", GroupType::Normal);
+        top.children.push(GroupChild::Group(Box::new(topic_children)));
+
+        merge_top_level_group(&mut self.groups, top);
+        Ok(form_id)
+    }
+
+    /// 生成插件
+    ///
+    /// 写入 TES4 头部（HEDR、CNAM、SNAM、MAST/DATA），并同步记录数和下一个对象 ID。
+    pub fn build(self) -> Result<Plugin, Box<dyn std::error::Error>> {
+        if let Some(count) = self.assigned_master_count {
+            if count != self.masters.len() {
+                return Err("主文件必须在创建新记录之前添加".into());
+            }
+        }
+
+        let mut header = Record::new("TES4", 0, 0, Vec::new());
+        self.header.apply_to(&mut header);
+        let mut plugin = Plugin::from_parts(self.path, header, self.groups)?;
+        plugin.set_masters(self.masters);
+        plugin.update_header_counts();
+
+        Ok(plugin)
+    }
+
+    /// 构建单元所在的组链，`content` 放入单元所在的子块中
+    fn cell_skeleton(&self, cell: u32, content: GroupChild) -> Result<Group, Box<dyn std::error::Error>> {
+        if let Some(&(worldspace, x, y)) = self.exterior_cells.get(&cell) {
+            let block = grid_label(x.div_euclid(32), y.div_euclid(32));
+            let sub_block = grid_label(x.div_euclid(8), y.div_euclid(8));
+
+            let mut sub_block = Group::new(sub_block, GroupType::ExteriorCellSubBlock);
+            sub_block.children.push(content);
+            let mut block = Group::new(block, GroupType::ExteriorCellBlock);
+            block.children.push(GroupChild::Group(Box::new(sub_block)));
+            let mut world_children = Group::new(worldspace.to_le_bytes(), GroupType::WorldChildren);
+            world_children.children.push(GroupChild::Group(Box::new(block)));
+            let mut top = Group::new(*b"WRLD", GroupType::Normal);
+            top.children.push(GroupChild::Group(Box::new(world_children)));
+            return Ok(top);
+        }
+
+        if self.interior_cells.contains(&cell) {
+            let object_id = cell & 0x00FFFFFF;
+            let block = (object_id % 10) as i32;
+            let sub_block = ((object_id / 10) % 10) as i32;
+
+            let mut sub_block = Group::new(sub_block.to_le_bytes(), GroupType::InteriorCellSubBlock);
+            sub_block.children.push(content);
+            let mut block = Group::new(block.to_le_bytes(), GroupType::InteriorCellBlock);
+            block.children.push(GroupChild::Group(Box::new(sub_block)));
+            let mut top = Group::new(*b"CELL", GroupType::Normal);
+            top.children.push(GroupChild::Group(Box::new(block)));
+            return Ok(top);
+        }
+
+        Err(format!("单元 {:08X} 不在插件中，请先添加 CELL 记录", cell).into())
+    }
+
+    /// 检查顶级组中是否存在指定记录
+    fn contains_record(&self, label: &[u8; 4], form_id: u32) -> bool {
+        self.groups.iter()
+            .filter(|g| g.label == label && g.group_type == GroupType::Normal)
+            .any(|g| g.children.iter().any(|c| matches!(c, GroupChild::Record(r) if r.form_id == form_id)))
+    }
+
+    fn expect_type(record: &Record, record_type: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if record.record_type != record_type {
+            return Err(format!("需要 {} 记录，实际为 {}", record_type, record.record_type).into());
+        }
+        Ok(())
+    }
+}
+
+/// 室外块/子块标签：Y 在前，X 在后（各 16 位）
+fn grid_label(x: i32, y: i32) -> [u8; 4] {
+    let mut label = [0u8; 4];
+    label[..2].copy_from_slice(&(y as i16).to_le_bytes());
+    label[2..].copy_from_slice(&(x as i16).to_le_bytes());
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::test_support::{edid, zstring};
+
+    #[test]
+    fn test_build_round_trip() {
+        let mut builder = PluginBuilder::new("Built.esp")
+            .master("Skyrim.esm")
+            .author("Tester")
+            .description("Synthetic fixture");
+
+        let sword = builder.new_record("WEAP", vec![edid("TestSword"), Subrecord::new("FULL", zstring("Test Sword"))]);
+        let sword_id = builder.add_record(sword).unwrap();
+        let misc = builder.new_record("MISC", vec![edid("TestMisc")]);
+        builder.add_record(misc).unwrap();
+
+        let cell = builder.new_record("CELL", vec![edid("TestCell")]);
+        let cell_id = builder.add_interior_cell(cell).unwrap();
+        let reference = builder.new_record("REFR", vec![Subrecord::new("NAME", sword_id.to_le_bytes().to_vec())]);
+        builder.add_cell_child(cell_id, reference, true).unwrap();
+
+        let topic = builder.new_record("DIAL", vec![edid("TestTopic")]);
+        let topic_id = builder.add_record(topic).unwrap();
+        let info = builder.new_record("INFO", vec![Subrecord::new("NAM1", zstring("Hello"))]);
+        builder.add_topic_info(topic_id, info).unwrap();
+
+        let plugin = builder.build().unwrap();
+        assert_eq!(sword_id, 0x01000800);
+        assert_eq!(plugin.masters, vec!["Skyrim.esm"]);
+
+        let labels: Vec<String> = plugin.groups.iter().map(|g| g.get_label_string()).collect();
+        assert_eq!(labels, vec!["MISC", "WEAP", "CELL", "DIAL"]);
+
+        // CELL 对象 ID 0x802 (2050)：块 0，子块 5
+        let block = match &plugin.groups[2].children[0] {
+            GroupChild::Group(block) => block,
+            _ => panic!("expected block group"),
+        };
+        assert_eq!(block.label, 0i32.to_le_bytes());
+        match &block.children[0] {
+            GroupChild::Group(sub_block) => assert_eq!(sub_block.label, 5i32.to_le_bytes()),
+            _ => panic!("expected sub-block group"),
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Built.esp");
+        plugin.write_to_file(path.clone()).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let reloaded = Plugin::load(path.clone()).unwrap();
+        assert_eq!(reloaded.masters, vec!["Skyrim.esm"]);
+        assert_eq!(reloaded.count_records_and_groups(), plugin.count_records_and_groups());
+        let hedr = reloaded.header.find_subrecord("HEDR").unwrap();
+        assert_eq!(u32::from_le_bytes(hedr.data[4..8].try_into().unwrap()), plugin.count_records_and_groups());
+        assert_eq!(u32::from_le_bytes(hedr.data[8..12].try_into().unwrap()), 0x806);
+
+        let strings = reloaded.extract_strings();
+        assert!(strings.iter().any(|s| s.text == "Hello"));
+        assert!(strings.iter().any(|s| s.text == "Test Sword"));
+
+        // 重新写出的文件与原文件一致
+        reloaded.write_to_file(path.clone()).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_exterior_cell_placement() {
+        let mut builder = PluginBuilder::new("World.esp").master("Skyrim.esm");
+        builder.add_record(Record::new("WRLD", 0x0000003C, 0, vec![edid("Tamriel")])).unwrap();
+
+        let cell = builder.new_record("CELL", vec![edid("Outside")]);
+        let cell_id = builder.add_exterior_cell(0x0000003C, -1, 40, cell).unwrap();
+        let plugin = builder.build().unwrap();
+
+        let world_children = match &plugin.groups[0].children[1] {
+            GroupChild::Group(group) => group,
+            _ => panic!("expected world children group"),
+        };
+        assert_eq!(world_children.parent_form_id(), Some(0x0000003C));
+
+        let block = match &world_children.children[0] {
+            GroupChild::Group(group) => group,
+            _ => panic!("expected block group"),
+        };
+        assert_eq!(block.label, grid_label(-1, 1));
+        let sub_block = match &block.children[0] {
+            GroupChild::Group(group) => group,
+            _ => panic!("expected sub-block group"),
+        };
+        assert_eq!(sub_block.label, grid_label(-1, 5));
+
+        let cell = plugin.groups[0].get_records()[1];
+        assert_eq!(cell.form_id, cell_id);
+        let xclc = cell.find_subrecord("XCLC").unwrap();
+        assert_eq!(&xclc.data[..8], &[(-1i32).to_le_bytes(), 40i32.to_le_bytes()].concat()[..]);
+    }
+
+    #[test]
+    fn test_child_requires_parent() {
+        let mut builder = PluginBuilder::new("Test.esp");
+        let reference = builder.new_record("REFR", vec![]);
+        assert!(builder.add_record(reference.clone()).is_err());
+        assert!(builder.add_cell_child(0x00000D00, reference, false).is_err());
+
+        let mut builder = PluginBuilder::new("Test.esp");
+        builder.new_record("MISC", vec![]);
+        assert!(builder.master("Skyrim.esm").build().is_err());
+    }
+}
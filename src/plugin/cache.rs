@@ -0,0 +1,304 @@
+//! 插件索引缓存
+//!
+//! 批量处理时同一批未修改的主文件（Skyrim.esm、Update.esm、DLC 等）会被反复解析。
+//! 索引缓存按插件保存记录位置、FormID/EditorID 索引和字符串位置，
+//! 文件未变化时加载插件只需解析头部记录。
+//!
+//! 缓存以文件大小、修改时间和内容 CRC32 为键；任何一项不一致都视为过期，
+//! 重新完整解析并覆盖缓存。
+
+use super::Plugin;
+use crate::datatypes::{ParseSource, Signature};
+use crate::record::Record;
+use crate::group::{Group, GroupChild};
+use crate::string_file::StringFileType;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// 缓存格式版本（格式变化时递增，旧版本的缓存自动失效）
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// 插件索引缓存目录
+///
+/// 通过 `ParseOptions::cache` 启用，每个插件对应目录中的一个 JSON 文件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginCache {
+    dir: PathBuf,
+}
+
+impl PluginCache {
+    /// 使用指定目录作为缓存目录（不存在时在首次写入时创建）
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        PluginCache { dir: dir.into() }
+    }
+
+    /// 缓存目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 插件对应的缓存文件路径
+    ///
+    /// 文件名包含插件名和完整路径的 CRC32，不同目录中的同名插件互不覆盖。
+    pub fn index_path(&self, plugin_path: &Path) -> PathBuf {
+        let full_path = std::fs::canonicalize(plugin_path).unwrap_or_else(|_| plugin_path.to_path_buf());
+        let mut crc = flate2::Crc::new();
+        crc.update(full_path.to_string_lossy().as_bytes());
+
+        let name = plugin_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        self.dir.join(format!("{}.{:08x}.index.json", name, crc.sum()))
+    }
+
+    /// 读取与 `fingerprint` 一致的缓存（不存在、无法解析或已过期时返回 `None`）
+    ///
+    /// 内容哈希只在大小和修改时间一致时才计算。
+    pub(crate) fn load(&self, plugin_path: &Path, fingerprint: &FileFingerprint, data: &[u8]) -> Option<PluginIndex> {
+        let json = std::fs::read(self.index_path(plugin_path)).ok()?;
+        let mut index: PluginIndex = serde_json::from_slice(&json).ok()?;
+
+        let stale = index.version != CACHE_FORMAT_VERSION
+            || index.fingerprint.size != fingerprint.size
+            || index.fingerprint.modified != fingerprint.modified
+            || index.fingerprint.content_hash != content_hash(data);
+        if stale {
+            #[cfg(debug_assertions)]
+            println!("索引缓存已过期: {}", plugin_path.display());
+            return None;
+        }
+
+        index.build_lookup();
+        Some(index)
+    }
+
+    /// 写入缓存（原子替换已有的缓存文件）
+    pub(crate) fn store(&self, plugin_path: &Path, index: &PluginIndex) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.dir)?;
+        crate::io::esp_io::write_file_atomically(&self.index_path(plugin_path), &mut |writer| {
+            serde_json::to_writer(writer, index)?;
+            Ok(())
+        })
+    }
+}
+
+/// 文件指纹：判断缓存是否对应当前文件内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 修改时间（UNIX 纪元以来的纳秒数，无法获取时为 0）
+    pub modified: u128,
+    /// 文件内容的 CRC32
+    pub content_hash: u32,
+}
+
+impl FileFingerprint {
+    /// 读取文件元数据（内容哈希在写入缓存时才计算）
+    pub(crate) fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+        FileFingerprint { size: metadata.len(), modified, content_hash: 0 }
+    }
+
+    /// 填入文件内容的 CRC32
+    pub(crate) fn with_content_hash(self, data: &[u8]) -> Self {
+        FileFingerprint { content_hash: content_hash(data), ..self }
+    }
+}
+
+/// 文件内容的 CRC32
+fn content_hash(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+/// 子记录中的字符串值（解析 STRING 文件之前的形式）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StringValue {
+    /// 普通插件：子记录中的文本
+    Inline(String),
+    /// 本地化插件：STRING 文件中的字符串
+    Localized {
+        /// STRING 文件类型
+        file_type: StringFileType,
+        /// 字符串 ID
+        string_id: u32,
+    },
+}
+
+/// 缓存中的字符串位置
+///
+/// 本地化插件只保存 StringID，提取时按当前加载的 STRING 文件查找文本，
+/// 因此同一份缓存可用于不同语言。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedString {
+    /// 所在记录的 FormID
+    pub form_id: u32,
+    /// 所在记录的编辑器ID
+    pub editor_id: Option<String>,
+    /// 记录类型
+    pub record_type: Signature,
+    /// 子记录类型
+    pub subrecord_type: Signature,
+    /// 子记录索引（与 `ExtractedString::index` 一致）
+    pub index: i32,
+    /// 字符串值
+    pub value: StringValue,
+}
+
+/// 缓存中的记录位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedRecord {
+    /// FormID
+    pub form_id: u32,
+    /// 记录类型
+    pub record_type: Signature,
+    /// 编辑器ID
+    pub editor_id: Option<String>,
+    /// 记录在文件中的起始偏移（含 24 字节头部）
+    pub offset: u64,
+    /// 记录长度（含头部）
+    pub len: u64,
+}
+
+/// 插件索引：记录位置、FormID/EditorID 索引和字符串位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginIndex {
+    /// 缓存格式版本
+    pub version: u32,
+    /// 源文件指纹
+    pub fingerprint: FileFingerprint,
+    /// 所有记录（按文件顺序，不含头部记录）
+    pub records: Vec<IndexedRecord>,
+    /// 所有字符串位置（按提取顺序）
+    pub strings: Vec<IndexedString>,
+    /// FormID -> `records` 下标
+    #[serde(skip)]
+    by_form_id: HashMap<u32, usize>,
+    /// EditorID -> `records` 下标（重复时取第一个）
+    #[serde(skip)]
+    by_editor_id: HashMap<String, usize>,
+}
+
+impl PluginIndex {
+    /// 按 FormID 查找记录位置
+    pub fn find_form_id(&self, form_id: u32) -> Option<&IndexedRecord> {
+        self.by_form_id.get(&form_id).map(|&i| &self.records[i])
+    }
+
+    /// 按编辑器ID查找记录位置
+    pub fn find_editor_id(&self, editor_id: &str) -> Option<&IndexedRecord> {
+        self.by_editor_id.get(editor_id).map(|&i| &self.records[i])
+    }
+
+    /// 建立 FormID/EditorID 查找表
+    fn build_lookup(&mut self) {
+        self.by_form_id.clear();
+        self.by_editor_id.clear();
+        for (i, record) in self.records.iter().enumerate() {
+            self.by_form_id.entry(record.form_id).or_insert(i);
+            if let Some(editor_id) = &record.editor_id {
+                self.by_editor_id.entry(editor_id.clone()).or_insert(i);
+            }
+        }
+    }
+}
+
+impl Plugin {
+    /// 从完整解析的插件生成索引
+    pub(crate) fn build_index(&self, fingerprint: FileFingerprint) -> PluginIndex {
+        let mut records = Vec::new();
+        for group in &self.groups {
+            Self::collect_indexed_records(group, &mut records);
+        }
+
+        let strings = self.string_sites();
+
+        let mut index = PluginIndex {
+            version: CACHE_FORMAT_VERSION,
+            fingerprint,
+            records,
+            strings,
+            by_form_id: HashMap::new(),
+            by_editor_id: HashMap::new(),
+        };
+        index.build_lookup();
+        index
+    }
+
+    /// 收集组内所有记录的位置（没有源文件位置的记录不计入）
+    fn collect_indexed_records(group: &Group, records: &mut Vec<IndexedRecord>) {
+        for child in &group.children {
+            match child {
+                GroupChild::Group(subgroup) => Self::collect_indexed_records(subgroup, records),
+                GroupChild::Record(record) => {
+                    if let Some(span) = record.source_span {
+                        records.push(IndexedRecord {
+                            form_id: record.form_id,
+                            record_type: record.record_type,
+                            editor_id: record.get_editor_id(),
+                            offset: span.offset,
+                            len: span.len,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// 插件是否从索引缓存加载（只解析了头部记录，`groups` 为空）
+    pub fn is_index_only(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// 从索引缓存加载时使用的索引
+    pub fn index(&self) -> Option<&PluginIndex> {
+        self.index.as_deref()
+    }
+
+    /// 按 FormID 查找记录
+    ///
+    /// 从索引缓存加载的插件按缓存中的偏移从内存映射中单独解析该记录。
+    pub fn find_record(&self, form_id: u32) -> Option<Cow<'_, Record>> {
+        match &self.index {
+            Some(index) => self.parse_indexed_record(index.find_form_id(form_id)?).map(Cow::Owned),
+            None => self.find_loaded_record(|record| record.form_id == form_id).map(Cow::Borrowed),
+        }
+    }
+
+    /// 按编辑器ID查找记录（重复时返回文件中的第一个）
+    pub fn find_record_by_editor_id(&self, editor_id: &str) -> Option<Cow<'_, Record>> {
+        match &self.index {
+            Some(index) => self.parse_indexed_record(index.find_editor_id(editor_id)?).map(Cow::Owned),
+            None => self.find_loaded_record(|record| record.get_editor_id().as_deref() == Some(editor_id))
+                .map(Cow::Borrowed),
+        }
+    }
+
+    /// 在已解析的组中查找记录
+    fn find_loaded_record<F: Fn(&Record) -> bool>(&self, predicate: F) -> Option<&Record> {
+        self.groups.iter()
+            .find_map(|group| group.get_records().into_iter().find(|record| predicate(record)))
+    }
+
+    /// 从内存映射中解析索引指向的记录
+    fn parse_indexed_record(&self, entry: &IndexedRecord) -> Option<Record> {
+        let mmap = self.mmap.as_ref()?;
+        let end = usize::try_from(entry.offset + entry.len).ok().filter(|end| *end <= mmap.len())?;
+        let mut cursor = Cursor::new(&mmap[..end]);
+        cursor.set_position(entry.offset);
+
+        let source = ParseSource::new(self.source_id, mmap.clone());
+        match Record::parse_in_source(&mut cursor, Some(&source)) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                eprintln!("警告: 无法从索引位置解析记录 [{:08X}]: {}", entry.form_id, e);
+                None
+            }
+        }
+    }
+}
@@ -1,12 +1,203 @@
 use super::Plugin;
+use crate::datatypes::Signature;
 use crate::record::Record;
 use crate::group::{Group, GroupChild};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// ESL 可用的最小对象 ID
+const ESL_MIN_OBJECT_ID: u32 = 0x800;
+/// ESL 可用的最大对象 ID
+const ESL_MAX_OBJECT_ID: u32 = 0xFFF;
+/// ESL 插件最多可包含的新记录数
+const ESL_MAX_NEW_RECORDS: usize = (ESL_MAX_OBJECT_ID - ESL_MIN_OBJECT_ID + 1) as usize;
+
+/// ESL 资格判定结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EslVerdict {
+    /// 所有新记录的 FormID 已在 0x800-0xFFF 范围内，可直接设置 LightMaster 标志
+    Eligible,
+    /// 新记录数量符合限制，但需要先重编号（压缩）FormID
+    NeedsCompaction,
+    /// 新记录数量超过 2048 个，无法转换为 ESL
+    Ineligible,
+}
+
+/// 单个 FormID 的重编号映射
+#[derive(Debug, Clone, Serialize)]
+pub struct FormIdMapping {
+    /// 记录类型
+    pub record_type: Signature,
+    /// 编辑器ID
+    pub editor_id: Option<String>,
+    /// 原 FormID
+    pub old_form_id: u32,
+    /// 重编号后的 FormID
+    pub new_form_id: u32,
+}
+
+/// ESL 资格分析报告
+///
+/// 由 `Plugin::analyze_esl()` 生成，不会修改插件。
+/// `mapping` 与 `eslify_formids()` 实际执行的重编号完全一致，可用于预览。
+#[derive(Debug, Clone, Serialize)]
+pub struct EslAnalysis {
+    /// 插件名称
+    pub plugin_name: String,
+    /// 判定结论
+    pub verdict: EslVerdict,
+    /// 是否已经是轻量插件
+    pub is_light: bool,
+    /// 新记录数量（非来自主文件的记录）
+    pub new_record_count: usize,
+    /// 覆盖记录数量（来自主文件的记录）
+    pub override_record_count: usize,
+    /// 超出 0x800-0xFFF 范围的新记录 FormID
+    pub out_of_range_form_ids: Vec<u32>,
+    /// 新增的 CELL 记录 FormID（ESL 中新增 CELL 存在已知的引擎问题）
+    pub new_cells: Vec<u32>,
+    /// 目录中依赖此插件的其他插件
+    pub dependents: Vec<String>,
+    /// 重编号映射（仅包含 FormID 会改变的记录）
+    pub mapping: Vec<FormIdMapping>,
+    /// 警告信息
+    pub warnings: Vec<String>,
+}
+
+impl std::fmt::Display for EslAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== ESL 资格分析 ===")?;
+        writeln!(f, "插件: {}", self.plugin_name)?;
+        writeln!(f, "结论: {}", match self.verdict {
+            EslVerdict::Eligible => "可直接转换为 ESL",
+            EslVerdict::NeedsCompaction => "需要重编号 FormID 后转换",
+            EslVerdict::Ineligible => "无法转换为 ESL",
+        })?;
+        writeln!(f, "已是轻量插件: {}", if self.is_light { "是" } else { "否" })?;
+        writeln!(f, "新记录数: {} / {}", self.new_record_count, ESL_MAX_NEW_RECORDS)?;
+        writeln!(f, "覆盖记录数: {}", self.override_record_count)?;
+        writeln!(f, "超出范围的 FormID: {}", self.out_of_range_form_ids.len())?;
+        writeln!(f, "新增 CELL: {}", self.new_cells.len())?;
+        writeln!(f, "依赖此插件的插件: {}", self.dependents.len())?;
+        for dependent in &self.dependents {
+            writeln!(f, "  - {}", dependent)?;
+        }
+        writeln!(f, "将重编号的记录: {}", self.mapping.len())?;
+        for warning in &self.warnings {
+            writeln!(f, "⚠️ {}", warning)?;
+        }
+        Ok(())
+    }
+}
 
 impl Plugin {
+    /// 分析插件是否可以转换为 ESL (Light Plugin)
+    ///
+    /// 统计新记录数量、检查已超出 0x800-0xFFF 范围的 FormID、新增的 CELL 记录，
+    /// 并计算 `eslify_formids()` 将要应用的重编号映射。此方法不会修改插件。
+    ///
+    /// # 参数
+    /// * `plugin_dir` - 可选插件目录，提供时会扫描目录中依赖此插件的其他插件
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let plugin = Plugin::load("MyMod.esp".into())?;
+    /// let analysis = plugin.analyze_esl(Some(Path::new("Data")))?;
+    /// println!("{}", analysis);
+    /// ```
+    pub fn analyze_esl(&self, plugin_dir: Option<&Path>) -> Result<EslAnalysis, Box<dyn std::error::Error>> {
+        let mut records = Vec::new();
+        for group in &self.groups {
+            Self::extract_group_records(group, &mut records);
+        }
+
+        let mut new_record_count = 0;
+        let mut override_record_count = 0;
+        let mut out_of_range_form_ids = Vec::new();
+        let mut new_cells = Vec::new();
+
+        for record in &records {
+            if !self.is_new_record(record.form_id) {
+                override_record_count += 1;
+                continue;
+            }
+
+            new_record_count += 1;
+
+            let object_id = record.form_id & 0x00FFFFFF;
+            if !(ESL_MIN_OBJECT_ID..=ESL_MAX_OBJECT_ID).contains(&object_id) {
+                out_of_range_form_ids.push(record.form_id);
+            }
+
+            if record.record_type == "CELL" {
+                new_cells.push(record.form_id);
+            }
+        }
+
+        let mapping: Vec<FormIdMapping> = self.compute_esl_mapping(&records)
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(record, old_form_id, new_form_id)| FormIdMapping {
+                record_type: record.record_type,
+                editor_id: record.get_editor_id(),
+                old_form_id,
+                new_form_id,
+            })
+            .collect();
+
+        let dependents = match plugin_dir {
+            Some(dir) => self.find_dependents(dir)?,
+            None => Vec::new(),
+        };
+
+        let verdict = if new_record_count > ESL_MAX_NEW_RECORDS {
+            EslVerdict::Ineligible
+        } else if out_of_range_form_ids.is_empty() {
+            EslVerdict::Eligible
+        } else {
+            EslVerdict::NeedsCompaction
+        };
+
+        let mut warnings = Vec::new();
+        if verdict == EslVerdict::Ineligible {
+            warnings.push(format!(
+                "新记录数 {} 超过 ESL 限制 {}",
+                new_record_count, ESL_MAX_NEW_RECORDS
+            ));
+        }
+        if !new_cells.is_empty() {
+            warnings.push(format!(
+                "插件新增了 {} 个 CELL，ESL 中的新增 CELL 可能导致存档中的单元格修改丢失",
+                new_cells.len()
+            ));
+        }
+        if !dependents.is_empty() && !mapping.is_empty() {
+            warnings.push(format!(
+                "{} 个插件依赖此插件，重编号后需要同步更新这些插件中的引用",
+                dependents.len()
+            ));
+        }
+
+        Ok(EslAnalysis {
+            plugin_name: self.get_name().to_string(),
+            verdict,
+            is_light: self.is_light(),
+            new_record_count,
+            override_record_count,
+            out_of_range_form_ids,
+            new_cells,
+            dependents,
+            mapping,
+            warnings,
+        })
+    }
+
     /// 重编号 FormID 以符合 ESL (Light Plugin) 规范
     ///
     /// 将插件中所有记录的 FormID 重新编号，从 0x800 开始，适用于轻量插件。
-    /// 仅修改属于当前插件的记录（非来自外部主文件的记录）。
+    /// 仅修改属于当前插件的记录（非来自外部主文件的记录），插件内部对这些记录的引用会同步更新。
+    /// 可先调用 `analyze_esl()` 预览将要应用的映射。
     ///
     /// # ESL 限制
     /// - 最多支持 2048 (0x800) 个记录
@@ -14,62 +205,203 @@ impl Plugin {
     ///
     /// # 错误
     /// - 如果记录数超过 2048 个，返回错误
+    /// - 需要重编号且记录中有无法重写的 FormID 字段（CTDA、VMAD、未知记录类型等）时返回错误，插件保持不变
     ///
     /// # 参考
     /// 根据 mapping 文档的 Python 版本 `eslify_formids()` 方法实现
     pub fn eslify_formids(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // 提取所有记录的可变引用
-        let mut all_records = Vec::new();
-        for group in &mut self.groups {
-            Self::extract_group_records_mut(group, &mut all_records);
+        let mapping: HashMap<u32, u32> = {
+            let mut records = Vec::new();
+            for group in &self.groups {
+                Self::extract_group_records(group, &mut records);
+            }
+
+            let new_record_count = records.iter()
+                .filter(|record| self.is_new_record(record.form_id))
+                .count();
+
+            // ESL 限制：最多 2048 (0x800) 个记录
+            if new_record_count > ESL_MAX_NEW_RECORDS {
+                return Err(format!(
+                    "ESL 插件记录数超过限制！最多支持 {} 个记录，当前有 {} 个",
+                    ESL_MAX_NEW_RECORDS, new_record_count
+                ).into());
+            }
+
+            self.compute_esl_mapping(&records)
+                .into_iter()
+                .filter(|(_, old, new)| old != new)
+                .map(|(_, old, new)| (old, new))
+                .collect()
+        };
+
+        if !mapping.is_empty() {
+            let uncovered = self.uncovered_form_id_fields();
+            if let Some(first) = uncovered.first() {
+                return Err(format!(
+                    "{} 处子记录中的 FormID 无法重写（首个: {}），拒绝 ESL 重编号",
+                    uncovered.len(), first
+                ).into());
+            }
         }
 
-        // 从 0x800 开始编号
-        let mut current_formid = 0x800u32;
+        // 同时重写记录自身 FormID 与插件内部对这些记录的引用
+        let _renumbered = self.remap_form_ids(|form_id| {
+            mapping.get(&form_id).copied().unwrap_or(form_id)
+        });
+
+        #[cfg(debug_assertions)]
+        println!("ESL FormID 重编号完成：共 {} 个记录", _renumbered);
+
+        Ok(())
+    }
+
+    /// 计算 ESL 重编号映射
+    ///
+    /// 按记录出现顺序为新记录从 0x800 开始分配对象 ID，保留主文件索引（高8位）。
+    /// 覆盖记录保持不变。返回值与 `records` 一一对应：(记录, 原 FormID, 新 FormID)。
+    fn compute_esl_mapping<'a>(&self, records: &[&'a Record]) -> Vec<(&'a Record, u32, u32)> {
+        let mut next_object_id = ESL_MIN_OBJECT_ID;
+
+        records.iter().map(|record| {
+            if self.is_new_record(record.form_id) {
+                let new_form_id = (record.form_id & 0xFF000000) | (next_object_id & ESL_MAX_OBJECT_ID);
+                next_object_id += 1;
+                (*record, record.form_id, new_form_id)
+            } else {
+                (*record, record.form_id, record.form_id)
+            }
+        }).collect()
+    }
 
-        for record in all_records {
-            // 获取主文件索引（FormID 高字节）
-            let master_index = (record.form_id >> 24) as usize;
+    /// 判断 FormID 是否属于当前插件新增的记录（非来自外部主文件）
+    pub(crate) fn is_new_record(&self, form_id: u32) -> bool {
+        (form_id >> 24) as usize >= self.masters.len()
+    }
 
-            // 仅修改属于当前插件的记录（非外部主文件）
-            if master_index >= self.masters.len() {
-                // 保留高20位，替换低12位
-                let high_bits = record.form_id & 0xFFFFF000;
-                let new_formid = high_bits | (current_formid & 0xFFF);
+    /// 扫描目录，找出将当前插件列为主文件的其他插件
+    fn find_dependents(&self, plugin_dir: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let own_name = self.get_name().to_lowercase();
+        let mut dependents = Vec::new();
 
-                record.form_id = new_formid;
-                record.is_modified = true;
+        for entry in std::fs::read_dir(plugin_dir)? {
+            let path = entry?.path();
+            if !crate::is_supported_file(&path) {
+                continue;
+            }
 
-                current_formid += 1;
+            let file_name = match path.file_name().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            if file_name.to_lowercase() == own_name {
+                continue;
+            }
 
-                // ESL 限制：最多 2048 (0x800) 个记录
-                if current_formid > 0xFFF {
-                    return Err(format!(
-                        "ESL 插件记录数超过限制！最多支持 2048 个记录，当前已处理 {} 个",
-                        current_formid - 0x800
-                    ).into());
+            match Self::read_masters_from_file(&path) {
+                Ok(masters) => {
+                    if masters.iter().any(|m| m.to_lowercase() == own_name) {
+                        dependents.push(file_name);
+                    }
+                }
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("警告: 无法读取插件头部 {:?}: {}", path, _e);
                 }
             }
         }
 
-        #[cfg(debug_assertions)]
-        println!("ESL FormID 重编号完成：共 {} 个记录", current_formid - 0x800);
-
-        Ok(())
+        dependents.sort();
+        Ok(dependents)
     }
 
-    /// 递归提取组中所有记录的可变引用
-    fn extract_group_records_mut<'a>(
-        group: &'a mut Group,
-        records: &mut Vec<&'a mut Record>
-    ) {
-        for child in &mut group.children {
+    /// 递归提取组中所有记录的引用
+    fn extract_group_records<'a>(group: &'a Group, records: &mut Vec<&'a Record>) {
+        for child in &group.children {
             match child {
                 GroupChild::Record(record) => records.push(record),
                 GroupChild::Group(nested_group) => {
-                    Self::extract_group_records_mut(nested_group, records);
+                    Self::extract_group_records(nested_group, records);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::test_support::{group, plugin_with_groups};
+    use crate::subrecord::Subrecord;
+
+    fn create_test_plugin(form_ids: &[(&str, u32)]) -> Plugin {
+        let records = form_ids.iter()
+            .map(|(record_type, form_id)| Record::new(record_type, *form_id, 0, vec![]))
+            .collect();
+        plugin_with_groups("Test.esp", &["Skyrim.esm"], vec![group(b"MISC", records)])
+    }
+
+    #[test]
+    fn test_analyze_esl_needs_compaction() {
+        let plugin = create_test_plugin(&[
+            ("MISC", 0x00012345), // 覆盖记录
+            ("MISC", 0x01000801),
+            ("MISC", 0x01001D8A),
+            ("CELL", 0x01000900),
+        ]);
+
+        let analysis = plugin.analyze_esl(None).unwrap();
+        assert_eq!(analysis.verdict, EslVerdict::NeedsCompaction);
+        assert_eq!(analysis.new_record_count, 3);
+        assert_eq!(analysis.override_record_count, 1);
+        assert_eq!(analysis.out_of_range_form_ids, vec![0x01001D8A]);
+        assert_eq!(analysis.new_cells, vec![0x01000900]);
+
+        let new_ids: Vec<u32> = analysis.mapping.iter().map(|m| m.new_form_id).collect();
+        assert_eq!(new_ids, vec![0x01000800, 0x01000801, 0x01000802]);
+    }
+
+    #[test]
+    fn test_eslify_matches_analysis() {
+        let mut plugin = create_test_plugin(&[
+            ("MISC", 0x01000801),
+            ("MISC", 0x01001D8A),
+        ]);
+
+        let analysis = plugin.analyze_esl(None).unwrap();
+        plugin.eslify_formids().unwrap();
+
+        let records = plugin.groups[0].get_records();
+        for mapping in &analysis.mapping {
+            assert!(records.iter().any(|r| r.form_id == mapping.new_form_id));
+        }
+        assert!(plugin.analyze_esl(None).unwrap().mapping.is_empty());
+        assert_eq!(plugin.analyze_esl(None).unwrap().verdict, EslVerdict::Eligible);
+    }
+
+    #[test]
+    fn test_eslify_updates_references() {
+        let mut plugin = create_test_plugin(&[("MISC", 0x01001D8A)]);
+        plugin.groups[0].children.push(GroupChild::Record(Record::new("MISC", 0x00012345, 0, vec![
+            Subrecord::new("KWDA", 0x01001D8Au32.to_le_bytes().to_vec()),
+        ])));
+
+        plugin.eslify_formids().unwrap();
+
+        let records = plugin.groups[0].get_records();
+        assert_eq!(records[0].form_id, 0x01000800);
+        assert_eq!(records[1].find_subrecord("KWDA").unwrap().data, 0x01000800u32.to_le_bytes());
+        assert!(records[1].is_modified);
+    }
+
+    #[test]
+    fn test_eslify_refuses_uncovered_fields() {
+        let mut plugin = create_test_plugin(&[("MISC", 0x01001D8A)]);
+        plugin.groups[0].children.push(GroupChild::Record(Record::new("MISC", 0x01001D8B, 0, vec![
+            Subrecord::new("VMAD", vec![0; 16]),
+        ])));
+
+        assert!(plugin.eslify_formids().is_err());
+        assert_eq!(plugin.groups[0].get_records()[0].form_id, 0x01001D8A);
+    }
+}
@@ -0,0 +1,310 @@
+use super::Plugin;
+use crate::form_id_fields::FormIdFields;
+use crate::group::{Group, GroupChild, GroupType};
+use crate::record::Record;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+
+/// 只能出现在子组中的记录类型（需要父组结构，不能直接放入顶级组）
+pub(crate) const CHILD_RECORD_TYPES: &[&str] = &[
+    "CELL", "REFR", "ACHR", "PGRE", "PHZD", "PMIS", "PARW", "PBAR", "PBEA", "PCON",
+    "PFLA", "NAVM", "LAND", "INFO",
+];
+
+/// 记录转发结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ForwardReport {
+    /// 转发的记录在目标插件中的 FormID（与请求顺序一致）
+    pub forwarded: Vec<u32>,
+    /// 目标插件中被覆盖替换的已有记录数量
+    pub replaced: usize,
+    /// 自动添加到目标插件的主文件
+    pub added_masters: Vec<String>,
+}
+
+impl Plugin {
+    /// 插入顶级组
+    ///
+    /// 已存在同类型的顶级组时按 FormID 合并记录（已有记录被替换），
+    /// 否则按 `TOP_LEVEL_GROUP_ORDER` 插入到正确位置。
+    ///
+    /// # 返回
+    /// 被替换的旧记录
+    pub fn insert_group(&mut self, group: Group) -> Vec<Record> {
+        self.merge_top_level_group(group)
+    }
+
+    /// 插入记录到其类型对应的顶级组
+    ///
+    /// 仅适用于位于顶级组中的记录；CELL/REFR/INFO 等需要父组结构的记录
+    /// 请使用 `forward_records()` 或 `insert_group()`。
+    ///
+    /// # 返回
+    /// 被替换的同 FormID 旧记录
+    pub fn insert_record(&mut self, record: Record) -> Result<Option<Record>, Box<dyn std::error::Error>> {
+        if CHILD_RECORD_TYPES.iter().any(|t| record.record_type == *t) {
+            return Err(format!(
+                "{} 记录需要父组结构，请使用 forward_records() 或 insert_group()",
+                record.record_type
+            ).into());
+        }
+
+        let mut group = Group::new(record.record_type, GroupType::Normal);
+        group.children.push(GroupChild::Record(record));
+        Ok(self.insert_group(group).into_iter().next())
+    }
+
+    /// 从另一个插件复制记录作为覆盖记录
+    ///
+    /// 记录连同其父组链（WRLD/CELL/DIAL 及对应子组）一起复制到当前插件。
+    /// 记录引用的主文件会自动添加到当前插件的主文件列表，所有 FormID 重映射到当前插件的索引。
+    /// 当前插件中已有的同 FormID 记录会被替换；仅为维持结构而复制的父记录不会覆盖已有记录。
+    /// 主文件索引需要改变时，如果转发的记录中有无法重映射的 FormID 字段（CTDA、VMAD 等），
+    /// 返回错误且插件保持不变。
+    ///
+    /// # 参数
+    /// * `source` - 源插件
+    /// * `form_ids` - 要转发的记录（源插件中的 FormID）
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let source = Plugin::load("Source.esp".into())?;
+    /// let mut patch = Plugin::load("Patch.esp".into())?;
+    /// let report = patch.forward_records(&source, &[0x01000801])?;
+    /// patch.write_to_file("Patch.esp".into())?;
+    /// ```
+    pub fn forward_records(
+        &mut self,
+        source: &Plugin,
+        form_ids: &[u32],
+    ) -> Result<ForwardReport, Box<dyn std::error::Error>> {
+        let wanted: HashSet<u32> = form_ids.iter().copied().collect();
+
+        // 1. 复制记录及其父组链
+        let mut found = HashSet::new();
+        let mut skeletons = Vec::new();
+        for group in &source.groups {
+            let skeleton = group.clone_filtered(|record| {
+                if wanted.contains(&record.form_id) {
+                    found.insert(record.form_id);
+                    true
+                } else {
+                    false
+                }
+            });
+            skeletons.extend(skeleton);
+        }
+        if let Some(missing) = form_ids.iter().find(|form_id| !found.contains(form_id)) {
+            return Err(format!("源插件中不存在 FormID {:08X}", missing).into());
+        }
+
+        // 2. 收集缺少的主文件（按源插件中的主文件顺序）
+        let fields = FormIdFields::embedded();
+        let mut references = Vec::new();
+        for skeleton in &skeletons {
+            Self::collect_group_references(skeleton, fields, &mut references);
+        }
+
+        let source_name = source.get_name().to_string();
+        let own_name = self.get_name().to_string();
+        let source_master = |index: usize| source.masters.get(index).unwrap_or(&source_name).clone();
+
+        let source_indices: BTreeSet<usize> = references.iter()
+            .map(|reference| ((reference.referenced_form_id >> 24) as usize).min(source.masters.len()))
+            .collect();
+        let mut added_masters: Vec<String> = Vec::new();
+        for index in source_indices {
+            let name = source_master(index);
+            if !name.eq_ignore_ascii_case(&own_name)
+                && self.find_master(&name).is_none()
+                && !added_masters.iter().any(|added| added.eq_ignore_ascii_case(&name))
+            {
+                added_masters.push(name);
+            }
+        }
+
+        // 3. 源插件主文件索引 -> 当前插件主文件索引（含待添加的主文件）
+        let new_masters: Vec<&String> = self.masters.iter().chain(&added_masters).collect();
+        let target_indices: Vec<u32> = (0..=source.masters.len())
+            .map(|index| {
+                let name = source_master(index);
+                new_masters.iter()
+                    .position(|master| master.eq_ignore_ascii_case(&name))
+                    .unwrap_or(new_masters.len()) as u32
+            })
+            .collect();
+
+        // 主文件索引发生变化时，无法重写的 FormID 字段会指向错误的主文件
+        let identity = target_indices.iter().enumerate().all(|(i, &j)| i as u32 == j);
+        if !identity {
+            let mut uncovered = Vec::new();
+            for skeleton in &skeletons {
+                Self::collect_group_uncovered(skeleton, fields, &mut uncovered);
+            }
+            if let Some(first) = uncovered.first() {
+                return Err(format!(
+                    "{} 处子记录中的 FormID 无法重映射（首个: {}），拒绝转发",
+                    uncovered.len(), first
+                ).into());
+            }
+        }
+
+        let added: Vec<&str> = added_masters.iter().map(String::as_str).collect();
+        self.add_masters(&added)?;
+
+        let remap = |form_id: u32| {
+            let index = ((form_id >> 24) as usize).min(source.masters.len());
+            (target_indices[index] << 24) | (form_id & 0x00FFFFFF)
+        };
+        let forwarded: Vec<u32> = form_ids.iter().map(|form_id| remap(*form_id)).collect();
+        let forwarded_set: HashSet<u32> = forwarded.iter().copied().collect();
+
+        // 4. 合并到当前插件：已有的父记录不复制，子组直接合并到已有父记录之后
+        let existing: HashSet<u32> = self.groups.iter()
+            .flat_map(|group| group.get_records())
+            .map(|record| record.form_id)
+            .collect();
+        let mut replaced = 0;
+        for mut skeleton in skeletons {
+            Self::remap_group(&mut skeleton, fields, &remap);
+            strip_existing_parents(&mut skeleton, &existing, &forwarded_set);
+            replaced += self.merge_top_level_group(skeleton).len();
+        }
+
+        self.update_header_counts();
+
+        #[cfg(debug_assertions)]
+        println!(
+            "记录转发完成：{} 个记录，替换 {} 个，新增主文件 {} 个",
+            forwarded.len(), replaced, added_masters.len()
+        );
+
+        Ok(ForwardReport {
+            forwarded,
+            replaced,
+            added_masters,
+        })
+    }
+}
+
+/// 移除骨架中当前插件已有的父记录（转发的记录除外）
+fn strip_existing_parents(group: &mut Group, existing: &HashSet<u32>, forwarded: &HashSet<u32>) {
+    group.children.retain_mut(|child| match child {
+        GroupChild::Record(record) => {
+            forwarded.contains(&record.form_id) || !existing.contains(&record.form_id)
+        }
+        GroupChild::Group(nested) => {
+            strip_existing_parents(nested, existing, forwarded);
+            true
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::test_support::{edid, group, plugin_with_groups};
+    use crate::subrecord::Subrecord;
+
+    /// 创建室内单元组结构：CELL 顶级组 -> 块 -> 子块 -> CELL [+ 子组 -> 临时子组 -> 引用]
+    fn cell_group(cell: Record, references: Vec<Record>) -> Group {
+        let cell_id = cell.form_id;
+        let mut sub_block = Group::new([0; 4], GroupType::InteriorCellSubBlock);
+        sub_block.children.push(GroupChild::Record(cell));
+
+        if !references.is_empty() {
+            let mut temporary = Group::new(cell_id.to_le_bytes(), GroupType::CellTemporaryChildren);
+            temporary.children.extend(references.into_iter().map(GroupChild::Record));
+            let mut children = Group::new(cell_id.to_le_bytes(), GroupType::CellChildren);
+            children.children.push(GroupChild::Group(Box::new(temporary)));
+            sub_block.children.push(GroupChild::Group(Box::new(children)));
+        }
+
+        let mut block = Group::new([0; 4], GroupType::InteriorCellBlock);
+        block.children.push(GroupChild::Group(Box::new(sub_block)));
+        let mut top = Group::new(*b"CELL", GroupType::Normal);
+        top.children.push(GroupChild::Group(Box::new(block)));
+        top
+    }
+
+    #[test]
+    fn test_forward_record_with_parent_chain() {
+        let source = plugin_with_groups("Source.esp", &["Skyrim.esm"], vec![
+            cell_group(Record::new("CELL", 0x00000D00, 0, vec![edid("SourceCell")]), vec![
+                Record::new("REFR", 0x01000801, 0, vec![Subrecord::new("NAME", 0x01000800u32.to_le_bytes().to_vec())]),
+                Record::new("REFR", 0x01000802, 0, vec![]),
+            ]),
+            group(b"WEAP", vec![Record::new("WEAP", 0x01000800, 0, vec![])]),
+        ]);
+
+        let mut target = plugin_with_groups("Target.esp", &["Skyrim.esm", "Update.esm"], vec![
+            group(b"WEAP", vec![Record::new("WEAP", 0x02000800, 0, vec![])]),
+            cell_group(Record::new("CELL", 0x00000D00, 0, vec![edid("TargetCell")]), vec![]),
+        ]);
+
+        let report = target.forward_records(&source, &[0x01000801, 0x01000800]).unwrap();
+
+        assert_eq!(report.added_masters, vec!["Source.esp"]);
+        assert_eq!(report.forwarded, vec![0x02000801, 0x02000800]);
+        assert_eq!(report.replaced, 0);
+        assert_eq!(target.masters, vec!["Skyrim.esm", "Update.esm", "Source.esp"]);
+
+        // 目标插件自身记录的索引随主文件增加而后移
+        let weapons: Vec<u32> = target.groups[0].get_records().iter().map(|r| r.form_id).collect();
+        assert_eq!(weapons, vec![0x03000800, 0x02000800]);
+
+        // 已有的 CELL 覆盖不被父记录替换，引用插入到 CELL 之后的子组中
+        let cells = target.groups[1].get_records();
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].get_editor_id().as_deref(), Some("TargetCell"));
+        assert_eq!(cells[1].form_id, 0x02000801);
+        assert_eq!(cells[1].find_subrecord("NAME").unwrap().data, 0x02000800u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_forward_refuses_uncovered_fields_on_master_order_change() {
+        // 源插件与目标插件的主文件顺序不同
+        let source = plugin_with_groups("Source.esp", &["Update.esm", "Skyrim.esm"], vec![
+            group(b"WEAP", vec![Record::new("WEAP", 0x02000801, 0, vec![
+                Subrecord::new("KWDA", [0x00000ABCu32.to_le_bytes(), 0x01000123u32.to_le_bytes()].concat()),
+            ])]),
+            group(b"PERK", vec![Record::new("PERK", 0x02000800, 0, vec![
+                edid("TestPerk"),
+                Subrecord::new("CTDA", vec![0; 32]),
+            ])]),
+        ]);
+        let mut target = plugin_with_groups("Target.esp", &["Skyrim.esm", "Update.esm"], vec![]);
+
+        assert!(target.forward_records(&source, &[0x02000800]).is_err());
+        assert_eq!(target.masters, vec!["Skyrim.esm", "Update.esm"]);
+        assert!(target.groups.is_empty());
+
+        // 可重映射的字段正常转发
+        let report = target.forward_records(&source, &[0x02000801]).unwrap();
+        assert_eq!(report.forwarded, vec![0x02000801]);
+        let weapon = target.groups[0].get_records()[0];
+        assert_eq!(
+            weapon.find_subrecord("KWDA").unwrap().data,
+            [0x01000ABCu32.to_le_bytes(), 0x00000123u32.to_le_bytes()].concat()
+        );
+
+        // 主文件索引不变时无需重映射，允许转发
+        let source = plugin_with_groups("Source.esp", &["Skyrim.esm"], vec![
+            group(b"PERK", vec![Record::new("PERK", 0x01000800, 0, vec![Subrecord::new("CTDA", vec![0; 32])])]),
+        ]);
+        let mut target = plugin_with_groups("Target.esp", &["Skyrim.esm"], vec![]);
+        assert_eq!(target.forward_records(&source, &[0x01000800]).unwrap().forwarded, vec![0x01000800]);
+    }
+
+    #[test]
+    fn test_insert_record() {
+        let mut plugin = plugin_with_groups("Target.esp", &["Skyrim.esm"], vec![]);
+        assert!(plugin.insert_record(Record::new("WEAP", 0x00012EB7, 0, vec![])).unwrap().is_none());
+        assert!(plugin.insert_record(Record::new("ARMO", 0x00012E49, 0, vec![])).unwrap().is_none());
+        assert!(plugin.insert_record(Record::new("WEAP", 0x00012EB7, 0, vec![])).unwrap().is_some());
+        assert!(plugin.insert_record(Record::new("REFR", 0x00012345, 0, vec![])).is_err());
+
+        let labels: Vec<String> = plugin.groups.iter().map(|g| g.get_label_string()).collect();
+        assert_eq!(labels, vec!["ARMO", "WEAP"]);
+    }
+}
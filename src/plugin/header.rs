@@ -0,0 +1,272 @@
+use super::Plugin;
+use crate::datatypes::{zstring, RawString, RecordFlags, Signature};
+use crate::group::{Group, GroupChild};
+use crate::record::Record;
+use crate::subrecord::Subrecord;
+
+/// 新建 HEDR 时使用的文件版本（Skyrim SE）
+pub(crate) const DEFAULT_HEDR_VERSION: f32 = 1.71;
+
+/// TES4 头部子记录的标准顺序（用于插入缺失的子记录）
+const HEADER_SUBRECORD_ORDER: &[&str] = &[
+    "HEDR", "OFST", "DELE", "CNAM", "SNAM", "MAST", "DATA", "ONAM", "INTV", "INCC",
+];
+
+/// TES4 头部的类型化视图
+///
+/// 通过 `Plugin::header_info()` 读取，修改字段后通过 `Plugin::set_header_info()` 写回。
+/// 主文件列表不在此结构中，请使用 `add_master()` 等主文件管理方法。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginHeader {
+    /// 文件版本（HEDR）
+    pub version: f32,
+    /// 记录与组的数量（HEDR）
+    pub record_count: u32,
+    /// 下一个可用对象 ID（HEDR）
+    pub next_object_id: u32,
+    /// 作者（CNAM）
+    pub author: Option<String>,
+    /// 描述（SNAM）
+    pub description: Option<String>,
+    /// ESM 标志
+    pub esm: bool,
+    /// ESL (LightMaster) 标志
+    pub light: bool,
+    /// 本地化标志
+    pub localized: bool,
+    /// 覆盖的主文件记录列表（ONAM）
+    pub overridden_forms: Vec<u32>,
+}
+
+impl Default for PluginHeader {
+    fn default() -> Self {
+        PluginHeader {
+            version: DEFAULT_HEDR_VERSION,
+            record_count: 0,
+            next_object_id: 0x800,
+            author: None,
+            description: None,
+            esm: false,
+            light: false,
+            localized: false,
+            overridden_forms: Vec::new(),
+        }
+    }
+}
+
+impl PluginHeader {
+    /// 从 TES4 头部记录读取
+    pub fn from_record(record: &Record) -> Self {
+        let mut header = PluginHeader::default();
+
+        if let Some(hedr) = record.find_subrecord("HEDR").filter(|sr| sr.data.len() >= 12) {
+            let data = &hedr.data;
+            header.version = f32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            header.record_count = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+            header.next_object_id = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+        }
+
+        header.author = record.find_subrecord("CNAM").map(|sr| RawString::parse_zstring(&sr.data).content);
+        header.description = record.find_subrecord("SNAM").map(|sr| RawString::parse_zstring(&sr.data).content);
+        header.esm = record.flags & RecordFlags::MASTER_FILE.bits() != 0;
+        header.light = record.flags & RecordFlags::LIGHT_MASTER.bits() != 0;
+        header.localized = record.flags & RecordFlags::LOCALIZED.bits() != 0;
+        header.overridden_forms = record.find_subrecord("ONAM")
+            .map(|sr| sr.data.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect())
+            .unwrap_or_default();
+
+        header
+    }
+
+    /// 写回 TES4 头部记录
+    ///
+    /// 只改写本结构描述的字段和标志位，其余子记录（MAST/DATA、OFST、INTV 等）和标志保持不变。
+    /// 有变化时记录被标记为已修改。
+    pub fn apply_to(&self, record: &mut Record) {
+        let mut hedr = Vec::with_capacity(12);
+        hedr.extend_from_slice(&self.version.to_le_bytes());
+        hedr.extend_from_slice(&self.record_count.to_le_bytes());
+        hedr.extend_from_slice(&self.next_object_id.to_le_bytes());
+
+        let onam = (!self.overridden_forms.is_empty()).then(|| {
+            self.overridden_forms.iter().flat_map(|form_id| form_id.to_le_bytes()).collect()
+        });
+
+        let mut changed = false;
+        changed |= set_header_subrecord(record, "HEDR", Some(hedr));
+        changed |= set_header_subrecord(record, "CNAM", self.author.as_deref().map(zstring));
+        changed |= set_header_subrecord(record, "SNAM", self.description.as_deref().map(zstring));
+        changed |= set_header_subrecord(record, "ONAM", onam);
+
+        let mut flags = record.flags;
+        for (flag, enabled) in [
+            (RecordFlags::MASTER_FILE, self.esm),
+            (RecordFlags::LIGHT_MASTER, self.light),
+            (RecordFlags::LOCALIZED, self.localized),
+        ] {
+            if enabled {
+                flags |= flag.bits();
+            } else {
+                flags &= !flag.bits();
+            }
+        }
+        if flags != record.flags {
+            record.flags = flags;
+            changed = true;
+        }
+
+        if changed {
+            record.is_modified = true;
+        }
+    }
+}
+
+/// 设置头部子记录（`None` 表示删除），缺失时按标准顺序插入。返回是否有变化
+fn set_header_subrecord(record: &mut Record, subrecord_type: &str, data: Option<Vec<u8>>) -> bool {
+    let subrecords = record.subrecords_mut();
+    let existing = subrecords.iter().position(|sr| sr.record_type == subrecord_type);
+
+    match (existing, data) {
+        (Some(index), Some(data)) => {
+            if subrecords[index].data == data {
+                return false;
+            }
+            subrecords[index].set_data(data);
+        }
+        (Some(index), None) => {
+            subrecords.remove(index);
+        }
+        (None, Some(data)) => {
+            let rank = |t: Signature| HEADER_SUBRECORD_ORDER.iter().position(|o| t == *o).unwrap_or(HEADER_SUBRECORD_ORDER.len());
+            let own_rank = rank(Signature::from_name(subrecord_type));
+            let position = subrecords.iter()
+                .position(|sr| rank(sr.record_type) > own_rank)
+                .unwrap_or(subrecords.len());
+            subrecords.insert(position, Subrecord::new(subrecord_type, data));
+        }
+        (None, None) => return false,
+    }
+    true
+}
+
+impl Plugin {
+    /// 读取类型化的头部信息
+    pub fn header_info(&self) -> PluginHeader {
+        PluginHeader::from_record(&self.header)
+    }
+
+    /// 写回头部信息（见 `PluginHeader::apply_to`）
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let mut info = plugin.header_info();
+    /// info.author = Some("Translator".to_string());
+    /// info.light = true;
+    /// plugin.set_header_info(&info);
+    /// ```
+    pub fn set_header_info(&mut self, header: &PluginHeader) {
+        header.apply_to(&mut self.header);
+    }
+
+    /// 统计记录与组的总数（即 HEDR 中的记录数，不含头部记录）
+    pub fn count_records_and_groups(&self) -> u32 {
+        fn count_group(group: &Group) -> u32 {
+            1 + group.children.iter().map(|child| match child {
+                GroupChild::Group(nested) => count_group(nested),
+                GroupChild::Record(_) => 1,
+            }).sum::<u32>()
+        }
+
+        self.groups.iter().map(count_group).sum()
+    }
+
+    /// 同步头部 HEDR 中的记录数和下一个可用对象 ID
+    ///
+    /// 下一个对象 ID 至少为插件自身记录的最大对象 ID + 1（且不小于 0x800）。
+    /// 头部缺少 HEDR 时会新建一个。
+    pub fn update_header_counts(&mut self) {
+        let record_count = self.count_records_and_groups();
+
+        let max_object_id = self.groups.iter()
+            .flat_map(|group| group.get_records())
+            .filter(|record| self.is_new_record(record.form_id))
+            .map(|record| record.form_id & 0x00FFFFFF)
+            .max();
+
+        let mut info = self.header_info();
+        info.record_count = record_count;
+        info.next_object_id = match max_object_id {
+            Some(max) => info.next_object_id.max(max + 1).max(0x800),
+            None => info.next_object_id.max(0x800),
+        };
+        info.apply_to(&mut self.header);
+    }
+
+    /// 头部 HEDR 记录数与实际不一致时，返回修正后的头部副本（写入文件时使用）
+    pub(crate) fn header_with_synced_count(&self) -> Option<Record> {
+        let hedr = self.header.find_subrecord("HEDR").filter(|sr| sr.data.len() >= 12)?;
+        let record_count = self.count_records_and_groups();
+        if hedr.data[4..8] == record_count.to_le_bytes() {
+            return None;
+        }
+
+        let mut header = self.header.clone();
+        let mut info = PluginHeader::from_record(&header);
+        info.record_count = record_count;
+        info.apply_to(&mut header);
+        Some(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::test_support::{builder, group};
+
+    fn create_test_plugin() -> Plugin {
+        let mut plugin = builder("Test.esp", &["Skyrim.esm"]).version(1.7).build().unwrap();
+        // 未知的头部子记录保持原位
+        plugin.header.subrecords_mut().push(Subrecord::new("INTV", vec![1, 0, 0, 0]));
+        plugin
+    }
+
+    #[test]
+    fn test_header_info_round_trip() {
+        let mut plugin = create_test_plugin();
+        let mut info = plugin.header_info();
+        assert_eq!(info.version, 1.7);
+        assert_eq!(info.author, None);
+
+        info.author = Some("Author".to_string());
+        info.description = Some("Description".to_string());
+        info.light = true;
+        info.overridden_forms = vec![0x00012EB7];
+        plugin.set_header_info(&info);
+
+        assert_eq!(plugin.header_info(), info);
+        assert!(plugin.is_light());
+        let order: Vec<Signature> = plugin.header.subrecords().iter().map(|sr| sr.record_type).collect();
+        assert_eq!(order, vec!["HEDR", "CNAM", "SNAM", "MAST", "DATA", "ONAM", "INTV"]);
+
+        info.author = None;
+        info.overridden_forms.clear();
+        plugin.set_header_info(&info);
+        let order: Vec<Signature> = plugin.header.subrecords().iter().map(|sr| sr.record_type).collect();
+        assert_eq!(order, vec!["HEDR", "SNAM", "MAST", "DATA", "INTV"]);
+    }
+
+    #[test]
+    fn test_write_syncs_record_count() {
+        let mut plugin = create_test_plugin();
+        plugin.groups.push(group(b"WEAP", vec![Record::new("WEAP", 0x01000800, 0, vec![])]));
+        assert_eq!(plugin.header_info().record_count, 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        plugin.write_to_file(path.clone()).unwrap();
+
+        let reloaded = Plugin::load(path).unwrap();
+        assert_eq!(reloaded.header_info().record_count, 2);
+        assert_eq!(reloaded.masters, vec!["Skyrim.esm"]);
+    }
+}
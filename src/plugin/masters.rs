@@ -0,0 +1,449 @@
+use super::{FormIdReference, Plugin, UncoveredField};
+use crate::datatypes::SharedBytes;
+use crate::subrecord::Subrecord;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// 主文件列表的最大长度（索引 0xFF 保留）
+const MAX_MASTERS: usize = 0xFF;
+
+/// 主文件管理错误
+#[derive(Debug, Error)]
+pub enum MasterError {
+    /// 主文件不在列表中
+    #[error("主文件不存在: {0}")]
+    NotFound(String),
+
+    /// 主文件已在列表中
+    #[error("主文件已存在: {0}")]
+    AlreadyExists(String),
+
+    /// 主文件仍被记录引用，无法移除
+    #[error("主文件 {master} 仍被 {} 处引用，无法移除", .references.len())]
+    InUse {
+        master: String,
+        references: Vec<FormIdReference>,
+    },
+
+    /// 新顺序不是当前主文件列表的排列
+    #[error("主文件顺序无效: {0}")]
+    InvalidOrder(String),
+
+    /// 主文件数量超过限制
+    #[error("主文件数量超过限制: 最多 {MAX_MASTERS} 个")]
+    TooManyMasters,
+
+    /// 记录中有无法重映射的 FormID 字段（CTDA、VMAD 等），修改主文件索引会使其指向错误的主文件
+    #[error("{} 处子记录中的 FormID 无法重映射（CTDA、VMAD 等），拒绝修改主文件列表", .fields.len())]
+    UncoveredFields {
+        fields: Vec<UncoveredField>,
+    },
+}
+
+impl Plugin {
+    /// 添加主文件到列表末尾
+    ///
+    /// 插件自身记录的主文件索引会随之后移一位。
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// plugin.add_master("Dawnguard.esm")?;
+    /// plugin.write_to_file("output.esp".into())?;
+    /// ```
+    pub fn add_master(&mut self, name: &str) -> Result<(), MasterError> {
+        self.add_masters(&[name])
+    }
+
+    /// 按顺序将多个主文件添加到列表末尾
+    ///
+    /// 所有记录只重映射一次。任一主文件已存在（或重复）时返回错误，插件保持不变。
+    pub fn add_masters(&mut self, names: &[&str]) -> Result<(), MasterError> {
+        for (i, name) in names.iter().enumerate() {
+            let duplicated = names[..i].iter().any(|other| other.eq_ignore_ascii_case(name));
+            if duplicated || self.find_master(name).is_some() {
+                return Err(MasterError::AlreadyExists(name.to_string()));
+            }
+        }
+        if self.masters.len() + names.len() > MAX_MASTERS {
+            return Err(MasterError::TooManyMasters);
+        }
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let old_count = self.masters.len();
+        let mut new_masters = self.masters.clone();
+        new_masters.extend(names.iter().map(|name| name.to_string()));
+
+        let index_map: Vec<usize> = (0..old_count).collect();
+        self.remap_master_indices(&index_map, new_masters.len())?;
+        self.set_masters(new_masters);
+
+        Ok(())
+    }
+
+    /// 从列表中移除主文件
+    ///
+    /// 如果仍有记录（或 FormID 字段）引用该主文件，返回 `MasterError::InUse`
+    /// 并列出所有引用位置；记录中有无法检查的 FormID 字段时返回 `MasterError::UncoveredFields`。
+    /// 出错时插件保持不变。
+    pub fn remove_master(&mut self, name: &str) -> Result<(), MasterError> {
+        let removed = self.find_master(name)
+            .ok_or_else(|| MasterError::NotFound(name.to_string()))?;
+
+        let references = self.master_references(removed);
+        if !references.is_empty() {
+            return Err(MasterError::InUse {
+                master: self.masters[removed].clone(),
+                references,
+            });
+        }
+
+        let old_count = self.masters.len();
+        let index_map: Vec<usize> = (0..old_count)
+            .map(|i| if i > removed { i - 1 } else { i })
+            .collect();
+        self.remap_master_indices(&index_map, old_count - 1)?;
+
+        let mut new_masters = self.masters.clone();
+        new_masters.remove(removed);
+        self.set_masters(new_masters);
+
+        Ok(())
+    }
+
+    /// 重命名主文件
+    ///
+    /// 只修改头部中的文件名，主文件索引不变。
+    pub fn rename_master(&mut self, old_name: &str, new_name: &str) -> Result<(), MasterError> {
+        let index = self.find_master(old_name)
+            .ok_or_else(|| MasterError::NotFound(old_name.to_string()))?;
+        if let Some(existing) = self.find_master(new_name) {
+            if existing != index {
+                return Err(MasterError::AlreadyExists(new_name.to_string()));
+            }
+        }
+
+        let mut new_masters = self.masters.clone();
+        new_masters[index] = new_name.to_string();
+        self.set_masters(new_masters);
+
+        Ok(())
+    }
+
+    /// 按给定顺序重排主文件
+    ///
+    /// # 参数
+    /// * `order` - 新的主文件顺序，必须包含且仅包含当前所有主文件（不区分大小写）
+    pub fn reorder_masters(&mut self, order: &[&str]) -> Result<(), MasterError> {
+        if order.len() != self.masters.len() {
+            return Err(MasterError::InvalidOrder(format!(
+                "需要 {} 个主文件，提供了 {} 个",
+                self.masters.len(),
+                order.len()
+            )));
+        }
+
+        // 旧索引 -> 新索引
+        let mut index_map = vec![usize::MAX; self.masters.len()];
+        for (new_index, name) in order.iter().enumerate() {
+            let old_index = self.find_master(name)
+                .ok_or_else(|| MasterError::NotFound(name.to_string()))?;
+            if index_map[old_index] != usize::MAX {
+                return Err(MasterError::InvalidOrder(format!("主文件重复: {}", name)));
+            }
+            index_map[old_index] = new_index;
+        }
+
+        let new_masters: Vec<String> = order.iter()
+            .map(|name| self.masters[self.find_master(name).unwrap()].clone())
+            .collect();
+
+        let count = self.masters.len();
+        self.remap_master_indices(&index_map, count)?;
+        self.set_masters(new_masters);
+
+        Ok(())
+    }
+
+    /// 移除所有未被引用的主文件
+    ///
+    /// 记录中有无法检查的 FormID 字段（CTDA、VMAD 等）时无法确认主文件未被使用，
+    /// 返回 `MasterError::UncoveredFields`，插件保持不变。
+    ///
+    /// # 返回
+    /// 被移除的主文件名列表
+    pub fn clean_masters(&mut self) -> Result<Vec<String>, MasterError> {
+        let mut used = vec![false; self.masters.len()];
+        for reference in self.collect_form_id_references() {
+            let index = (reference.referenced_form_id >> 24) as usize;
+            if index < used.len() {
+                used[index] = true;
+            }
+        }
+
+        let old_count = self.masters.len();
+        let mut index_map = Vec::with_capacity(old_count);
+        let mut new_masters = Vec::new();
+        let mut removed = Vec::new();
+        for (index, name) in self.masters.iter().enumerate() {
+            if used[index] {
+                index_map.push(new_masters.len());
+                new_masters.push(name.clone());
+            } else {
+                // 未被引用的索引不会出现在任何 FormID 中，映射值无意义
+                index_map.push(usize::MAX);
+                removed.push(name.clone());
+            }
+        }
+
+        if !removed.is_empty() {
+            let new_count = new_masters.len();
+            self.remap_master_indices(&index_map, new_count)?;
+            self.set_masters(new_masters);
+        }
+
+        Ok(removed)
+    }
+
+    /// 查找主文件索引（不区分大小写）
+    pub(crate) fn find_master(&self, name: &str) -> Option<usize> {
+        self.masters.iter().position(|m| m.eq_ignore_ascii_case(name))
+    }
+
+    /// 查找引用指定主文件索引的所有位置
+    fn master_references(&self, index: usize) -> Vec<FormIdReference> {
+        self.collect_form_id_references()
+            .into_iter()
+            .filter(|reference| (reference.referenced_form_id >> 24) as usize == index)
+            .collect()
+    }
+
+    /// 重写所有 FormID 的主文件索引（高8位）
+    ///
+    /// # 参数
+    /// * `index_map` - 旧主文件索引 -> 新主文件索引
+    /// * `new_count` - 新的主文件数量；不小于旧主文件数量的索引视为插件自身，映射为 `new_count`
+    ///
+    /// 有无法重映射的 FormID 字段时不做任何修改，返回 `MasterError::UncoveredFields`。
+    fn remap_master_indices(&mut self, index_map: &[usize], new_count: usize) -> Result<(), MasterError> {
+        let old_count = index_map.len();
+        if new_count == old_count && index_map.iter().enumerate().all(|(i, &j)| i == j) {
+            return Ok(());
+        }
+
+        let fields = self.uncovered_form_id_fields();
+        if !fields.is_empty() {
+            return Err(MasterError::UncoveredFields { fields });
+        }
+
+        let _modified = self.remap_form_ids(|form_id| {
+            let index = (form_id >> 24) as usize;
+            let new_index = if index < old_count { index_map[index] } else { new_count };
+            ((new_index as u32) << 24) | (form_id & 0x00FFFFFF)
+        });
+
+        #[cfg(debug_assertions)]
+        println!("主文件索引重映射完成：共 {} 个记录", _modified);
+
+        Ok(())
+    }
+
+    /// 将主文件列表写回头部记录（MAST + DATA 子记录对）
+    ///
+    /// 保留原有主文件的 DATA 内容，新主文件的 DATA 为 8 字节 0。
+    pub(crate) fn set_masters(&mut self, masters: Vec<String>) {
+        let subrecords = std::mem::take(self.header.subrecords_mut());
+
+        let mut master_data: HashMap<String, SharedBytes> = HashMap::new();
+        let mut kept = Vec::with_capacity(subrecords.len());
+        let mut insert_at = None;
+        let mut iter = subrecords.into_iter().peekable();
+        while let Some(subrecord) = iter.next() {
+            if subrecord.record_type == "MAST" {
+                insert_at.get_or_insert(kept.len());
+                let name = crate::datatypes::RawString::parse_zstring(&subrecord.data).content;
+                if iter.peek().is_some_and(|next| next.record_type == "DATA") {
+                    let data = iter.next().unwrap().data;
+                    master_data.insert(name.to_lowercase(), data);
+                }
+                continue;
+            }
+            kept.push(subrecord);
+        }
+
+        // 没有 MAST 时，插入到 HEDR/OFST/DELE/CNAM/SNAM 之后
+        let insert_at = insert_at.unwrap_or_else(|| {
+            kept.iter()
+                .rposition(|sr| matches!(sr.record_type.as_bytes(), b"HEDR" | b"OFST" | b"DELE" | b"CNAM" | b"SNAM"))
+                .map(|i| i + 1)
+                .unwrap_or(0)
+        });
+
+        let mut pairs = Vec::with_capacity(masters.len() * 2);
+        for name in &masters {
+            let name_data = crate::datatypes::zstring(name);
+            let data = master_data.remove(&name.to_lowercase()).unwrap_or_else(|| vec![0; 8].into());
+            pairs.push(Subrecord::new("MAST", name_data));
+            pairs.push(Subrecord::new("DATA", data.into_vec()));
+        }
+
+        kept.splice(insert_at..insert_at, pairs);
+        *self.header.subrecords_mut() = kept;
+        self.header.is_modified = true;
+        self.masters = masters;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::{Group, GroupChild, GroupType};
+    use crate::record::Record;
+    use crate::datatypes::Signature;
+    use crate::plugin::test_support::builder;
+
+    fn create_test_plugin() -> Plugin {
+        let mut builder = builder("Test.esp", &["Skyrim.esm", "Update.esm"]);
+
+        // 覆盖 Skyrim.esm 的记录，KWDA 引用 Update.esm 与自身
+        let mut kwda = Vec::new();
+        kwda.extend_from_slice(&0x01000ABCu32.to_le_bytes());
+        kwda.extend_from_slice(&0x02000801u32.to_le_bytes());
+        builder.add_record(Record::new("WEAP", 0x00012EB7, 0, vec![Subrecord::new("KWDA", kwda)])).unwrap();
+        // 插件自身的新记录（0x02000800）
+        let own = builder.new_record("WEAP", vec![]);
+        builder.add_record(own).unwrap();
+
+        builder.build().unwrap()
+    }
+
+    fn kwda_values(plugin: &Plugin) -> Vec<u32> {
+        let records = plugin.groups[0].get_records();
+        records[0].find_subrecord("KWDA").unwrap().data
+            .chunks(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+
+    #[test]
+    fn test_add_master_shifts_own_records() {
+        let mut plugin = create_test_plugin();
+        plugin.add_master("Dawnguard.esm").unwrap();
+
+        assert_eq!(plugin.masters, vec!["Skyrim.esm", "Update.esm", "Dawnguard.esm"]);
+        assert_eq!(Plugin::extract_masters(&plugin.header), plugin.masters);
+        assert_eq!(plugin.header.subrecords()[0].record_type, "HEDR");
+        assert_eq!(plugin.groups[0].get_records()[1].form_id, 0x03000800);
+        assert_eq!(kwda_values(&plugin), vec![0x01000ABC, 0x03000801]);
+        assert!(matches!(plugin.add_master("dawnguard.esm"), Err(MasterError::AlreadyExists(_))));
+    }
+
+    #[test]
+    fn test_add_masters_remaps_once() {
+        let mut plugin = create_test_plugin();
+        assert!(matches!(
+            plugin.add_masters(&["Dawnguard.esm", "dawnguard.esm"]),
+            Err(MasterError::AlreadyExists(_))
+        ));
+        assert_eq!(plugin.masters.len(), 2);
+
+        plugin.add_masters(&["Dawnguard.esm", "HearthFires.esm"]).unwrap();
+        assert_eq!(plugin.masters, vec!["Skyrim.esm", "Update.esm", "Dawnguard.esm", "HearthFires.esm"]);
+        assert_eq!(plugin.groups[0].get_records()[1].form_id, 0x04000800);
+        assert_eq!(kwda_values(&plugin), vec![0x01000ABC, 0x04000801]);
+    }
+
+    #[test]
+    fn test_remove_master_in_use() {
+        let mut plugin = create_test_plugin();
+        match plugin.remove_master("Update.esm") {
+            Err(MasterError::InUse { references, .. }) => {
+                assert_eq!(references.len(), 1);
+                assert_eq!(references[0].subrecord_type, Some(Signature::from_name("KWDA")));
+                assert_eq!(references[0].form_id, 0x00012EB7);
+            }
+            other => panic!("expected InUse, got {:?}", other),
+        }
+        assert_eq!(plugin.masters.len(), 2);
+    }
+
+    #[test]
+    fn test_reorder_and_clean_masters() {
+        let mut plugin = create_test_plugin();
+        plugin.reorder_masters(&["Update.esm", "Skyrim.esm"]).unwrap();
+        assert_eq!(plugin.masters, vec!["Update.esm", "Skyrim.esm"]);
+        assert_eq!(plugin.groups[0].get_records()[0].form_id, 0x01012EB7);
+        assert_eq!(kwda_values(&plugin), vec![0x00000ABC, 0x02000801]);
+
+        plugin.add_master("Unused.esm").unwrap();
+        assert_eq!(plugin.clean_masters().unwrap(), vec!["Unused.esm"]);
+        assert_eq!(plugin.groups[0].get_records()[1].form_id, 0x02000800);
+        assert!(plugin.reorder_masters(&["Skyrim.esm", "Skyrim.esm"]).is_err());
+    }
+
+    #[test]
+    fn test_master_changes_refused_with_uncovered_fields() {
+        let mut plugin = create_test_plugin();
+        plugin.add_master("Unused.esm").unwrap();
+
+        // 条件中的 FormID 位置取决于条件函数，无法重映射
+        let mut perks = Group::new(*b"PERK", GroupType::Normal);
+        perks.children.push(GroupChild::Record(Record::new("PERK", 0x03000801, 0, vec![
+            Subrecord::new("EDID", b"TestPerk\0".to_vec()),
+            Subrecord::new("CTDA", vec![0; 32]),
+        ])));
+        plugin.groups.push(perks);
+
+        let uncovered = plugin.uncovered_form_id_fields();
+        assert_eq!(uncovered.len(), 1);
+        assert_eq!(uncovered[0].subrecord_type, Signature::from_name("CTDA"));
+        assert_eq!(uncovered[0].editor_id.as_deref(), Some("TestPerk"));
+
+        assert!(matches!(plugin.clean_masters(), Err(MasterError::UncoveredFields { .. })));
+        assert!(matches!(plugin.remove_master("Unused.esm"), Err(MasterError::UncoveredFields { .. })));
+        assert!(matches!(plugin.add_master("Dawnguard.esm"), Err(MasterError::UncoveredFields { .. })));
+        assert!(matches!(
+            plugin.reorder_masters(&["Update.esm", "Skyrim.esm", "Unused.esm"]),
+            Err(MasterError::UncoveredFields { .. })
+        ));
+
+        // 插件保持不变；只改名不涉及索引
+        assert_eq!(plugin.masters, vec!["Skyrim.esm", "Update.esm", "Unused.esm"]);
+        assert_eq!(plugin.groups[0].get_records()[1].form_id, 0x03000800);
+        plugin.rename_master("Unused.esm", "Renamed.esm").unwrap();
+    }
+
+    #[test]
+    fn test_unlisted_record_type_blocks_reorder() {
+        let mut plugin = create_test_plugin();
+
+        // RACE 不在 FormID 字段定义表中，其 DATA 可能包含 FormID
+        let mut races = Group::new(*b"RACE", GroupType::Normal);
+        races.children.push(GroupChild::Record(Record::new("RACE", 0x02000801, 0, vec![
+            Subrecord::new("EDID", b"TestRace\0".to_vec()),
+            Subrecord::new("DATA", vec![0; 128]),
+        ])));
+        plugin.groups.push(races);
+
+        let uncovered = plugin.uncovered_form_id_fields();
+        assert_eq!(uncovered.len(), 1);
+        assert_eq!(uncovered[0].record_type, Signature::from_name("RACE"));
+        assert_eq!(uncovered[0].subrecord_type, Signature::from_name("DATA"));
+
+        assert!(matches!(
+            plugin.reorder_masters(&["Update.esm", "Skyrim.esm"]),
+            Err(MasterError::UncoveredFields { .. })
+        ));
+        assert_eq!(plugin.masters, vec!["Skyrim.esm", "Update.esm"]);
+    }
+
+    #[test]
+    fn test_rename_master() {
+        let mut plugin = create_test_plugin();
+        plugin.rename_master("update.esm", "Update_Patched.esm").unwrap();
+        assert_eq!(plugin.masters[1], "Update_Patched.esm");
+        assert_eq!(plugin.groups[0].get_records()[1].form_id, 0x02000800);
+        assert!(matches!(plugin.rename_master("Missing.esm", "A.esm"), Err(MasterError::NotFound(_))));
+    }
+}
@@ -0,0 +1,429 @@
+use super::Plugin;
+use crate::datatypes::Signature;
+use crate::group::{Group, GroupType, TOP_LEVEL_GROUP_ORDER};
+use crate::record::Record;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 新记录的最小对象 ID
+const MIN_OBJECT_ID: u32 = 0x800;
+/// 对象 ID 的最大值（低24位）
+const MAX_OBJECT_ID: u32 = 0x00FFFFFF;
+
+/// 合并映射条目：源插件中的新记录在合并插件中的 FormID
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeMapEntry {
+    /// 源插件名称
+    pub plugin: String,
+    /// 记录类型
+    pub record_type: Signature,
+    /// 编辑器ID
+    pub editor_id: Option<String>,
+    /// 源插件中的 FormID
+    pub old_form_id: u32,
+    /// 合并插件中的 FormID
+    pub new_form_id: u32,
+}
+
+/// 合并冲突：多个插件覆盖了同一条记录
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeConflict {
+    /// 合并插件中的 FormID
+    pub form_id: u32,
+    /// 记录类型
+    pub record_type: Signature,
+    /// 编辑器ID
+    pub editor_id: Option<String>,
+    /// 最终生效的插件（合并顺序中最后一个）
+    pub winner: String,
+    /// 被覆盖的插件（按合并顺序）
+    pub overridden: Vec<String>,
+}
+
+/// 插件合并报告
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeReport {
+    /// 参与合并的插件（按合并顺序）
+    pub merged_plugins: Vec<String>,
+    /// 合并插件的主文件列表
+    pub masters: Vec<String>,
+    /// 合并映射（所有新记录）
+    pub merge_map: Vec<MergeMapEntry>,
+    /// 重复覆盖的记录
+    pub conflicts: Vec<MergeConflict>,
+    /// 警告信息
+    pub warnings: Vec<String>,
+}
+
+impl MergeReport {
+    /// 因 FormID 冲突被重编号的记录数量
+    pub fn renumbered_count(&self) -> usize {
+        self.merge_map.iter()
+            .filter(|entry| entry.old_form_id & MAX_OBJECT_ID != entry.new_form_id & MAX_OBJECT_ID)
+            .count()
+    }
+
+    /// 将合并报告（含合并映射）以 JSON 格式保存
+    pub fn save_merge_map(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== 插件合并报告 ===")?;
+        writeln!(f, "合并插件: {}", self.merged_plugins.join(", "))?;
+        writeln!(f, "主文件: {}", self.masters.len())?;
+        for master in &self.masters {
+            writeln!(f, "  - {}", master)?;
+        }
+        writeln!(f, "新记录: {} (重编号 {})", self.merge_map.len(), self.renumbered_count())?;
+        writeln!(f, "覆盖冲突: {}", self.conflicts.len())?;
+        for conflict in &self.conflicts {
+            writeln!(
+                f,
+                "  [{:08X}] {} {}: {} 覆盖 {}",
+                conflict.form_id,
+                conflict.record_type,
+                conflict.editor_id.as_deref().unwrap_or(""),
+                conflict.winner,
+                conflict.overridden.join(", ")
+            )?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "⚠️ {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// 源插件主文件索引在合并插件中的去向
+#[derive(Clone, Copy)]
+enum MasterTarget {
+    /// 合并插件的主文件索引
+    Master(u32),
+    /// 参与合并的插件（按合并顺序的下标）
+    Merged(usize),
+}
+
+impl Plugin {
+    /// 将多个插件合并为一个
+    ///
+    /// - 主文件取所有插件主文件的并集（参与合并的插件自身除外）
+    /// - 新记录尽量保留原对象 ID，冲突时分配新的对象 ID
+    /// - 所有 FormID 引用（含插件之间的引用）同步重映射
+    /// - 同一记录被多个插件覆盖时，合并顺序中靠后的插件生效，并记录到报告中
+    ///
+    /// 合并结果通过 `write_to_file()` 写出，合并映射通过 `MergeReport::save_merge_map()` 保存。
+    /// 本地化插件的字符串 ID 指向各自的 STRING 文件，无法合并。
+    ///
+    /// 记录中有无法重映射的 FormID 字段（CTDA、VMAD、未知记录类型等）时默认返回错误；
+    /// 显式允许时继续合并，这些字段保持原值并逐条列入报告的警告中。
+    ///
+    /// # 参数
+    /// * `plugins` - 按合并顺序排列的插件
+    /// * `output_path` - 合并插件的路径（决定插件名称）
+    /// * `allow_uncovered` - 是否允许合并含无法重映射字段的插件
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let plugins = vec![Plugin::load("A.esp".into())?, Plugin::load("B.esp".into())?];
+    /// let (merged, report) = Plugin::merge(plugins, "Merged.esp".into(), false)?;
+    /// merged.write_to_file("Merged.esp".into())?;
+    /// report.save_merge_map(Path::new("Merged.merge_map.json"))?;
+    /// ```
+    pub fn merge(
+        plugins: Vec<Plugin>,
+        output_path: PathBuf,
+        allow_uncovered: bool,
+    ) -> Result<(Plugin, MergeReport), Box<dyn std::error::Error>> {
+        let first = plugins.first().ok_or("没有需要合并的插件")?;
+        if let Some(localized) = plugins.iter().find(|p| p.is_localized()) {
+            return Err(format!("本地化插件无法合并: {}", localized.get_name()).into());
+        }
+
+        // 无法重映射的 FormID 字段合并后会指向错误的记录
+        let mut uncovered_warnings = Vec::new();
+        for plugin in &plugins {
+            let uncovered = plugin.uncovered_form_id_fields();
+            if let (false, Some(first)) = (allow_uncovered, uncovered.first()) {
+                return Err(format!(
+                    "{} 中有 {} 处子记录的 FormID 无法重映射（首个: {}），拒绝合并",
+                    plugin.get_name(), uncovered.len(), first
+                ).into());
+            }
+            uncovered_warnings.extend(uncovered.iter().map(|field| {
+                format!("{}: {} 中的 FormID 未重映射", plugin.get_name(), field)
+            }));
+        }
+
+        let names: Vec<String> = plugins.iter().map(|p| p.get_name().to_string()).collect();
+        let merged_index = |name: &str| names.iter().position(|n| n.eq_ignore_ascii_case(name));
+
+        // 1. 主文件并集
+        let mut masters: Vec<String> = Vec::new();
+        for plugin in &plugins {
+            for master in &plugin.masters {
+                if merged_index(master).is_none() && !masters.iter().any(|m| m.eq_ignore_ascii_case(master)) {
+                    masters.push(master.clone());
+                }
+            }
+        }
+        if masters.len() >= 0xFF {
+            return Err(format!("合并后的主文件数量超过限制: {}", masters.len()).into());
+        }
+        let self_index = masters.len() as u32;
+
+        // 2. 为新记录分配对象 ID（冲突时从所有原对象 ID 之后分配）
+        let max_object_id = plugins.iter()
+            .flat_map(|p| p.groups.iter().flat_map(|g| g.get_records()).filter(|r| p.is_new_record(r.form_id)))
+            .map(|r| r.form_id & MAX_OBJECT_ID)
+            .max()
+            .unwrap_or(0);
+        let mut next_free = (max_object_id + 1).max(MIN_OBJECT_ID);
+        let mut used = HashSet::new();
+        let mut renumber: Vec<HashMap<u32, u32>> = vec![HashMap::new(); plugins.len()];
+        let mut merge_map = Vec::new();
+
+        for (index, plugin) in plugins.iter().enumerate() {
+            for record in plugin.groups.iter().flat_map(|g| g.get_records()) {
+                if !plugin.is_new_record(record.form_id) {
+                    continue;
+                }
+
+                let object_id = record.form_id & MAX_OBJECT_ID;
+                let new_object_id = if used.insert(object_id) {
+                    object_id
+                } else {
+                    if next_free > MAX_OBJECT_ID {
+                        return Err("合并插件的对象 ID 已耗尽".into());
+                    }
+                    used.insert(next_free);
+                    next_free += 1;
+                    next_free - 1
+                };
+
+                renumber[index].insert(object_id, new_object_id);
+                merge_map.push(MergeMapEntry {
+                    plugin: names[index].clone(),
+                    record_type: record.record_type,
+                    editor_id: record.get_editor_id(),
+                    old_form_id: record.form_id,
+                    new_form_id: (self_index << 24) | new_object_id,
+                });
+            }
+        }
+
+        // 3. 创建合并插件（头部以第一个插件为模板）
+        let mut warnings = Vec::new();
+        if plugins.iter().any(|p| p.is_light()) {
+            warnings.push("合并插件未设置 ESL 标志，如需转换请使用 eslify_formids()".to_string());
+        }
+        warnings.extend(uncovered_warnings);
+
+        let header_subrecords = first.header.subrecords().iter()
+            .filter(|sr| !matches!(sr.record_type.as_bytes(), b"MAST" | b"DATA" | b"ONAM" | b"OFST"))
+            .cloned()
+            .collect();
+        let header = Record::new("TES4", 0, first.header.flags & !0x200, header_subrecords);
+        let mut merged = Plugin::from_parts(output_path, header, Vec::new())?;
+        merged.set_masters(masters.clone());
+
+        // 4. 重映射并合并各插件的记录树
+        let mut sources: HashMap<u32, Vec<String>> = HashMap::new();
+        for (index, mut plugin) in plugins.into_iter().enumerate() {
+            let targets: Vec<MasterTarget> = plugin.masters.iter()
+                .map(|master| match merged_index(master) {
+                    Some(merged) => MasterTarget::Merged(merged),
+                    None => {
+                        let position = masters.iter().position(|m| m.eq_ignore_ascii_case(master)).unwrap();
+                        MasterTarget::Master(position as u32)
+                    }
+                })
+                .collect();
+
+            plugin.remap_form_ids(|form_id| {
+                let master_index = (form_id >> 24) as usize;
+                let object_id = form_id & MAX_OBJECT_ID;
+                let target = targets.get(master_index).copied().unwrap_or(MasterTarget::Merged(index));
+                match target {
+                    MasterTarget::Master(new_index) => (new_index << 24) | object_id,
+                    MasterTarget::Merged(source) => {
+                        let new_object_id = renumber[source].get(&object_id).copied().unwrap_or(object_id);
+                        (self_index << 24) | new_object_id
+                    }
+                }
+            });
+
+            for group in std::mem::take(&mut plugin.groups) {
+                for record in group.get_records() {
+                    sources.entry(record.form_id).or_default().push(names[index].clone());
+                }
+                merged.merge_top_level_group(group);
+            }
+        }
+
+        // 5. 汇总冲突（按合并插件中的记录顺序）
+        let mut conflicts = Vec::new();
+        for record in merged.groups.iter().flat_map(|g| g.get_records()) {
+            if let Some(plugins) = sources.get(&record.form_id) {
+                if plugins.len() > 1 {
+                    let (winner, overridden) = plugins.split_last().unwrap();
+                    conflicts.push(MergeConflict {
+                        form_id: record.form_id,
+                        record_type: record.record_type,
+                        editor_id: record.get_editor_id(),
+                        winner: winner.clone(),
+                        overridden: overridden.to_vec(),
+                    });
+                }
+            }
+        }
+
+        merged.update_header_counts();
+
+        #[cfg(debug_assertions)]
+        println!("插件合并完成：{} 个新记录，{} 个冲突", merge_map.len(), conflicts.len());
+
+        let report = MergeReport {
+            merged_plugins: names,
+            masters,
+            merge_map,
+            conflicts,
+            warnings,
+        };
+
+        Ok((merged, report))
+    }
+
+    /// 合并顶级组
+    ///
+    /// 已存在同类型的顶级组时递归合并其子元素，否则按 `TOP_LEVEL_GROUP_ORDER` 插入。
+    ///
+    /// # 返回
+    /// 被替换的旧记录
+    pub(crate) fn merge_top_level_group(&mut self, group: Group) -> Vec<Record> {
+        merge_top_level_group(&mut self.groups, group)
+    }
+}
+
+/// 将顶级组合并到组列表中（见 `Plugin::merge_top_level_group`）
+pub(crate) fn merge_top_level_group(groups: &mut Vec<Group>, group: Group) -> Vec<Record> {
+    let existing = groups.iter_mut()
+        .find(|g| g.label == group.label && g.group_type == group.group_type);
+
+    if let Some(existing) = existing {
+        return existing.merge_children(group.children);
+    }
+
+    let order = |label: Signature| {
+        TOP_LEVEL_GROUP_ORDER.iter().position(|t| label == *t).unwrap_or(usize::MAX)
+    };
+    let new_order = order(group.label);
+    let position = if group.group_type == GroupType::Normal && new_order != usize::MAX {
+        groups.iter()
+            .position(|g| order(g.label) > new_order)
+            .unwrap_or(groups.len())
+    } else {
+        groups.len()
+    };
+    groups.insert(position, group);
+
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::test_support::{edid, group, plugin_with_groups};
+    use crate::subrecord::Subrecord;
+
+    #[test]
+    fn test_merge_renumbers_and_remaps() {
+        let a = plugin_with_groups("A.esp", &["Skyrim.esm"], vec![
+            group(b"MISC", vec![Record::new("MISC", 0x01000800, 0, vec![])]),
+        ]);
+        // B 依赖 A：引用 A 的记录，且新记录与 A 的对象 ID 冲突
+        let b = plugin_with_groups("B.esp", &["Skyrim.esm", "Update.esm", "A.esp"], vec![
+            group(b"WEAP", vec![Record::new("WEAP", 0x03000800, 0, vec![
+                Subrecord::new("KWDA", [0x01000123u32.to_le_bytes(), 0x02000800u32.to_le_bytes()].concat()),
+            ])]),
+            group(b"MISC", vec![Record::new("MISC", 0x00012345, 0, vec![])]),
+        ]);
+
+        let (merged, report) = Plugin::merge(vec![a, b], PathBuf::from("Merged.esp"), false).unwrap();
+
+        assert_eq!(merged.masters, vec!["Skyrim.esm", "Update.esm"]);
+        assert_eq!(report.merge_map.len(), 2);
+        assert_eq!(report.renumbered_count(), 1);
+        assert_eq!(report.merge_map[1].new_form_id, 0x02000801);
+
+        // 顶级组按标准顺序排列：MISC 在 WEAP 之前
+        assert_eq!(merged.groups.iter().map(|g| g.get_label_string()).collect::<Vec<_>>(), vec!["MISC", "WEAP"]);
+
+        let weapon = merged.groups[1].get_records()[0];
+        assert_eq!(weapon.form_id, 0x02000801);
+        assert_eq!(
+            weapon.find_subrecord("KWDA").unwrap().data,
+            [0x01000123u32.to_le_bytes(), 0x02000800u32.to_le_bytes()].concat()
+        );
+
+        // 写出后可重新加载
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Merged.esp");
+        merged.write_to_file(path.clone()).unwrap();
+        let reloaded = Plugin::load(path).unwrap();
+        assert_eq!(reloaded.masters, merged.masters);
+        assert_eq!(reloaded.count_records_and_groups(), 5);
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let a = plugin_with_groups("A.esp", &["Skyrim.esm"], vec![
+            group(b"MISC", vec![Record::new("MISC", 0x00012345, 0, vec![edid("A")])]),
+        ]);
+        let b = plugin_with_groups("B.esp", &["Skyrim.esm"], vec![
+            group(b"MISC", vec![Record::new("MISC", 0x00012345, 0, vec![edid("B")])]),
+        ]);
+
+        let (merged, report) = Plugin::merge(vec![a, b], PathBuf::from("Merged.esp"), false).unwrap();
+
+        assert_eq!(merged.groups[0].children.len(), 1);
+        assert_eq!(merged.groups[0].get_records()[0].get_editor_id().as_deref(), Some("B"));
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].winner, "B.esp");
+        assert_eq!(report.conflicts[0].overridden, vec!["A.esp"]);
+    }
+
+    #[test]
+    fn test_merge_refuses_uncovered_fields() {
+        // 条件参数引用 A 的记录，但 CTDA 中 FormID 的位置取决于条件函数
+        let mut ctda = vec![0; 32];
+        ctda[8..12].copy_from_slice(&0x01000800u32.to_le_bytes());
+        let plugins = || vec![
+            plugin_with_groups("A.esp", &["Skyrim.esm"], vec![
+                group(b"MISC", vec![Record::new("MISC", 0x01000800, 0, vec![])]),
+            ]),
+            plugin_with_groups("B.esp", &["Skyrim.esm", "A.esp"], vec![
+                group(b"PERK", vec![Record::new("PERK", 0x02000800, 0, vec![
+                    edid("TestPerk"),
+                    Subrecord::new("CTDA", ctda.clone()),
+                ])]),
+            ]),
+        ];
+
+        let error = Plugin::merge(plugins(), PathBuf::from("Merged.esp"), false).unwrap_err();
+        assert!(error.to_string().contains("B.esp"));
+
+        // 显式允许时继续合并，受影响的记录逐条列入警告
+        let (merged, report) = Plugin::merge(plugins(), PathBuf::from("Merged.esp"), true).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("TestPerk") && report.warnings[0].contains("CTDA"));
+        let perk = merged.groups.iter().find(|g| g.label == "PERK").unwrap().get_records()[0];
+        assert_eq!(perk.form_id, 0x01000801);
+        assert_eq!(perk.find_subrecord("CTDA").unwrap().data, ctda);
+    }
+}
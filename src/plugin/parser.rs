@@ -1,12 +1,13 @@
 use super::Plugin;
-use crate::datatypes::{read_u32, RawString};
+use super::cache::{FileFingerprint, PluginCache};
+use crate::datatypes::{read_u32, ByteSource, ParseSource, RawString, Signature, SourceSpan};
 use crate::record::Record;
 use crate::group::Group;
 use crate::string_file::StringFileSet;
 use crate::string_routes::DefaultStringRouter;
 use crate::io::EspReader;
 use crate::utils::EspError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::io::{Cursor, Read};
 use std::sync::Arc;
@@ -14,6 +15,78 @@ use serde_json;
 use memmap2::Mmap;
 use rayon::prelude::*;
 
+/// 含有其他类型记录的顶级组（按记录类型过滤时仍需进入其中查找）
+const CONTAINER_GROUPS: &[Signature] = &[
+    Signature::from_name("CELL"),
+    Signature::from_name("WRLD"),
+    Signature::from_name("DIAL"),
+];
+
+/// 解析选项
+///
+/// 默认完整解析所有组和记录。提取字符串等只读场景可以跳过无关的组和记录类型，
+/// 并延迟解压和拆分子记录。跳过了组或记录的插件不能写回文件。
+///
+/// # 示例
+/// ```rust,ignore
+/// let options = ParseOptions::strings_only()?;
+/// let plugin = Plugin::load_with_options("Skyrim.esm".into(), &options)?;
+/// let strings = plugin.extract_strings();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// 跳过这些顶级组（按组标签，如 "NAVI"、"LAND"）
+    pub skip_groups: HashSet<Signature>,
+    /// 只解析这些类型的记录（`None` 表示全部）
+    ///
+    /// 标签不在其中的顶级组整组跳过；CELL/WRLD/DIAL 组中不匹配的记录逐个跳过，
+    /// 不含匹配记录的子组（如单元的引用子组）不保留。
+    pub record_types: Option<HashSet<Signature>>,
+    /// 延迟解压和拆分子记录，首次通过 `Record::subrecords()` 等方法访问时再解析
+    pub lazy: bool,
+    /// 插件索引缓存（`None` 表示不使用缓存）
+    ///
+    /// 缓存有效时只解析头部记录，字符串提取和记录查找使用缓存中的索引
+    /// （见 `Plugin::is_index_only`）；缓存过期或不存在时完整解析插件
+    /// （忽略 `skip_groups` 和 `record_types`）并重新写入缓存。
+    pub cache: Option<PluginCache>,
+}
+
+impl ParseOptions {
+    /// 只解析含有可翻译字符串的记录类型，并延迟解析（用于批量提取字符串）
+    pub fn strings_only() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(ParseOptions {
+            skip_groups: HashSet::new(),
+            record_types: Some(Plugin::load_string_records()?.keys()
+                .map(|record_type| record_type.parse())
+                .collect::<Result<_, String>>()?),
+            lazy: true,
+            cache: None,
+        })
+    }
+
+    /// 是否会跳过部分内容
+    pub fn is_partial(&self) -> bool {
+        !self.skip_groups.is_empty() || self.record_types.is_some()
+    }
+
+    /// 是否解析该标签的顶级组
+    fn parses_group(&self, label: Signature) -> bool {
+        if self.skip_groups.contains(&label) {
+            return false;
+        }
+        match &self.record_types {
+            Some(types) => types.contains(&label) || CONTAINER_GROUPS.contains(&label),
+            None => true,
+        }
+    }
+
+    /// 创建解析组时使用的数据来源
+    fn source(&self, source_id: Option<u64>, bytes: ByteSource) -> ParseSource {
+        ParseSource { source_id, bytes, lazy: self.lazy, record_types: self.record_types.clone() }
+    }
+}
+
 impl Plugin {
     /// 使用自定义 Reader 加载插件文件（v0.6.0+ 新增 - P2.4）
     ///
@@ -41,22 +114,23 @@ impl Plugin {
 
         // 创建字符串路由器实例（v0.6.0 - P2.3）
         #[allow(deprecated)]
-        let string_router = Arc::new(DefaultStringRouter::new(string_records.clone()));
+        let string_router = Arc::new(DefaultStringRouter::from_embedded_data()?);
 
         // 使用注入的 reader 读取数据（v0.6.0 - P2.4）
         let raw_data = reader.read(&path)?;
-        let data_bytes = raw_data.bytes;
+        let data_bytes = Arc::new(raw_data.bytes);
 
-        // 保留对数据的引用以支持 mmap（虽然这里使用的是 Vec）
+        // 记录数据引用整个文件缓冲区；没有内存映射，写入时不能从源文件复制
         let mmap: Option<Arc<Mmap>> = None;
+        let source = ParseSource::new(None, data_bytes.clone());
 
         let mut cursor = Cursor::new(&data_bytes[..]);
 
-        let header = Record::parse(&mut cursor)?;
+        let header = Record::parse_in_source(&mut cursor, Some(&source))?;
         Self::validate_esp_file(&header)?;
 
         let masters = Self::extract_masters(&header);
-        let groups = Self::parse_groups(&mut cursor, &data_bytes[..])?;
+        let groups = Self::parse_groups(&mut cursor, &source, &ParseOptions::default())?;
 
         #[allow(deprecated)]
         Ok(Plugin {
@@ -69,6 +143,9 @@ impl Plugin {
             string_files: None,
             language: String::new(),
             mmap,
+            source_id: None,
+            partial: false,
+            index: None,
         })
     }
 
@@ -88,27 +165,52 @@ impl Plugin {
     /// let plugin = Plugin::load("example.esp".into())?;
     /// ```
     pub fn load(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_with_options(path, &ParseOptions::default())
+    }
+
+    /// 按解析选项加载插件文件
+    ///
+    /// 跳过了组或记录时（见 `ParseOptions::is_partial`），插件只能用于读取，写入会返回错误。
+    pub fn load_with_options(path: PathBuf, options: &ParseOptions) -> Result<Self, Box<dyn std::error::Error>> {
         let string_records = Self::load_string_records()?;
 
         // 创建字符串路由器实例（v0.6.0 - P2.3）
         #[allow(deprecated)]
-        let string_router = Arc::new(DefaultStringRouter::new(string_records.clone()));
+        let string_router = Arc::new(DefaultStringRouter::from_embedded_data()?);
 
         // 使用内存映射文件（零拷贝，性能提升 ~500-600ms）
         let file = std::fs::File::open(&path)?;
+        let fingerprint = FileFingerprint::from_metadata(&file.metadata()?);
         let mmap = unsafe { Mmap::map(&file)? };
         let mmap = Arc::new(mmap);
 
+        let source_id = SourceSpan::next_source_id();
         let mut cursor = Cursor::new(&mmap[..]);
 
-        let header = Record::parse(&mut cursor)?;
+        let header = Record::parse_in_source(&mut cursor, Some(&ParseSource::new(Some(source_id), mmap.clone())))?;
         Self::validate_esp_file(&header)?;
 
         let masters = Self::extract_masters(&header);
-        let groups = Self::parse_groups(&mut cursor, &mmap[..])?;
+
+        // 缓存有效时不解析组
+        let index = options.cache.as_ref()
+            .and_then(|cache| cache.load(&path, &fingerprint, &mmap))
+            .map(Arc::new);
+
+        let (groups, partial) = if index.is_some() {
+            (Vec::new(), true)
+        } else if options.cache.is_some() {
+            // 缓存未命中：完整解析以便生成索引
+            let full_options = ParseOptions { lazy: options.lazy, ..ParseOptions::default() };
+            let source = full_options.source(Some(source_id), mmap.clone());
+            (Self::parse_groups(&mut cursor, &source, &full_options)?, false)
+        } else {
+            let source = options.source(Some(source_id), mmap.clone());
+            (Self::parse_groups(&mut cursor, &source, options)?, options.is_partial())
+        };
 
         #[allow(deprecated)]
-        Ok(Plugin {
+        let plugin = Plugin {
             path,
             header,
             groups,
@@ -118,7 +220,22 @@ impl Plugin {
             string_files: None,
             language: String::new(),
             mmap: Some(mmap),
-        })
+            source_id: Some(source_id),
+            partial,
+            index,
+        };
+
+        if let Some(cache) = &options.cache {
+            if plugin.index.is_none() {
+                let index = plugin.build_index(fingerprint.with_content_hash(plugin.mmap.as_deref().unwrap()));
+                // 缓存写入失败不影响加载结果
+                if let Err(e) = cache.store(&plugin.path, &index) {
+                    eprintln!("警告: 无法写入索引缓存 {}: {}", cache.index_path(&plugin.path).display(), e);
+                }
+            }
+        }
+
+        Ok(plugin)
     }
 
     /// 创建新的插件实例（已弃用，请使用 `Plugin::load()`）
@@ -142,20 +259,23 @@ impl Plugin {
         let string_records = Self::load_string_records()?;
 
         // 创建字符串路由器实例（v0.6.0 - P2.3）
-        let string_router = Arc::new(DefaultStringRouter::new(string_records.clone()));
+        let string_router = Arc::new(DefaultStringRouter::from_embedded_data()?);
 
         // 使用内存映射文件（零拷贝，性能提升 ~500-600ms）
         let file = std::fs::File::open(&path)?;
         let mmap = unsafe { Mmap::map(&file)? };
         let mmap = Arc::new(mmap);
 
+        let source_id = SourceSpan::next_source_id();
+        let source = ParseSource::new(Some(source_id), mmap.clone());
+
         let mut cursor = Cursor::new(&mmap[..]);
 
-        let header = Record::parse(&mut cursor)?;
+        let header = Record::parse_in_source(&mut cursor, Some(&source))?;
         Self::validate_esp_file(&header)?;
 
         let masters = Self::extract_masters(&header);
-        let groups = Self::parse_groups(&mut cursor, &mmap[..])?;
+        let groups = Self::parse_groups(&mut cursor, &source, &ParseOptions::default())?;
 
         // 检查是否为本地化插件
         let is_localized = header.flags & 0x00000080 != 0;
@@ -220,21 +340,92 @@ impl Plugin {
             string_files,
             language,
             mmap: Some(mmap),
+            source_id: Some(source_id),
+            partial: false,
+            index: None,
         })
     }
 
+    /// 从已有的头部记录和组列表组装插件实例（不读取文件）
+    ///
+    /// 主文件列表从头部记录的 MAST 子记录中提取。
+    pub(crate) fn from_parts(
+        path: PathBuf,
+        header: Record,
+        groups: Vec<Group>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::validate_esp_file(&header)?;
+
+        let string_records = Self::load_string_records()?;
+        #[allow(deprecated)]
+        let string_router = Arc::new(DefaultStringRouter::from_embedded_data()?);
+        let masters = Self::extract_masters(&header);
+
+        #[allow(deprecated)]
+        Ok(Plugin {
+            path,
+            header,
+            groups,
+            masters,
+            string_records,
+            string_router,
+            string_files: None,
+            language: String::new(),
+            mmap: None,
+            source_id: None,
+            partial: false,
+            index: None,
+        })
+    }
+
+    /// 仅读取插件文件的头部记录并返回主文件列表（不解析组）
+    ///
+    /// 用于快速扫描目录中插件之间的依赖关系。
+    pub fn read_masters_from_file(path: &std::path::Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut file = std::fs::File::open(path)?;
+
+        let mut header_bytes = vec![0u8; 24];
+        file.read_exact(&mut header_bytes)?;
+
+        let data_size = u32::from_le_bytes([header_bytes[4], header_bytes[5], header_bytes[6], header_bytes[7]]);
+        let mut data = vec![0u8; data_size as usize];
+        file.read_exact(&mut data)?;
+        header_bytes.extend_from_slice(&data);
+
+        let mut cursor = Cursor::new(&header_bytes[..]);
+        let header = Record::parse(&mut cursor)?;
+        Self::validate_esp_file(&header)?;
+
+        Ok(Self::extract_masters(&header))
+    }
+
     /// 验证ESP文件格式
     pub(crate) fn validate_esp_file(header: &Record) -> Result<(), Box<dyn std::error::Error>> {
-        if !matches!(header.record_type.as_str(), "TES4" | "TES3") {
+        if !matches!(header.record_type.as_bytes(), b"TES4" | b"TES3") {
             return Err(EspError::InvalidFormat.into());
         }
         Ok(())
     }
 
     /// 解析所有组（并行版本，性能提升 1.5-2x）
-    pub(crate) fn parse_groups(cursor: &mut Cursor<&[u8]>, data: &[u8]) -> Result<Vec<Group>, Box<dyn std::error::Error>> {
-        // 第一遍：快速扫描获取所有顶级 Group 边界
-        let group_ranges = Self::scan_group_boundaries(cursor, data)?;
+    ///
+    /// 记录与子记录的数据引用 `source` 中的切片，不复制文件数据。
+    /// `options` 跳过的顶级组不会被解析。
+    pub(crate) fn parse_groups(
+        cursor: &mut Cursor<&[u8]>,
+        source: &ParseSource,
+        options: &ParseOptions,
+    ) -> Result<Vec<Group>, Box<dyn std::error::Error>> {
+        let data: &[u8] = (*source.bytes).as_ref();
+
+        // 第一遍：快速扫描获取所有顶级 Group 边界（组大小已验证不小于 24 字节）
+        let mut group_ranges = Self::scan_group_boundaries(cursor, data)?;
+        group_ranges.retain(|&(start, _)| {
+            let start = start as usize;
+            let label = data.get(start + 8..start + 12)
+                .and_then(|label| <[u8; 4]>::try_from(label).ok());
+            label.is_none_or(|label| options.parses_group(Signature(label)))
+        });
 
         if group_ranges.is_empty() {
             return Ok(Vec::new());
@@ -248,9 +439,10 @@ impl Plugin {
                 if end > data.len() as u64 {
                     return Err(format!("Group 边界超出数据范围: {}..{} (数据长度: {})", start, end, data.len()));
                 }
-                let group_data = &data[start as usize..end as usize];
-                let mut group_cursor = Cursor::new(group_data);
-                Group::parse(&mut group_cursor).map_err(|e| e.to_string())
+                // 游标位置保持为文件中的绝对偏移，数据截止到组末尾
+                let mut group_cursor = Cursor::new(&data[..end as usize]);
+                group_cursor.set_position(start);
+                Group::parse_in_source(&mut group_cursor, Some(source)).map_err(|e| e.to_string())
             })
             .collect();
 
@@ -311,9 +503,197 @@ impl Plugin {
 
     /// 从头部记录提取主文件列表
     pub(crate) fn extract_masters(header: &Record) -> Vec<String> {
-        header.subrecords.iter()
+        header.subrecords().iter()
             .filter(|sr| sr.record_type == "MAST")
             .map(|sr| RawString::parse_zstring(&sr.data).content)
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::CompressionPolicy;
+    use crate::group::GroupChild;
+    use crate::plugin::{PluginBuilder, WriteOptions};
+    use crate::plugin::test_support::zstring;
+    use crate::subrecord::Subrecord;
+
+    /// 写出包含 WEAP（压缩）、NAVI 和带引用的 CELL 的测试插件
+    fn write_fixture(path: &std::path::Path) {
+        let mut builder = PluginBuilder::new(path);
+        let full: Vec<u8> = zstring("Test Sword").into_iter().chain(std::iter::repeat_n(b'x', 2000)).collect();
+        let sword = builder.new_record("WEAP", vec![Subrecord::new("FULL", full)]);
+        builder.add_record(sword).unwrap();
+        let navi = builder.new_record("NAVI", vec![Subrecord::new("NVER", vec![12, 0, 0, 0])]);
+        builder.add_record(navi).unwrap();
+        let cell = builder.new_record("CELL", vec![Subrecord::new("FULL", zstring("Test Cell"))]);
+        let cell_id = builder.add_interior_cell(cell).unwrap();
+        let reference = builder.new_record("REFR", vec![Subrecord::new("NAME", vec![0; 4])]);
+        builder.add_cell_child(cell_id, reference, false).unwrap();
+
+        let options = WriteOptions { compression: CompressionPolicy::Always { threshold: 1024 } };
+        builder.build().unwrap().write_to_file_with(path.to_path_buf(), &options).unwrap();
+    }
+
+    #[test]
+    fn test_index_cache_hit_and_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        write_fixture(&path);
+
+        let cache = PluginCache::new(dir.path().join("cache"));
+        let options = ParseOptions { cache: Some(cache.clone()), ..ParseOptions::default() };
+
+        // 首次加载：完整解析并写入缓存
+        let parsed = Plugin::load_with_options(path.clone(), &options).unwrap();
+        assert!(!parsed.is_index_only());
+        assert!(cache.index_path(&path).exists());
+        let expected = parsed.extract_strings();
+        assert!(!expected.is_empty());
+
+        // 再次加载：只解析头部记录
+        let cached = Plugin::load_with_options(path.clone(), &options).unwrap();
+        assert!(cached.is_index_only());
+        assert!(cached.groups.is_empty());
+        assert_eq!(format!("{:?}", cached.extract_strings()), format!("{:?}", expected));
+
+        let sword_id = parsed.groups[0].get_records()[0].form_id;
+        let sword = cached.find_record(sword_id).unwrap();
+        assert_eq!(sword.record_type, "WEAP");
+        assert!(sword.find_subrecord("FULL").unwrap().data.starts_with(b"Test Sword\0"));
+        assert!(cached.find_record(0xDEADBEEF).is_none());
+
+        // 缓存加载的插件不能写入
+        assert!(cached.write_to(&mut Vec::new(), &WriteOptions::default()).is_err());
+
+        // 文件变化后缓存失效，重新完整解析
+        let mut plugin = Plugin::load(path.clone()).unwrap();
+        let sword = plugin.groups[0].find_record_mut(sword_id).unwrap();
+        sword.subrecords_mut()[0].set_data(zstring("Renamed"));
+        sword.subrecords_mut().insert(0, Subrecord::new("EDID", zstring("TestSword")));
+        sword.mark_modified();
+        plugin.write_to_file(path.clone()).unwrap();
+
+        let reloaded = Plugin::load_with_options(path.clone(), &options).unwrap();
+        assert!(!reloaded.is_index_only());
+        assert!(reloaded.extract_strings().iter().any(|s| s.text == "Renamed"));
+
+        let cached = Plugin::load_with_options(path, &options).unwrap();
+        assert!(cached.is_index_only());
+        let sword = cached.find_record_by_editor_id("TestSword").unwrap();
+        assert_eq!(sword.form_id, sword_id);
+        assert_eq!(sword.find_subrecord("FULL").unwrap().data, zstring("Renamed"));
+        assert!(cached.find_record_by_editor_id("Missing").is_none());
+    }
+
+    #[test]
+    fn test_filtered_lazy_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        write_fixture(&path);
+
+        let options = ParseOptions {
+            record_types: Some(["WEAP", "CELL"].iter().map(|t| Signature::from_name(t)).collect()),
+            lazy: true,
+            ..ParseOptions::default()
+        };
+        let plugin = Plugin::load_with_options(path, &options).unwrap();
+        assert!(plugin.is_partial());
+
+        // NAVI 顶级组被跳过，CELL 的引用子组被丢弃
+        let labels: Vec<Signature> = plugin.groups.iter().map(|group| group.label).collect();
+        assert_eq!(labels, vec!["WEAP", "CELL"]);
+        let cells = plugin.groups[1].get_records();
+        assert_eq!(cells.len(), 1);
+        assert!(!plugin.groups[1].children.iter().any(|child| matches!(child,
+            GroupChild::Group(block) if block.get_records().iter().any(|r| r.record_type == "REFR"))));
+
+        // 压缩记录在首次访问时才解压
+        let sword = plugin.groups[0].get_records()[0];
+        assert!(!sword.is_parsed());
+        let full = sword.find_subrecord("FULL").unwrap();
+        assert!(full.data.starts_with(b"Test Sword\0"));
+        assert!(sword.is_parsed());
+
+        // 部分加载的插件不能写入
+        assert!(plugin.write_to(&mut Vec::new(), &WriteOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_lazy_load_writes_identical_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        write_fixture(&path);
+
+        let options = ParseOptions { lazy: true, ..ParseOptions::default() };
+        let mut plugin = Plugin::load_with_options(path.clone(), &options).unwrap();
+        assert!(!plugin.is_partial());
+
+        let mut output = Vec::new();
+        plugin.write_to(&mut output, &WriteOptions::default()).unwrap();
+        assert_eq!(output, std::fs::read(&path).unwrap());
+
+        // 修改延迟解析的压缩记录
+        let form_id = plugin.groups[0].get_records()[0].form_id;
+        let sword = plugin.groups[0].find_record_mut(form_id).unwrap();
+        sword.subrecords_mut()[0].set_data(zstring("Renamed"));
+        sword.mark_modified();
+        plugin.write_to_file(path.clone()).unwrap();
+
+        let reloaded = Plugin::load(path).unwrap();
+        let sword = reloaded.groups[0].get_records()[0];
+        assert_eq!(sword.find_subrecord("FULL").unwrap().data, zstring("Renamed"));
+    }
+
+    #[test]
+    fn test_large_nested_groups_parse_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+
+        // 单元组超过并行阈值，块/子块/单元子项各层都会被拆分
+        let mut builder = PluginBuilder::new(&path);
+        for i in 0..400 {
+            let cell = builder.new_record("CELL", vec![Subrecord::new("EDID", zstring(&format!("Cell{}", i)))]);
+            let cell_id = builder.add_interior_cell(cell).unwrap();
+            for _ in 0..2 {
+                let reference = builder.new_record("REFR", vec![Subrecord::new("DATA", vec![i as u8; 2048])]);
+                builder.add_cell_child(cell_id, reference, false).unwrap();
+            }
+        }
+        builder.build().unwrap().write_to_file(path.clone()).unwrap();
+
+        // 顺序遍历文件得到所有单元组记录的 FormID（按文件顺序）
+        let bytes = std::fs::read(&path).unwrap();
+        let mut expected = Vec::new();
+        let mut pos = 0;
+        let mut in_cells = false;
+        while pos < bytes.len() {
+            let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            if &bytes[pos..pos + 4] == b"GRUP" {
+                if bytes[pos + 12..pos + 16] == [0; 4] {
+                    in_cells = &bytes[pos + 8..pos + 12] == b"CELL";
+                }
+                pos += 24;
+            } else {
+                if in_cells {
+                    expected.push(u32::from_le_bytes(bytes[pos + 12..pos + 16].try_into().unwrap()));
+                }
+                pos += 24 + size;
+            }
+        }
+        assert_eq!(expected.len(), 1200);
+
+        let plugin = Plugin::load(path.clone()).unwrap();
+        let cells = plugin.groups.iter().find(|group| group.label == "CELL").unwrap();
+        assert!(cells.size > 1024 * 1024);
+        let form_ids: Vec<u32> = cells.get_records().iter().map(|record| record.form_id).collect();
+        assert_eq!(form_ids, expected);
+
+        // 不复制源文件数据，按解析结果重新序列化应得到相同字节
+        let options = WriteOptions { compression: CompressionPolicy::Never };
+        let mut output = Vec::new();
+        plugin.write_to(&mut output, &options).unwrap();
+        assert_eq!(output, bytes);
+    }
+}
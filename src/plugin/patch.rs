@@ -0,0 +1,160 @@
+use super::translate::apply_translations_to_group;
+use super::Plugin;
+use crate::datatypes::RecordFlags;
+use crate::record::Record;
+use crate::string_types::ExtractedString;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+impl Plugin {
+    /// 生成独立的翻译覆盖补丁（不修改原插件）
+    ///
+    /// 补丁插件以原插件的主文件加原插件自身作为主文件列表（主文件索引保持不变，无需重映射），
+    /// 只包含字符串被翻译改变的记录（作为覆盖记录复制），以及维持组结构所需的
+    /// 父记录（WRLD/CELL/DIAL）。原插件中尚未保存的修改记录也会被包含。
+    /// 返回的补丁通过 `write_to_file()` 写出。
+    ///
+    /// # 参数
+    /// * `translations` - 翻译列表
+    /// * `output_path` - 补丁插件路径（决定插件名称）
+    /// * `esl` - 是否设置 ESL 标志（补丁不含新记录，始终满足 ESL 限制）
+    ///
+    /// # 错误
+    /// 本地化插件的字符串保存在 STRING 文件中，无法生成覆盖补丁。
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let plugin = Plugin::load("MyMod.esp".into())?;
+    /// let patch = plugin.create_translation_patch(translations, "MyMod_CHS.esp".into(), true)?;
+    /// patch.write_to_file("MyMod_CHS.esp".into())?;
+    /// ```
+    pub fn create_translation_patch(
+        &self,
+        translations: Vec<ExtractedString>,
+        output_path: PathBuf,
+        esl: bool,
+    ) -> Result<Plugin, Box<dyn std::error::Error>> {
+        if self.is_localized() {
+            return Err("本地化插件的字符串保存在 STRING 文件中，无法生成覆盖补丁".into());
+        }
+
+        let translation_map: HashMap<String, ExtractedString> = translations
+            .into_iter()
+            .map(|t| (t.get_unique_key(), t))
+            .collect();
+
+        let plugin_name = self.get_name().to_string();
+        let mut groups = self.groups.clone();
+
+        let mut _applied_count = 0;
+        for group in &mut groups {
+            _applied_count += apply_translations_to_group(
+                group,
+                &translation_map,
+                self.string_router(),
+                &self.masters,
+                &plugin_name,
+            )?;
+        }
+
+        for group in &mut groups {
+            group.retain_records(|record| record.is_modified);
+        }
+        groups.retain(|group| !group.children.is_empty());
+
+        let flags = if esl { RecordFlags::LIGHT_MASTER.bits() } else { 0 };
+        let header = Record::new("TES4", 0, flags, Vec::new());
+        let mut patch = Plugin::from_parts(output_path, header, groups)?;
+
+        let mut masters = self.masters.clone();
+        masters.push(plugin_name);
+        patch.set_masters(masters);
+        patch.update_header_counts();
+
+        #[cfg(debug_assertions)]
+        println!(
+            "翻译补丁生成完成：应用 {} 个翻译，包含 {} 个记录和组",
+            _applied_count,
+            patch.count_records_and_groups()
+        );
+
+        Ok(patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::{Group, GroupChild};
+    use crate::plugin::test_support::{builder, edid, zstring};
+    use crate::subrecord::Subrecord;
+
+    /// 模拟从文件加载的插件：清除新建记录的修改标记
+    fn clear_modified(group: &mut Group) {
+        for child in &mut group.children {
+            match child {
+                GroupChild::Group(nested) => clear_modified(nested),
+                GroupChild::Record(record) => record.is_modified = false,
+            }
+        }
+    }
+
+    /// WEAP SwordA (0x01000800)、SwordB (0x01000801)，DIAL Topic (0x01000802) 及其 INFO (0x01000803)
+    fn create_test_plugin() -> Plugin {
+        let mut builder = builder("Source.esp", &["Skyrim.esm"]);
+        for (editor_id, name) in [("SwordA", "Sword A"), ("SwordB", "Sword B")] {
+            let weapon = builder.new_record("WEAP", vec![edid(editor_id), Subrecord::new("FULL", zstring(name))]);
+            builder.add_record(weapon).unwrap();
+        }
+        let topic = builder.new_record("DIAL", vec![edid("Topic")]);
+        let topic = builder.add_record(topic).unwrap();
+        let info = builder.new_record("INFO", vec![Subrecord::new("NAM1", zstring("Hello there"))]);
+        builder.add_topic_info(topic, info).unwrap();
+
+        let mut source = builder.build().unwrap();
+        for group in &mut source.groups {
+            clear_modified(group);
+        }
+        source
+    }
+
+    #[test]
+    fn test_translation_patch_contains_only_changed_records() {
+        let source = create_test_plugin();
+        let translations: Vec<ExtractedString> = source.extract_strings()
+            .into_iter()
+            .filter(|s| s.text == "Sword A" || s.text == "Hello there")
+            .map(|mut s| {
+                s.text = format!("{} (CN)", s.text);
+                s
+            })
+            .collect();
+        assert_eq!(translations.len(), 2);
+
+        let patch = source.create_translation_patch(translations, PathBuf::from("Source_CN.esp"), true).unwrap();
+
+        assert_eq!(patch.masters, vec!["Skyrim.esm", "Source.esp"]);
+        assert!(patch.is_light());
+
+        let weapons = patch.groups[0].get_records();
+        assert_eq!(weapons.len(), 1);
+        assert_eq!(weapons[0].form_id, 0x01000800);
+        assert_eq!(weapons[0].find_subrecord("FULL").unwrap().data, zstring("Sword A (CN)"));
+
+        // DIAL 父记录随 INFO 一起保留
+        let dialogue: Vec<u32> = patch.groups[1].get_records().iter().map(|r| r.form_id).collect();
+        assert_eq!(dialogue, vec![0x01000802, 0x01000803]);
+
+        // 原插件未被修改
+        assert_eq!(source.groups[0].get_records()[0].find_subrecord("FULL").unwrap().data, zstring("Sword A"));
+    }
+
+    #[test]
+    fn test_translation_patch_without_changes_is_empty() {
+        let source = create_test_plugin();
+        let unchanged = source.extract_strings();
+        let patch = source.create_translation_patch(unchanged, PathBuf::from("Empty.esp"), false).unwrap();
+        assert!(patch.groups.is_empty());
+        assert!(!patch.is_light());
+    }
+}
@@ -0,0 +1,249 @@
+use super::Plugin;
+use crate::datatypes::Signature;
+use crate::form_id_fields::FormIdFields;
+use crate::group::{Group, GroupChild, GroupType};
+use crate::record::Record;
+use serde::Serialize;
+
+/// FormID 引用位置
+///
+/// 描述插件中一处 FormID 出现的位置，用于主文件引用检查。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormIdReference {
+    /// 所在记录的 FormID（头部记录为 0）
+    pub form_id: u32,
+    /// 所在记录类型
+    pub record_type: Signature,
+    /// 所在记录的编辑器ID
+    pub editor_id: Option<String>,
+    /// 子记录类型（`None` 表示记录自身的 FormID）
+    pub subrecord_type: Option<Signature>,
+    /// 被引用的 FormID
+    pub referenced_form_id: u32,
+}
+
+impl std::fmt::Display for FormIdReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:08X}] {}", self.form_id, self.record_type)?;
+        if let Some(editor_id) = &self.editor_id {
+            write!(f, " ({})", editor_id)?;
+        }
+        match &self.subrecord_type {
+            Some(subrecord_type) => write!(f, " {} -> {:08X}", subrecord_type, self.referenced_form_id),
+            None => write!(f, " 记录自身"),
+        }
+    }
+}
+
+/// 可能包含 FormID、但布局不固定而无法重写的子记录位置
+///
+/// 对应 formid_fields.json 中标记为 `"unsupported"` 的字段（CTDA、VMAD 等），以及未列出的记录类型中
+/// 没有通用定义的字段。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UncoveredField {
+    /// 所在记录的 FormID
+    pub form_id: u32,
+    /// 所在记录类型
+    pub record_type: Signature,
+    /// 所在记录的编辑器ID
+    pub editor_id: Option<String>,
+    /// 子记录类型
+    pub subrecord_type: Signature,
+}
+
+impl std::fmt::Display for UncoveredField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:08X}] {}", self.form_id, self.record_type)?;
+        if let Some(editor_id) = &self.editor_id {
+            write!(f, " ({})", editor_id)?;
+        }
+        write!(f, " {}", self.subrecord_type)
+    }
+}
+
+impl Plugin {
+    /// 收集可能包含 FormID、但无法定位重写的子记录
+    ///
+    /// 这些字段中的 FormID 不在 `collect_form_id_references()` 的结果中，也不会被
+    /// `remap_form_ids()` 改写，修改主文件列表前应确认结果为空。同一记录中的同类子记录只报告一次。
+    pub fn uncovered_form_id_fields(&self) -> Vec<UncoveredField> {
+        let fields = FormIdFields::embedded();
+        let mut uncovered = Vec::new();
+        for group in &self.groups {
+            Self::collect_group_uncovered(group, fields, &mut uncovered);
+        }
+        uncovered
+    }
+
+    /// 收集组内所有记录中无法重写的 FormID 字段
+    pub(crate) fn collect_group_uncovered(
+        group: &Group,
+        fields: &FormIdFields,
+        uncovered: &mut Vec<UncoveredField>,
+    ) {
+        for record in group.get_records() {
+            let mut seen = Vec::new();
+            for subrecord in record.subrecords() {
+                if fields.is_unsupported(record.record_type, subrecord.record_type)
+                    && !seen.contains(&subrecord.record_type)
+                {
+                    seen.push(subrecord.record_type);
+                    uncovered.push(UncoveredField {
+                        form_id: record.form_id,
+                        record_type: record.record_type,
+                        editor_id: record.get_editor_id(),
+                        subrecord_type: subrecord.record_type,
+                    });
+                }
+            }
+        }
+    }
+
+    /// 收集插件中所有 FormID 引用
+    ///
+    /// 包括记录自身的 FormID 以及 formid_fields.json 中定义的 FormID 字段（含头部 ONAM）。
+    /// 空 FormID (0) 不计入。
+    pub fn collect_form_id_references(&self) -> Vec<FormIdReference> {
+        let fields = FormIdFields::embedded();
+        let mut references = Vec::new();
+
+        Self::collect_record_field_references(&self.header, fields, &mut references);
+        for group in &self.groups {
+            Self::collect_group_references(group, fields, &mut references);
+        }
+
+        references
+    }
+
+    /// 收集组内所有记录的 FormID 引用
+    pub(crate) fn collect_group_references(
+        group: &Group,
+        fields: &FormIdFields,
+        references: &mut Vec<FormIdReference>,
+    ) {
+        for record in group.get_records() {
+            references.push(FormIdReference {
+                form_id: record.form_id,
+                record_type: record.record_type,
+                editor_id: record.get_editor_id(),
+                subrecord_type: None,
+                referenced_form_id: record.form_id,
+            });
+            Self::collect_record_field_references(record, fields, references);
+        }
+    }
+
+    /// 按映射函数重写插件中所有 FormID
+    ///
+    /// 重写范围：记录自身 FormID、FormID 字段（含头部 ONAM）、以 FormID 为标签的组
+    /// （世界空间/单元/对话主题子组）。值发生变化的记录会被标记为已修改。
+    ///
+    /// # 返回
+    /// 被修改的记录数量（不含头部）
+    pub fn remap_form_ids<F: Fn(u32) -> u32>(&mut self, remap: F) -> usize {
+        let fields = FormIdFields::embedded();
+
+        if Self::remap_record_fields(&mut self.header, fields, &remap) {
+            self.header.is_modified = true;
+        }
+
+        let mut modified = 0;
+        for group in &mut self.groups {
+            modified += Self::remap_group(group, fields, &remap);
+        }
+        modified
+    }
+
+    /// 递归重写组及其子元素中的 FormID
+    pub(crate) fn remap_group<F: Fn(u32) -> u32>(group: &mut Group, fields: &FormIdFields, remap: &F) -> usize {
+        if matches!(
+            group.group_type,
+            GroupType::WorldChildren
+                | GroupType::CellChildren
+                | GroupType::TopicChildren
+                | GroupType::CellPersistentChildren
+                | GroupType::CellTemporaryChildren
+        ) {
+            let label = group.label.to_u32();
+            if label != 0 {
+                group.label = Signature(remap(label).to_le_bytes());
+            }
+        }
+
+        let mut modified = 0;
+        for child in &mut group.children {
+            match child {
+                GroupChild::Group(nested_group) => {
+                    modified += Self::remap_group(nested_group, fields, remap);
+                }
+                GroupChild::Record(record) => {
+                    let mut changed = Self::remap_record_fields(record, fields, remap);
+
+                    let new_form_id = remap(record.form_id);
+                    if new_form_id != record.form_id {
+                        record.form_id = new_form_id;
+                        changed = true;
+                    }
+
+                    if changed {
+                        record.is_modified = true;
+                        modified += 1;
+                    }
+                }
+            }
+        }
+        modified
+    }
+
+    /// 重写记录中 FormID 字段的值，返回是否有字段被修改
+    fn remap_record_fields<F: Fn(u32) -> u32>(record: &mut Record, fields: &FormIdFields, remap: &F) -> bool {
+        let mut changed = false;
+
+        let record_type = record.record_type;
+        for subrecord in record.subrecords_mut() {
+            let offsets = fields.form_id_offsets(record_type, subrecord.record_type, subrecord.data.len());
+            for offset in offsets {
+                let bytes = &subrecord.data[offset..offset + 4];
+                let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                if value == 0 {
+                    continue;
+                }
+
+                let new_value = remap(value);
+                if new_value != value {
+                    // 只有实际改写时才复制共享数据
+                    subrecord.data.to_mut()[offset..offset + 4].copy_from_slice(&new_value.to_le_bytes());
+                    changed = true;
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// 收集记录中 FormID 字段的引用
+    fn collect_record_field_references(
+        record: &Record,
+        fields: &FormIdFields,
+        references: &mut Vec<FormIdReference>,
+    ) {
+        for subrecord in record.subrecords() {
+            let offsets = fields.form_id_offsets(record.record_type, subrecord.record_type, subrecord.data.len());
+            for offset in offsets {
+                let bytes = &subrecord.data[offset..offset + 4];
+                let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                if value == 0 {
+                    continue;
+                }
+
+                references.push(FormIdReference {
+                    form_id: record.form_id,
+                    record_type: record.record_type,
+                    editor_id: record.get_editor_id(),
+                    subrecord_type: Some(subrecord.record_type),
+                    referenced_form_id: value,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use super::Plugin;
+use super::cache::StringValue;
+use crate::string_file::{StringFileSet, StringFileType};
+use crate::string_types::{unique_key, ExtractedString};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 单个语言的翻译应用报告
+#[derive(Debug, Clone, Serialize)]
+pub struct StringApplyReport {
+    /// 目标语言
+    pub language: String,
+    /// 成功应用的翻译数量
+    pub applied: usize,
+    /// 未提供翻译、使用源语言文本的字符串数量
+    pub filled_from_source: usize,
+    /// 在插件中找不到对应 StringID 的翻译键
+    pub unmatched: Vec<String>,
+    /// 更新失败的字符串（源语言 STRING 文件中不存在）
+    pub failed: Vec<u32>,
+    /// 写出的 STRING 文件
+    pub files: Vec<PathBuf>,
+}
+
+impl std::fmt::Display for StringApplyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== 翻译应用报告: {} ===", self.language)?;
+        writeln!(f, "已应用翻译: {}", self.applied)?;
+        writeln!(f, "使用源语言填充: {}", self.filled_from_source)?;
+        writeln!(f, "未匹配的翻译: {}", self.unmatched.len())?;
+        for key in &self.unmatched {
+            writeln!(f, "  - {}", key)?;
+        }
+        if !self.failed.is_empty() {
+            writeln!(f, "更新失败的 StringID: {:?}", self.failed)?;
+        }
+        for file in &self.files {
+            writeln!(f, "已写入: {}", file.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl Plugin {
+    /// 一次为多个目标语言生成 STRING 文件（本地化插件）
+    ///
+    /// 每种语言以 `source` 的 STRING 文件为基础，应用该语言的翻译后写入
+    /// `output_dir/strings/<插件>_<语言>.*STRINGS`；未翻译的字符串保留源语言文本。
+    /// 插件只解析一次，StringID 映射在所有语言间共享。
+    ///
+    /// # 参数
+    /// * `translations` - 语言 -> 翻译列表
+    /// * `source` - 源语言的 STRING 文件集合（未翻译条目的来源）
+    /// * `output_dir` - 输出目录
+    ///
+    /// # 返回
+    /// 每种语言一份应用报告（按语言排序）
+    pub fn apply_translations_multilingual(
+        &self,
+        translations: &HashMap<String, Vec<ExtractedString>>,
+        source: &StringFileSet,
+        output_dir: &Path,
+    ) -> Result<Vec<StringApplyReport>, Box<dyn std::error::Error>> {
+        if !self.is_localized() {
+            return Err(format!("插件 {} 未设置 LOCALIZED 标志，无法生成 STRING 文件", self.get_name()).into());
+        }
+
+        // UniqueKey -> (StringFileType, StringID)，所有语言共享
+        let mut string_id_map: HashMap<String, (StringFileType, u32)> = HashMap::new();
+        for site in self.string_sites() {
+            if let StringValue::Localized { file_type, string_id } = site.value {
+                let key = unique_key(
+                    site.editor_id.as_deref(),
+                    &self.format_form_id(site.form_id),
+                    site.record_type,
+                    site.subrecord_type,
+                    site.index,
+                );
+                string_id_map.insert(key, (file_type, string_id));
+            }
+        }
+        let referenced: HashSet<(StringFileType, u32)> = string_id_map.values().copied().collect();
+
+        let string_dir = output_dir.join("strings");
+        std::fs::create_dir_all(&string_dir)?;
+
+        let languages: BTreeMap<&String, &Vec<ExtractedString>> = translations.iter().collect();
+        let mut reports = Vec::with_capacity(languages.len());
+
+        for (language, language_translations) in languages {
+            let mut set = source.clone();
+            set.language = language.clone();
+            for file in set.files.values_mut() {
+                file.language = language.clone();
+            }
+
+            let mut report = StringApplyReport {
+                language: language.clone(),
+                applied: 0,
+                filled_from_source: 0,
+                unmatched: Vec::new(),
+                failed: Vec::new(),
+                files: Vec::new(),
+            };
+
+            let mut translated: HashSet<(StringFileType, u32)> = HashSet::new();
+            for trans in language_translations {
+                let key = trans.get_unique_key();
+                let Some(&(file_type, string_id)) = string_id_map.get(&key) else {
+                    report.unmatched.push(key);
+                    continue;
+                };
+
+                match set.update_string(file_type, string_id, trans.get_text_to_apply().to_string()) {
+                    Ok(_) => {
+                        report.applied += 1;
+                        translated.insert((file_type, string_id));
+                    }
+                    Err(_) => report.failed.push(string_id),
+                }
+            }
+
+            report.filled_from_source = referenced
+                .iter()
+                .filter(|site| !translated.contains(site) && source.get_string_by_type(site.0, site.1).is_some())
+                .count();
+
+            set.write_all(&string_dir)?;
+            let mut file_types: Vec<StringFileType> = set.files.keys().copied().collect();
+            file_types.sort_by_key(|file_type| file_type.to_extension());
+            report.files = file_types
+                .into_iter()
+                .map(|file_type| string_dir.join(format!("{}_{}.{}", set.plugin_name, language, file_type.to_extension())))
+                .collect();
+
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::PluginBuilder;
+    use crate::string_file::StringFile;
+    use crate::subrecord::Subrecord;
+
+    #[test]
+    fn test_apply_translations_multilingual() {
+        let mut builder = PluginBuilder::new("Multi.esp").localized(true);
+        let sword = builder.new_record("WEAP", vec![
+            Subrecord::new("EDID", b"TestSword\0".to_vec()),
+            Subrecord::new("FULL", 1u32.to_le_bytes().to_vec()),
+        ]);
+        builder.add_record(sword).unwrap();
+        let coin = builder.new_record("MISC", vec![Subrecord::new("FULL", 2u32.to_le_bytes().to_vec())]);
+        builder.add_record(coin).unwrap();
+        let mut plugin = builder.build().unwrap();
+
+        let mut strings = StringFile::empty("Multi".to_string(), "english".to_string(), StringFileType::STRINGS);
+        strings.add_string(1, "Sword".to_string()).unwrap();
+        strings.add_string(2, "Coin".to_string()).unwrap();
+        let mut source = StringFileSet::new("Multi".to_string(), "english".to_string());
+        source.add_file(StringFileType::STRINGS, strings);
+        plugin.set_string_files(source.clone());
+
+        let extracted = plugin.extract_strings();
+        let sword = extracted.iter().find(|s| s.record_type == "WEAP").unwrap();
+        let translate = |text: &str| {
+            let mut translation = sword.clone();
+            translation.text = text.to_string();
+            translation
+        };
+        let mut unknown = translate("???");
+        unknown.editor_id = Some("Missing".to_string());
+
+        let translations = HashMap::from([
+            ("chinese".to_string(), vec![translate("剑")]),
+            ("german".to_string(), vec![translate("Schwert"), unknown]),
+        ]);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let reports = plugin.apply_translations_multilingual(&translations, &source, temp_dir.path()).unwrap();
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].language, "chinese");
+        assert_eq!(reports[0].applied, 1);
+        assert_eq!(reports[0].filled_from_source, 1);
+        assert_eq!(reports[1].language, "german");
+        assert_eq!(reports[1].unmatched.len(), 1);
+
+        let german = StringFile::new(temp_dir.path().join("strings/Multi_german.STRINGS")).unwrap();
+        assert_eq!(german.get_string(1).unwrap().content, "Schwert");
+        assert_eq!(german.get_string(2).unwrap().content, "Coin");
+        assert_eq!(reports[1].files, vec![temp_dir.path().join("strings/Multi_german.STRINGS")]);
+
+        // 源语言不受影响
+        assert_eq!(source.get_string(1).unwrap().content, "Sword");
+    }
+}
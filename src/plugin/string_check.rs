@@ -0,0 +1,395 @@
+use super::Plugin;
+use super::cache::StringValue;
+use crate::datatypes::Signature;
+use crate::string_file::{StringFile, StringFileSet, StringFileType};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// 检查顺序（与 `StringFileSet::get_string` 的查找优先级一致）
+const FILE_TYPES: [StringFileType; 3] = [
+    StringFileType::STRINGS,
+    StringFileType::ILSTRINGS,
+    StringFileType::DLSTRINGS,
+];
+
+/// 插件引用但 STRING 文件中不存在的字符串
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingString {
+    /// 字符串ID
+    pub string_id: u32,
+    /// 应在的文件类型
+    pub file_type: StringFileType,
+    /// 引用它的记录 FormID
+    pub form_id: u32,
+    /// 引用它的记录编辑器ID
+    pub editor_id: Option<String>,
+    /// 记录类型
+    pub record_type: Signature,
+    /// 子记录类型
+    pub subrecord_type: Signature,
+}
+
+/// 存在于错误文件类型中的字符串
+#[derive(Debug, Clone, Serialize)]
+pub struct MisplacedString {
+    /// 字符串ID
+    pub string_id: u32,
+    /// 插件引用时对应的文件类型
+    pub expected: StringFileType,
+    /// 实际所在的文件类型
+    pub found_in: StringFileType,
+    /// 字符串内容
+    pub content: String,
+}
+
+/// 没有任何记录引用的字符串
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanString {
+    /// 字符串ID
+    pub string_id: u32,
+    /// 所在文件类型
+    pub file_type: StringFileType,
+    /// 字符串内容
+    pub content: String,
+}
+
+/// STRING 文件完整性检查报告
+///
+/// 由 `Plugin::check_strings()` 生成；`Plugin::fix_strings()` 在生成报告后把放错文件类型的字符串
+/// 移到正确的文件中，并可选删除孤立字符串（缺失的字符串无法自动修复）。
+///
+/// 孤立字符串按字符串路由判断，路由表未覆盖的本地化字段（如 FACT 的等级称号）引用的字符串
+/// 也会被列为孤立，因此默认只报告、不删除。
+#[derive(Debug, Clone, Serialize)]
+pub struct StringCheckReport {
+    /// 插件名称
+    pub plugin_name: String,
+    /// 插件引用的不同字符串数（按文件类型和 ID 计）
+    pub referenced_count: usize,
+    /// STRING 文件中的字符串总数
+    pub string_count: usize,
+    /// 缺失的字符串（每个引用一条）
+    pub missing: Vec<MissingString>,
+    /// 放错文件类型的字符串
+    pub misplaced: Vec<MisplacedString>,
+    /// 孤立字符串
+    pub orphans: Vec<OrphanString>,
+    /// 是否已执行修复
+    pub fixed: bool,
+    /// 修复时是否删除了孤立字符串
+    pub orphans_deleted: bool,
+    /// 警告信息
+    pub warnings: Vec<String>,
+}
+
+impl StringCheckReport {
+    /// 是否没有发现问题
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.misplaced.is_empty() && self.orphans.is_empty()
+    }
+
+    /// 问题总数
+    pub fn issue_count(&self) -> usize {
+        self.missing.len() + self.misplaced.len() + self.orphans.len()
+    }
+}
+
+impl std::fmt::Display for StringCheckReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== STRING 文件完整性检查 ===")?;
+        writeln!(f, "插件: {}", self.plugin_name)?;
+        writeln!(f, "引用的字符串: {}", self.referenced_count)?;
+        writeln!(f, "STRING 文件中的字符串: {}", self.string_count)?;
+        writeln!(f, "缺失: {}", self.missing.len())?;
+        for missing in &self.missing {
+            writeln!(f, "  [{:?}] {} <- {:08X} {}:{} {}",
+                missing.file_type,
+                missing.string_id,
+                missing.form_id,
+                missing.record_type,
+                missing.subrecord_type,
+                missing.editor_id.as_deref().unwrap_or("")
+            )?;
+        }
+        writeln!(f, "文件类型错误: {}", self.misplaced.len())?;
+        for misplaced in &self.misplaced {
+            writeln!(f, "  {} 在 {:?} 中，应在 {:?} 中", misplaced.string_id, misplaced.found_in, misplaced.expected)?;
+        }
+        writeln!(f, "孤立: {}", self.orphans.len())?;
+        if self.fixed {
+            if self.orphans_deleted {
+                writeln!(f, "已修复: 删除 {} 个孤立字符串，移动 {} 个字符串", self.orphans.len(), self.misplaced.len())?;
+            } else {
+                writeln!(f, "已修复: 移动 {} 个字符串（孤立字符串未删除）", self.misplaced.len())?;
+            }
+        }
+        for warning in &self.warnings {
+            writeln!(f, "警告: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+impl Plugin {
+    /// 检查插件引用的字符串与 STRING 文件是否一致
+    ///
+    /// 按字符串路由收集所有本地化字符串子记录中的 StringID，与 `string_files` 交叉比对：
+    /// - 缺失：引用的 ID 不在任何 STRING 文件中
+    /// - 文件类型错误：引用的 ID 不在应在的文件中，但在其他类型的文件中
+    /// - 孤立：STRING 文件中没有被任何记录引用的字符串
+    pub fn check_strings(&self, string_files: &StringFileSet) -> StringCheckReport {
+        let mut report = StringCheckReport {
+            plugin_name: self.get_name().to_string(),
+            referenced_count: 0,
+            string_count: string_files.total_count(),
+            missing: Vec::new(),
+            misplaced: Vec::new(),
+            orphans: Vec::new(),
+            fixed: false,
+            orphans_deleted: false,
+            warnings: Vec::new(),
+        };
+
+        if !self.is_localized() {
+            report.warnings.push("插件未设置 LOCALIZED 标志，字符串直接存储在插件中，跳过检查".to_string());
+            return report;
+        }
+        if self.is_partial() && !self.is_index_only() {
+            report.warnings.push("插件按解析选项跳过了部分记录，孤立字符串的判断可能不准确".to_string());
+        }
+
+        let contains = |file_type: StringFileType, id: u32| {
+            string_files.get_file(&file_type).is_some_and(|file| file.get_string(id).is_some())
+        };
+
+        // 插件引用的 (文件类型, ID)；缺失的字符串每个引用报告一次，放错位置的每个 ID 报告一次
+        let mut referenced: HashSet<(StringFileType, u32)> = HashSet::new();
+        for site in self.string_sites() {
+            let StringValue::Localized { file_type, string_id } = site.value else {
+                continue;
+            };
+            let first_reference = referenced.insert((file_type, string_id));
+
+            if contains(file_type, string_id) {
+                continue;
+            }
+            match FILE_TYPES.iter().find(|&&other| contains(other, string_id)) {
+                Some(&found_in) if first_reference => report.misplaced.push(MisplacedString {
+                    string_id,
+                    expected: file_type,
+                    found_in,
+                    content: string_files.get_string_by_type(found_in, string_id)
+                        .map(|entry| entry.content.clone())
+                        .unwrap_or_default(),
+                }),
+                Some(_) => {}
+                None => report.missing.push(MissingString {
+                    string_id,
+                    file_type,
+                    form_id: site.form_id,
+                    editor_id: site.editor_id,
+                    record_type: site.record_type,
+                    subrecord_type: site.subrecord_type,
+                }),
+            }
+        }
+        report.referenced_count = referenced.len();
+
+        // 放错位置的字符串会被移动，不算孤立
+        let misplaced: HashSet<(StringFileType, u32)> = report.misplaced.iter()
+            .map(|misplaced| (misplaced.found_in, misplaced.string_id))
+            .collect();
+        for file_type in FILE_TYPES {
+            let Some(file) = string_files.get_file(&file_type) else {
+                continue;
+            };
+            for id in file.get_string_ids() {
+                if referenced.contains(&(file_type, id)) || misplaced.contains(&(file_type, id)) {
+                    continue;
+                }
+                report.orphans.push(OrphanString {
+                    string_id: id,
+                    file_type,
+                    content: file.get_string(id).map(|entry| entry.content.clone()).unwrap_or_default(),
+                });
+            }
+        }
+
+        report
+    }
+
+    /// 检查并修复 STRING 文件：把放错文件类型的字符串移到正确的文件中
+    ///
+    /// 缺少目标类型的文件时新建一个空文件。字符串路由表并未覆盖所有本地化字段，
+    /// 孤立字符串中可能包含仍在使用的文本，因此只有 `delete_orphans` 为 `true` 时才删除。
+    /// 返回修复前的检查报告（`fixed` 为 `true`）。
+    pub fn fix_strings(
+        &self,
+        string_files: &mut StringFileSet,
+        delete_orphans: bool,
+    ) -> Result<StringCheckReport, Box<dyn std::error::Error>> {
+        if delete_orphans && self.is_partial() && !self.is_index_only() {
+            return Err("插件按解析选项跳过了部分记录，无法安全删除孤立字符串".into());
+        }
+
+        let mut report = self.check_strings(string_files);
+        if !self.is_localized() {
+            return Ok(report);
+        }
+
+        let referenced: HashSet<(StringFileType, u32)> = self.string_sites().into_iter()
+            .filter_map(|site| match site.value {
+                StringValue::Localized { file_type, string_id } => Some((file_type, string_id)),
+                StringValue::Inline(_) => None,
+            })
+            .collect();
+
+        for misplaced in &report.misplaced {
+            let entry = string_files.get_string_by_type(misplaced.found_in, misplaced.string_id)
+                .cloned()
+                .ok_or_else(|| format!("字符串 {} 不在 {:?} 中", misplaced.string_id, misplaced.found_in))?;
+
+            if string_files.get_file(&misplaced.expected).is_none() {
+                let file = StringFile::empty(
+                    string_files.plugin_name.clone(),
+                    string_files.language.clone(),
+                    misplaced.expected,
+                );
+                string_files.add_file(misplaced.expected, file);
+            }
+            let target = string_files.get_file_mut(&misplaced.expected).ok_or("无法创建 STRING 文件")?;
+            target.add_string(entry.id, entry.content)?;
+
+            // 原位置的条目同时被其他引用使用时保留
+            if !referenced.contains(&(misplaced.found_in, misplaced.string_id)) {
+                if let Some(source) = string_files.get_file_mut(&misplaced.found_in) {
+                    source.remove_string(misplaced.string_id);
+                }
+            }
+        }
+
+        if delete_orphans {
+            for orphan in &report.orphans {
+                if let Some(file) = string_files.get_file_mut(&orphan.file_type) {
+                    file.remove_string(orphan.string_id);
+                }
+            }
+            report.orphans_deleted = true;
+        }
+
+        report.fixed = true;
+        Ok(report)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::PluginBuilder;
+    use crate::subrecord::Subrecord;
+
+    fn string_id(id: u32) -> Vec<u8> {
+        id.to_le_bytes().to_vec()
+    }
+
+    /// WEAP FULL=1 DESC=2，MISC FULL=3（STRINGS 中缺失）
+    fn localized_plugin() -> Plugin {
+        let mut builder = PluginBuilder::new("Test.esp").localized(true);
+        let sword = builder.new_record("WEAP", vec![
+            Subrecord::new("EDID", b"TestSword\0".to_vec()),
+            Subrecord::new("FULL", string_id(1)),
+            Subrecord::new("DESC", string_id(2)),
+        ]);
+        builder.add_record(sword).unwrap();
+        let coin = builder.new_record("MISC", vec![Subrecord::new("FULL", string_id(3))]);
+        builder.add_record(coin).unwrap();
+        builder.build().unwrap()
+    }
+
+    /// STRINGS 包含 1、2（应在 DLSTRINGS 中）和孤立的 9，没有 DLSTRINGS 文件
+    fn string_files() -> StringFileSet {
+        let mut strings = StringFile::empty("Test".to_string(), "english".to_string(), StringFileType::STRINGS);
+        strings.add_string(1, "Test Sword".to_string()).unwrap();
+        strings.add_string(2, "A sharp blade.".to_string()).unwrap();
+        strings.add_string(9, "Unused".to_string()).unwrap();
+
+        let mut set = StringFileSet::new("Test".to_string(), "english".to_string());
+        set.add_file(StringFileType::STRINGS, strings);
+        set
+    }
+
+    #[test]
+    fn test_check_strings() {
+        let plugin = localized_plugin();
+        let report = plugin.check_strings(&string_files());
+
+        assert_eq!(report.referenced_count, 3);
+        assert_eq!(report.string_count, 3);
+        assert_eq!(report.issue_count(), 3);
+
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].string_id, 3);
+        assert_eq!(report.missing[0].record_type, "MISC");
+        assert_eq!(report.missing[0].file_type, StringFileType::STRINGS);
+
+        assert_eq!(report.misplaced.len(), 1);
+        assert_eq!(report.misplaced[0].string_id, 2);
+        assert_eq!(report.misplaced[0].expected, StringFileType::DLSTRINGS);
+        assert_eq!(report.misplaced[0].found_in, StringFileType::STRINGS);
+
+        assert_eq!(report.orphans.len(), 1);
+        assert_eq!(report.orphans[0].string_id, 9);
+    }
+
+    #[test]
+    fn test_fix_strings() {
+        let plugin = localized_plugin();
+        let mut set = string_files();
+
+        let report = plugin.fix_strings(&mut set, true).unwrap();
+        assert!(report.fixed && report.orphans_deleted);
+
+        let strings = set.get_file(&StringFileType::STRINGS).unwrap();
+        assert_eq!(strings.get_string_ids(), vec![1]);
+        let dlstrings = set.get_file(&StringFileType::DLSTRINGS).unwrap();
+        assert_eq!(dlstrings.get_string(2).unwrap().content, "A sharp blade.");
+
+        // 修复后只剩缺失的字符串
+        let report = plugin.check_strings(&set);
+        assert!(report.orphans.is_empty() && report.misplaced.is_empty());
+        assert_eq!(report.missing.len(), 1);
+    }
+
+    #[test]
+    fn test_fix_strings_keeps_orphans_by_default() {
+        // FACT 名称不在字符串路由表中，其 FULL 引用的字符串会被列为孤立
+        let mut builder = PluginBuilder::new("Test.esp").localized(true);
+        let faction = builder.new_record("FACT", vec![
+            Subrecord::new("EDID", b"TestFaction\0".to_vec()),
+            Subrecord::new("FULL", string_id(9)),
+        ]);
+        builder.add_record(faction).unwrap();
+        let plugin = builder.build().unwrap();
+        let mut set = string_files();
+
+        let report = plugin.fix_strings(&mut set, false).unwrap();
+        assert!(report.fixed && !report.orphans_deleted);
+        assert!(report.orphans.iter().any(|orphan| orphan.string_id == 9));
+
+        let strings = set.get_file(&StringFileType::STRINGS).unwrap();
+        assert_eq!(strings.get_string(9).unwrap().content, "Unused");
+    }
+
+    #[test]
+    fn test_check_strings_skips_unlocalized_plugin() {
+        let mut builder = PluginBuilder::new("Test.esp");
+        let sword = builder.new_record("WEAP", vec![Subrecord::new("FULL", b"Sword\0".to_vec())]);
+        builder.add_record(sword).unwrap();
+        let plugin = builder.build().unwrap();
+
+        let report = plugin.check_strings(&string_files());
+        assert!(report.is_clean());
+        assert_eq!(report.warnings.len(), 1);
+    }
+}
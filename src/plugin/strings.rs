@@ -1,21 +1,156 @@
 use super::Plugin;
+use super::cache::{IndexedString, StringValue};
 use crate::datatypes::{read_u32, RawString};
 use crate::record::Record;
 use crate::group::{Group, GroupChild};
-use crate::string_types::ExtractedString;
+use crate::string_file::{
+    MultiLanguageStringSet, StringFileSet, StringFileType, StringSetComparison, TermBase, TermBaseOptions,
+};
+use crate::string_types::{ExtractedString, MultiLanguageString};
 use crate::utils::is_valid_string;
 use std::io::Cursor;
 use rayon::prelude::*;
 
 impl Plugin {
     /// 提取所有字符串（并行版本，性能提升 1.5-2x）
+    ///
+    /// 从索引缓存加载的插件直接使用缓存中的字符串位置，不需要解析记录。
     pub fn extract_strings(&self) -> Vec<ExtractedString> {
+        if let Some(index) = &self.index {
+            return index.strings
+                .par_iter()
+                .filter_map(|site| self.resolve_string_site(site))
+                .collect();
+        }
+
         self.groups
             .par_iter()
             .flat_map(|group| self.extract_group_strings(group))
             .collect()
     }
 
+    /// 收集所有字符串位置（按提取顺序；从索引缓存加载时直接使用缓存）
+    pub(crate) fn string_sites(&self) -> Vec<IndexedString> {
+        if let Some(index) = &self.index {
+            return index.strings.clone();
+        }
+
+        self.groups
+            .par_iter()
+            .flat_map(|group| {
+                group.get_records().into_iter()
+                    .flat_map(|record| self.record_string_sites(record))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// 按多种语言并列提取字符串（每个 StringID 引用一行，每种语言一列）
+    ///
+    /// 只处理本地化插件中引用 STRING 文件的字段；所有语言都缺失或无效的字符串不产生行。
+    pub fn extract_multilingual_strings(&self, string_sets: &MultiLanguageStringSet) -> Vec<MultiLanguageString> {
+        if !self.is_localized() {
+            eprintln!("警告: 插件 {} 未设置 LOCALIZED 标志，没有可按语言提取的字符串", self.get_name());
+            return Vec::new();
+        }
+
+        self.string_sites()
+            .into_iter()
+            .filter_map(|site| {
+                let StringValue::Localized { file_type, string_id } = site.value else {
+                    return None;
+                };
+
+                let texts: std::collections::BTreeMap<String, String> = string_sets
+                    .get_entries(file_type, string_id)
+                    .into_iter()
+                    .filter(|(_, entry)| is_valid_string(&entry.content))
+                    .map(|(language, entry)| (language.to_string(), entry.content.clone()))
+                    .collect();
+
+                if texts.is_empty() {
+                    return None;
+                }
+
+                Some(MultiLanguageString {
+                    editor_id: site.editor_id,
+                    form_id: self.format_form_id(site.form_id),
+                    record_type: site.record_type.to_string(),
+                    subrecord_type: site.subrecord_type.to_string(),
+                    index: site.index,
+                    file_type,
+                    string_id,
+                    texts,
+                })
+            })
+            .collect()
+    }
+
+    /// 将语言对比结果中仍需翻译的字符串转换为提取格式（可直接作为翻译任务 JSON 导出）
+    ///
+    /// `text` 为源语言文本；不被插件引用的 StringID 不会出现在结果中。
+    pub fn extract_pending_strings(&self, comparison: &StringSetComparison) -> Vec<ExtractedString> {
+        let pending = comparison.pending_ids();
+        let source_texts: std::collections::HashMap<(StringFileType, u32), &str> = comparison.untranslated
+            .iter()
+            .chain(&comparison.missing_in_target)
+            .filter_map(|diff| Some(((diff.file_type, diff.string_id), diff.source_text.as_deref()?)))
+            .chain(comparison.placeholder_mismatches.iter()
+                .map(|m| ((m.file_type, m.string_id), m.source_text.as_str())))
+            .collect();
+
+        self.string_sites()
+            .into_iter()
+            .filter_map(|site| {
+                let StringValue::Localized { file_type, string_id } = site.value else {
+                    return None;
+                };
+                if !pending.contains(&(file_type, string_id)) {
+                    return None;
+                }
+
+                Some(ExtractedString::new(
+                    site.editor_id,
+                    self.format_form_id(site.form_id),
+                    site.record_type.to_string(),
+                    site.subrecord_type.to_string(),
+                    source_texts.get(&(file_type, string_id))?.to_string(),
+                    site.index,
+                ))
+            })
+            .collect()
+    }
+
+    /// 按插件引用的字符串对齐两种语言，生成双语术语库
+    ///
+    /// 与 `TermBase::align` 相同，但条目带有记录类型和 EDID；
+    /// `names_only` 时只收录 STRINGS 文件中的 FULL 字段（物品、地点、法术等名称）。
+    pub fn build_term_base(
+        &self,
+        source: &StringFileSet,
+        target: &StringFileSet,
+        options: &TermBaseOptions,
+    ) -> TermBase {
+        let mut term_base = TermBase::new(source.language.clone(), target.language.clone());
+        let mut seen = std::collections::HashSet::new();
+
+        for site in self.string_sites() {
+            let StringValue::Localized { file_type, string_id } = site.value else {
+                continue;
+            };
+            if options.names_only && (file_type != StringFileType::STRINGS || site.subrecord_type != "FULL") {
+                continue;
+            }
+
+            if let Some(entry) = term_base.push_aligned(source, target, file_type, string_id, options, &mut seen) {
+                entry.record_type = Some(site.record_type.to_string());
+                entry.editor_id = site.editor_id;
+            }
+        }
+
+        term_base
+    }
+
     /// 从组中提取字符串
     fn extract_group_strings(&self, group: &Group) -> Vec<ExtractedString> {
         let mut strings = Vec::new();
@@ -36,134 +171,119 @@ impl Plugin {
     ///
     /// 所有 string subrecord 都按出现顺序分配索引（0, 1, 2...）
     fn extract_record_strings(&self, record: &Record) -> Vec<ExtractedString> {
-        let mut strings = Vec::new();
+        self.record_string_sites(record)
+            .iter()
+            .filter_map(|site| self.resolve_string_site(site))
+            .collect()
+    }
+
+    /// 收集记录中的字符串位置（本地化插件只读取 StringID，不查找 STRING 文件）
+    ///
+    /// 索引按 string subrecord 在记录中的出现顺序分配，StringID 为 0 或数据
+    /// 不完整的字段不产生位置，但同样占用一个索引。
+    pub(crate) fn record_string_sites(&self, record: &Record) -> Vec<IndexedString> {
+        let mut sites = Vec::new();
 
         // 使用字符串路由器获取支持的子记录类型（v0.6.0 - P2.3）
-        let string_types = match self.string_router().get_string_subrecord_types(&record.record_type) {
+        let string_types = match self.string_router().get_string_subrecord_types(record.record_type) {
             Some(types) => types,
-            None => return strings,
+            None => return sites,
         };
 
         let editor_id = record.get_editor_id();
-        let form_id_str = self.format_form_id(record.form_id);
 
         // 全局索引计数器：按 subrecord 在 record.subrecords 中的出现顺序
         let mut index = 0i32;
 
-        for subrecord in &record.subrecords {
-            if string_types.contains(&subrecord.record_type) {
-                if let Some(extracted) = self.extract_string_from_subrecord_with_index(
-                    subrecord, &editor_id, &form_id_str, &record.record_type, index
-                ) {
-                    strings.push(extracted);
+        for subrecord in record.subrecords() {
+            if !string_types.contains(&subrecord.record_type) {
+                continue;
+            }
+
+            let value = if self.is_localized() {
+                // 本地化插件：数据是字符串ID（前4字节）
+                let mut cursor = Cursor::new(&subrecord.data[..]);
+                match read_u32(&mut cursor) {
+                    // StringID 为 0 表示无字符串或空字段，直接跳过不处理
+                    Ok(string_id) if string_id != 0 => Some(StringValue::Localized {
+                        file_type: Self::determine_string_file_type(record.record_type, subrecord.record_type),
+                        string_id,
+                    }),
+                    _ => None,
                 }
-                index += 1; // 每个 string subrecord 递增
+            } else {
+                // 普通插件：直接解析字符串
+                Some(StringValue::Inline(RawString::parse_zstring(&subrecord.data).content))
+            };
+
+            if let Some(value) = value {
+                sites.push(IndexedString {
+                    form_id: record.form_id,
+                    editor_id: editor_id.clone(),
+                    record_type: record.record_type,
+                    subrecord_type: subrecord.record_type,
+                    index,
+                    value,
+                });
             }
+            index += 1; // 每个 string subrecord 递增
         }
 
-        strings
+        sites
     }
 
-    /// 从子记录中提取字符串（带索引支持）
-    ///
-    /// 所有字段都有 index 参数，按 Record 内的顺序分配
-    fn extract_string_from_subrecord_with_index(
-        &self,
-        subrecord: &crate::subrecord::Subrecord,
-        editor_id: &Option<String>,
-        form_id_str: &str,
-        record_type: &str,
-        index: i32,
-    ) -> Option<ExtractedString> {
-        let raw_string = if self.is_localized() {
-            // 本地化插件：数据是字符串ID（前4字节）
-            let mut cursor = Cursor::new(&subrecord.data[..]);
-            let string_id = match read_u32(&mut cursor) {
-                Ok(id) => id,
-                Err(_) => return None,
-            };
+    /// 将字符串位置解析为提取结果（无效字符串返回 `None`）
+    pub(crate) fn resolve_string_site(&self, site: &IndexedString) -> Option<ExtractedString> {
+        let text = match &site.value {
+            StringValue::Inline(text) => text.clone(),
+            StringValue::Localized { file_type, string_id } => self.lookup_string_id(*file_type, *string_id),
+        };
 
-            // StringID 为 0 表示无字符串或空字段，直接跳过不处理
-            if string_id == 0 {
-                return None;
-            }
+        if !is_valid_string(&text) {
+            return None;
+        }
 
-            // 确定应该从哪个STRING文件查找
-            let file_type = Self::determine_string_file_type(record_type, &subrecord.record_type);
-
-            // 从STRING文件查找实际文本
-            if let Some(ref string_files) = self.string_files {
-                if let Some(entry) = string_files.get_string_by_type(file_type, string_id) {
-                    // 调试模式下输出成功提取的详细信息（设置环境变量 ESP_DEBUG_STRINGS=1 启用）
-                    #[cfg(debug_assertions)]
-                    if std::env::var("ESP_DEBUG_STRINGS").is_ok() {
-                        let editor_id_str = editor_id.as_ref()
-                            .map(|s| s.as_str())
-                            .unwrap_or("<无EditorID>");
-                        eprintln!(
-                            "DEBUG: StringID {} 从 {:?} 文件提取 (来自 {}.{}, FormID: {}, EditorID: {}, 内容: \"{}\")",
-                            string_id, file_type, record_type, &subrecord.record_type, form_id_str, editor_id_str,
-                            &entry.content.chars().take(30).collect::<String>()
-                        );
-                    }
-
-                    RawString {
-                        content: entry.content.clone(),
-                        encoding: "utf-8".to_string(),
-                    }
-                } else {
-                    // STRING文件中未找到，返回占位符
-                    #[cfg(debug_assertions)]
-                    {
-                        let editor_id_str = editor_id.as_ref()
-                            .map(|s| s.as_str())
-                            .unwrap_or("<无EditorID>");
-                        eprintln!(
-                            "警告: StringID {} 在 {:?} 文件中未找到 (来自 {}.{}, FormID: {}, EditorID: {})",
-                            string_id, file_type, record_type, &subrecord.record_type, form_id_str, editor_id_str
-                        );
-                    }
-
-                    RawString {
-                        content: format!("StringID_{}_{:?}", string_id, file_type),
-                        encoding: "ascii".to_string(),
-                    }
-                }
-            } else {
-                // 没有加载STRING文件
+        // 所有字段都有索引
+        Some(ExtractedString::new(
+            site.editor_id.clone(),
+            self.format_form_id(site.form_id),
+            site.record_type.to_string(),
+            site.subrecord_type.to_string(),
+            text,
+            site.index,
+        ))
+    }
+
+    /// 从 STRING 文件查找文本，找不到时返回占位符
+    fn lookup_string_id(&self, file_type: StringFileType, string_id: u32) -> String {
+        let Some(string_files) = &self.string_files else {
+            // 没有加载STRING文件
+            #[cfg(debug_assertions)]
+            eprintln!("警告: 本地化插件但未加载STRING文件 (StringID: {})", string_id);
+
+            return format!("StringID_{}", string_id);
+        };
+
+        match string_files.get_string_by_type(file_type, string_id) {
+            Some(entry) => {
+                // 调试模式下输出成功提取的详细信息（设置环境变量 ESP_DEBUG_STRINGS=1 启用）
                 #[cfg(debug_assertions)]
-                {
-                    let editor_id_str = editor_id.as_ref()
-                        .map(|s| s.as_str())
-                        .unwrap_or("<无EditorID>");
+                if std::env::var("ESP_DEBUG_STRINGS").is_ok() {
                     eprintln!(
-                        "警告: 本地化插件但未加载STRING文件 (StringID: {}, 来自 {}.{}, FormID: {}, EditorID: {})",
-                        string_id, record_type, &subrecord.record_type, form_id_str, editor_id_str
+                        "DEBUG: StringID {} 从 {:?} 文件提取 (内容: \"{}\")",
+                        string_id, file_type, &entry.content.chars().take(30).collect::<String>()
                     );
                 }
 
-                RawString {
-                    content: format!("StringID_{}", string_id),
-                    encoding: "ascii".to_string(),
-                }
+                entry.content.clone()
             }
-        } else {
-            // 普通插件：直接解析字符串
-            RawString::parse_zstring(&subrecord.data)
-        };
+            None => {
+                // STRING文件中未找到，返回占位符
+                #[cfg(debug_assertions)]
+                eprintln!("警告: StringID {} 在 {:?} 文件中未找到", string_id, file_type);
 
-        if is_valid_string(&raw_string.content) {
-            // 所有字段都有索引
-            Some(ExtractedString::new(
-                editor_id.clone(),
-                form_id_str.to_string(),
-                record_type.to_string(),
-                subrecord.record_type.clone(),
-                raw_string.content,
-                index,
-            ))
-        } else {
-            None
+                format!("StringID_{}_{:?}", string_id, file_type)
+            }
         }
     }
 }
@@ -0,0 +1,34 @@
+//! 插件模块测试共用的辅助函数
+
+use super::{Plugin, PluginBuilder};
+use crate::group::{Group, GroupChild, GroupType};
+use crate::record::Record;
+use crate::subrecord::Subrecord;
+
+pub(crate) use crate::datatypes::zstring;
+
+/// EDID 子记录
+pub(crate) fn edid(text: &str) -> Subrecord {
+    Subrecord::new("EDID", zstring(text))
+}
+
+/// 创建带主文件列表的构建器
+pub(crate) fn builder(name: &str, masters: &[&str]) -> PluginBuilder {
+    masters.iter().fold(PluginBuilder::new(name), |builder, master| builder.master(master))
+}
+
+/// 由记录组成的顶级组
+pub(crate) fn group(label: &[u8; 4], records: Vec<Record>) -> Group {
+    let mut group = Group::new(*label, GroupType::Normal);
+    group.children.extend(records.into_iter().map(GroupChild::Record));
+    group
+}
+
+/// 构建插件并按顺序放入预先构造的组
+///
+/// 用于需要指定 FormID、覆盖记录或特殊组结构的测试；组不经过合并，保持给定顺序。
+pub(crate) fn plugin_with_groups(name: &str, masters: &[&str], groups: Vec<Group>) -> Plugin {
+    let mut plugin = builder(name, masters).build().unwrap();
+    plugin.groups = groups;
+    plugin
+}
@@ -2,7 +2,7 @@ use super::Plugin;
 use crate::datatypes::read_u32;
 use crate::record::Record;
 use crate::group::{Group, GroupChild};
-use crate::string_types::ExtractedString;
+use crate::string_types::{unique_key, ExtractedString};
 use crate::string_file::StringFileType;
 use crate::string_routes::StringRouter;
 use std::collections::HashMap;
@@ -160,12 +160,12 @@ impl Plugin {
         let form_id_str = self.format_form_id(record.form_id);
 
         // 获取支持的字符串子记录类型（v0.6.0 - P2.3）
-        let valid_subrecord_types = self.string_router().get_string_subrecord_types(&record.record_type);
+        let valid_subrecord_types = self.string_router().get_string_subrecord_types(record.record_type);
 
         // 全局索引计数器（与提取/应用逻辑完全一致）
         let mut index = 0i32;
 
-        for subrecord in &record.subrecords {
+        for subrecord in record.subrecords() {
             if let Some(types) = valid_subrecord_types {
                 if types.contains(&subrecord.record_type) {
                     // 读取StringID
@@ -173,18 +173,17 @@ impl Plugin {
                     if let Ok(string_id) = read_u32(&mut cursor) {
                         // 确定文件类型
                         let file_type = Self::determine_string_file_type(
-                            &record.record_type,
-                            &subrecord.record_type,
+                            record.record_type,
+                            subrecord.record_type,
                         );
 
                         // 构建唯一键（所有字段都包含索引）
-                        let key = format!(
-                            "{}|{}|{} {}|{}",
-                            editor_id.as_deref().unwrap_or(""),
-                            form_id_str,
+                        let key = unique_key(
+                            editor_id.as_deref(),
+                            &form_id_str,
                             record.record_type,
                             subrecord.record_type,
-                            index
+                            index,
                         );
 
                         map.insert(key, (file_type, string_id));
@@ -281,7 +280,7 @@ impl Plugin {
 }
 
 /// 对组应用翻译
-fn apply_translations_to_group(
+pub(super) fn apply_translations_to_group(
     group: &mut Group,
     translations: &HashMap<String, ExtractedString>,
     string_router: &dyn StringRouter,
@@ -313,7 +312,7 @@ fn apply_translations_to_record(
     plugin_name: &str,
 ) -> Result<usize, Box<dyn std::error::Error>> {
     // 使用字符串路由器获取支持的子记录类型（v0.6.0 - P2.3）
-    let string_types = match string_router.get_string_subrecord_types(&record.record_type) {
+    let string_types = match string_router.get_string_subrecord_types(record.record_type) {
         Some(types) => types,
         None => return Ok(0),
     };
@@ -327,15 +326,16 @@ fn apply_translations_to_record(
     // 全局索引计数器（与提取逻辑完全一致）
     let mut index = 0i32;
 
-    for subrecord in &mut record.subrecords {
+    let record_type = record.record_type;
+    for subrecord in record.subrecords_mut() {
         if string_types.contains(&subrecord.record_type) {
-            let string_type = format!("{} {}", record.record_type, subrecord.record_type);
             // 构建带索引的 key（所有字段都包含 index）
-            let key = format!("{}|{}|{}|{}",
-                editor_id.as_deref().unwrap_or(""),
-                form_id_str,
-                string_type,
-                index
+            let key = unique_key(
+                editor_id.as_deref(),
+                &form_id_str,
+                record_type,
+                subrecord.record_type,
+                index,
             );
 
             #[cfg(debug_assertions)]
@@ -358,9 +358,11 @@ fn apply_translations_to_record(
                     );
 
                     let encoded_data = encode_string_with_encoding(text_to_apply, "utf-8")?;
-                    subrecord.data = encoded_data;
-                    subrecord.size = subrecord.data.len() as u16;
-                    modified = true;
+                    // 文本未变化时不标记修改，保留原始数据
+                    if subrecord.data != encoded_data {
+                        subrecord.set_data(encoded_data);
+                        modified = true;
+                    }
                     applied_count += 1;
                 }
             }
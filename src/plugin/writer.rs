@@ -1,127 +1,702 @@
 use super::Plugin;
+use crate::compression::{CompressionPolicy, CompressionSettings};
+use crate::datatypes::{RecordFlags, Signature, SourceSpan};
 use crate::record::Record;
 use crate::group::{Group, GroupChild};
-use std::path::PathBuf;
+use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// 写入选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// 记录压缩策略
+    pub compression: CompressionPolicy,
+}
+
+/// 记录的压缩处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CompressionAction {
+    /// 原样写出原始压缩数据
+    Kept,
+    /// 修改过的压缩记录，按原始 zlib 头部推断的参数重新压缩
+    Recompressed,
+    /// 原本未压缩，按策略压缩并设置压缩标志
+    Compressed,
+    /// 原本压缩，按策略解压写出并清除压缩标志
+    Decompressed,
+}
+
+/// 单个记录的压缩处理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CompressionDecision {
+    /// 记录 FormID
+    pub form_id: u32,
+    /// 记录类型
+    pub record_type: Signature,
+    /// 处理方式
+    pub action: CompressionAction,
+    /// 使用的压缩参数（重新压缩/新压缩时）
+    pub settings: Option<CompressionSettings>,
+    /// 解压后的数据大小
+    pub uncompressed_size: usize,
+    /// 写入的数据大小
+    pub written_size: usize,
+}
+
+/// 写入报告
+///
+/// 列出写入前或写入后为压缩记录的每个记录的处理方式，前后均未压缩的记录不列出。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WriteReport {
+    /// 各记录的压缩处理结果（按写入顺序）
+    pub records: Vec<CompressionDecision>,
+    /// 直接从源文件内存映射复制的字节数（未修改的组）
+    pub copied_bytes: u64,
+}
+
+impl WriteReport {
+    /// 统计某种处理方式的记录数量
+    pub fn count(&self, action: CompressionAction) -> usize {
+        self.records.iter().filter(|decision| decision.action == action).count()
+    }
+}
+
+/// 准备写出的记录数据
+struct PreparedRecord<'a> {
+    /// 写出的标志位（压缩标志按实际数据设置）
+    flags: u32,
+    /// 写出的数据部分
+    data: Cow<'a, [u8]>,
+    /// 压缩处理结果（压缩状态无关的记录为 `None`）
+    decision: Option<CompressionDecision>,
+}
+
+/// 第一遍遍历中缓存的压缩数据总量上限（字节）
+const COMPRESSED_CACHE_LIMIT: usize = 16 * 1024 * 1024;
+
+/// 流式写入的布局信息（第一遍遍历生成）
+///
+/// 组布局按先序遍历顺序保存（从源文件复制的组不包含其子组）。第一遍只记录大小：
+/// 重新序列化的未压缩数据在第二遍写出时重新生成；新压缩的数据按遍历序号缓存，
+/// 总量不超过 `cache_limit`，超出部分在第二遍重新压缩。
+#[derive(Default)]
+struct WriteLayout<'a> {
+    groups: Vec<GroupLayout>,
+    compressed_records: HashMap<usize, PreparedRecord<'a>>,
+    /// 已缓存的压缩数据字节数
+    cached_bytes: usize,
+    /// 缓存上限
+    cache_limit: usize,
+    /// 可复制的源文件（源标识，内存映射数据）
+    source: Option<(u64, &'a [u8])>,
+}
+
+/// 单个组的布局
+struct GroupLayout {
+    /// 组大小（含 24 字节组头部）
+    size: u32,
+    /// 未修改时在源文件中的字节范围（整组原样复制）
+    source_range: Option<Range<usize>>,
+}
+
+impl WriteLayout<'_> {
+    /// 返回源文件中与给定头部一致的字节范围（来自其他文件或头部已改变时为 `None`）
+    fn source_range(&self, span: Option<SourceSpan>, header: &[u8; 24]) -> Option<Range<usize>> {
+        let (source_id, bytes) = self.source?;
+        let span = span.filter(|span| span.source_id == source_id)?;
+        let range = span.offset as usize..(span.offset + span.len) as usize;
+        (range.end <= bytes.len() && bytes[range.start..].starts_with(header)).then_some(range)
+    }
+}
+
+/// 第二遍遍历的位置
+#[derive(Default)]
+struct LayoutCursor {
+    group: usize,
+    record: usize,
+}
 
 impl Plugin {
     /// 写入文件
     pub fn write_to_file(&self, path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        let mut output = Vec::new();
+        self.write_to_file_with(path, &WriteOptions::default())?;
+        Ok(())
+    }
+
+    /// 按写入选项写入文件
+    ///
+    /// 数据通过缓冲流写入同目录下的临时文件，完成后替换目标文件，
+    /// 因此可以直接覆盖插件自身的源文件。
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let options = WriteOptions { compression: CompressionPolicy::Never };
+    /// let report = plugin.write_to_file_with("MyMod.esp".into(), &options)?;
+    /// println!("解压 {} 个记录", report.count(CompressionAction::Decompressed));
+    /// ```
+    pub fn write_to_file_with(
+        &self,
+        path: PathBuf,
+        options: &WriteOptions,
+    ) -> Result<WriteReport, Box<dyn std::error::Error>> {
+        let mut report = WriteReport::default();
+        crate::io::esp_io::write_file_atomically(&path, &mut |output| {
+            report = self.write_to(output, options)?;
+            Ok(())
+        })?;
+        Ok(report)
+    }
+
+    /// 流式写入到任意输出流（文件、网络连接、内存缓冲区等）
+    ///
+    /// 先遍历一遍计算所有 GRUP 的大小，再按顺序写出，输出流无需支持 `Seek`。
+    /// 内存占用仅包括组大小表和有上限的压缩数据缓存，重新序列化的记录在写出时逐个生成。
+    ///
+    /// 通过 `Plugin::load()` 加载且使用 `CompressionPolicy::Preserve` 时，
+    /// 没有任何修改的组（记录未修改、子元素顺序和组头部与源文件一致）直接从内存映射复制，
+    /// 只有包含修改的组被重新序列化，输出与完整序列化逐字节相同。
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let stream = TcpStream::connect("127.0.0.1:9000")?;
+    /// plugin.write_to(&mut BufWriter::new(stream), &WriteOptions::default())?;
+    /// ```
+    pub fn write_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        options: &WriteOptions,
+    ) -> Result<WriteReport, Box<dyn std::error::Error>> {
+        self.write_with_cache_limit(writer, options, COMPRESSED_CACHE_LIMIT)
+    }
+
+    /// 按指定的压缩数据缓存上限流式写入
+    fn write_with_cache_limit<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        options: &WriteOptions,
+        cache_limit: usize,
+    ) -> Result<WriteReport, Box<dyn std::error::Error>> {
+        if self.partial {
+            return Err("插件加载时按解析选项跳过了部分组或记录，不能写入文件".into());
+        }
 
-        self.write_record(&self.header, &mut output)?;
+        // 记录增删后 HEDR 中的记录数会过期，写入时同步
+        let synced_header = self.header_with_synced_count();
+        let header = synced_header.as_ref().unwrap_or(&self.header);
+        let prepared_header = Self::prepare_record(header, options);
 
+        // 第一遍：计算组大小
+        let (mut layout, report) = self.measure(options, cache_limit);
+
+        // 第二遍：按顺序写出
+        Self::write_prepared_record(header, &prepared_header, writer)?;
+        let mut cursor = LayoutCursor::default();
         for group in &self.groups {
-            self.write_group(group, &mut output)?;
+            Self::write_group(group, options, &mut layout, &mut cursor, writer)?;
         }
 
-        std::fs::write(path, output)?;
-        Ok(())
+        Ok(report)
     }
 
-    /// 写入记录
-    pub(crate) fn write_record(&self, record: &Record, output: &mut Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
-        use crate::datatypes::RecordFlags;
+    /// 第一遍：计算所有组的布局，生成写入报告
+    fn measure(&self, options: &WriteOptions, cache_limit: usize) -> (WriteLayout<'_>, WriteReport) {
+        let mut report = WriteReport::default();
+        let mut layout = WriteLayout {
+            source: self.copyable_source(options),
+            cache_limit,
+            ..WriteLayout::default()
+        };
+        let mut record_index = 0;
+        for group in &self.groups {
+            Self::measure_group(group, options, &mut layout, &mut record_index, &mut report);
+        }
+        report.copied_bytes = layout.groups.iter()
+            .filter_map(|group| group.source_range.as_ref())
+            .map(|range| range.len() as u64)
+            .sum();
+        (layout, report)
+    }
 
-        // 写入记录类型
-        output.extend_from_slice(&record.record_type_bytes);
+    /// 返回写入时可直接复制的源文件数据
+    ///
+    /// 只有保持原压缩状态时，未修改记录的输出才与源文件一致。
+    fn copyable_source(&self, options: &WriteOptions) -> Option<(u64, &[u8])> {
+        if options.compression != CompressionPolicy::Preserve {
+            return None;
+        }
+        Some((self.source_id?, &self.mmap.as_ref()?[..]))
+    }
 
+    /// 决定记录的写出形式（压缩/解压/原样）
+    fn prepare_record<'a>(record: &'a Record, options: &WriteOptions) -> PreparedRecord<'a> {
         // 判断记录是否原本就是压缩的
         let is_originally_compressed = record.flags & RecordFlags::COMPRESSED.bits() != 0;
 
-        // 处理数据部分（使用 Cow 避免不必要的克隆，性能优化 ~500-800ms）
-        let data_to_write: Cow<[u8]> = if record.is_modified {
-            // 如果记录被修改，重新序列化子记录（需要新分配）
-            let mut subrecord_data = Vec::new();
-            for subrecord in &record.subrecords {
-                subrecord_data.extend_from_slice(&subrecord.record_type_bytes);
-                subrecord_data.extend_from_slice(&(subrecord.data.len() as u16).to_le_bytes());
-                subrecord_data.extend_from_slice(&subrecord.data);
+        // 保持原状的未修改压缩记录：直接使用原始压缩数据，延迟解析的记录无需解压
+        match (options.compression, &record.original_compressed_data) {
+            (CompressionPolicy::Preserve, Some(compressed_data)) if is_originally_compressed && !record.is_modified => {
+                let uncompressed_size = match compressed_data.get(..4) {
+                    Some(size) if !record.is_parsed() => u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize,
+                    _ => record.data().len(),
+                };
+                return PreparedRecord {
+                    flags: record.flags,
+                    data: Cow::Borrowed(compressed_data.as_slice()),
+                    decision: Some(CompressionDecision {
+                        form_id: record.form_id,
+                        record_type: record.record_type,
+                        action: CompressionAction::Kept,
+                        settings: None,
+                        uncompressed_size,
+                        written_size: compressed_data.len(),
+                    }),
+                };
             }
+            _ => {}
+        }
 
-            // 如果原本是压缩的，重新压缩
-            if is_originally_compressed {
-                let compressed = record.recompress_data()?;
-                #[cfg(debug_assertions)]
-                println!("🔄 重新压缩记录 {}: 解压大小 {} -> 压缩大小 {}",
-                    record.record_type, subrecord_data.len(), compressed.len());
-                Cow::Owned(compressed)
-            } else {
-                #[cfg(debug_assertions)]
-                println!("📝 修改非压缩记录 {}: 大小 {}", record.record_type, subrecord_data.len());
-                Cow::Owned(subrecord_data)
-            }
+        // 解压失败的记录没有子记录，只能原样写出
+        let is_unreadable = is_originally_compressed
+            && record.subrecords().is_empty()
+            && record.original_compressed_data.as_deref() == Some(record.data());
+
+        // 解压后的数据（使用 Cow 避免不必要的克隆，性能优化 ~500-800ms）
+        let uncompressed: Cow<[u8]> = if record.is_modified {
+            Cow::Owned(Record::serialize_subrecords(record.subrecords()))
         } else {
-            // 使用原始数据（零拷贝借用，避免 35MB 内存拷贝）
-            if let Some(compressed_data) = &record.original_compressed_data {
-                #[cfg(debug_assertions)]
-                println!("📦 保持压缩记录 {}: 原始压缩大小 {} (原始data_size: {})",
-                    record.record_type, compressed_data.len(), record.data_size);
+            Cow::Borrowed(record.data())
+        };
+
+        let should_compress = match options.compression {
+            _ if is_unreadable => true,
+            _ if record.record_type == "TES4" => false,
+            CompressionPolicy::Preserve => is_originally_compressed,
+            CompressionPolicy::Always { threshold } => uncompressed.len() > threshold,
+            CompressionPolicy::Never => false,
+        };
+
+        let mut decision = CompressionDecision {
+            form_id: record.form_id,
+            record_type: record.record_type,
+            action: CompressionAction::Kept,
+            settings: None,
+            uncompressed_size: uncompressed.len(),
+            written_size: 0,
+        };
+
+        // 处理数据部分
+        let data: Cow<[u8]> = match (should_compress, &record.original_compressed_data) {
+            // 未修改的压缩记录：使用原始压缩数据（零拷贝借用，避免 35MB 内存拷贝）
+            (true, Some(compressed_data)) if !record.is_modified || is_unreadable => {
                 Cow::Borrowed(compressed_data.as_slice())
-            } else {
+            }
+            (true, original) => {
+                // 只读取 zlib 头部，不做逐字节比较的参数检测
+                let settings = original.as_ref()
+                    .and_then(|data| crate::compression::header_settings(data.get(4..)?))
+                    .unwrap_or_default();
+                decision.action = if original.is_some() {
+                    CompressionAction::Recompressed
+                } else {
+                    CompressionAction::Compressed
+                };
+                decision.settings = Some(settings);
+
+                let mut data = Vec::with_capacity(uncompressed.len() / 2 + 4);
+                data.extend_from_slice(&(uncompressed.len() as u32).to_le_bytes());
+                data.extend_from_slice(&crate::compression::compress(&uncompressed, settings));
+
                 #[cfg(debug_assertions)]
-                println!("📄 保持未压缩记录 {}: 大小 {} (原始data_size: {})",
-                    record.record_type, record.raw_data.len(), record.data_size);
-                Cow::Borrowed(&record.raw_data)
+                println!("🔄 重新压缩记录 {}: 解压大小 {} -> 压缩大小 {} (级别 {}, {:?})",
+                    record.record_type, uncompressed.len(), data.len(), settings.level, settings.strategy);
+                Cow::Owned(data)
+            }
+            (false, _) => {
+                if is_originally_compressed {
+                    decision.action = CompressionAction::Decompressed;
+                }
+                uncompressed
             }
         };
 
-        // 写入数据大小
-        let actual_size = data_to_write.len() as u32;
-        output.extend_from_slice(&actual_size.to_le_bytes());
+        let flags = if should_compress {
+            record.flags | RecordFlags::COMPRESSED.bits()
+        } else {
+            record.flags & !RecordFlags::COMPRESSED.bits()
+        };
 
-        #[cfg(debug_assertions)]
-        if actual_size != record.data_size && !record.is_modified {
-            println!("⚠️  记录 {} 大小不匹配: 写入 {} vs 原始 {}",
-                record.record_type, actual_size, record.data_size);
-        }
+        let decision = (should_compress || is_originally_compressed).then(|| {
+            decision.written_size = data.len();
+            decision
+        });
+
+        PreparedRecord { flags, data, decision }
+    }
 
-        // 写入其他头部字段
-        output.extend_from_slice(&record.flags.to_le_bytes());
-        output.extend_from_slice(&record.form_id.to_le_bytes());
-        output.extend_from_slice(&record.timestamp.to_le_bytes());
-        output.extend_from_slice(&record.version_control_info.to_le_bytes());
-        output.extend_from_slice(&record.internal_version.to_le_bytes());
-        output.extend_from_slice(&record.unknown.to_le_bytes());
+    /// 第一遍：计算组大小（含 24 字节组头部），判断组能否从源文件复制
+    fn measure_group<'a>(
+        group: &'a Group,
+        options: &WriteOptions,
+        layout: &mut WriteLayout<'a>,
+        record_index: &mut usize,
+        report: &mut WriteReport,
+    ) -> u32 {
+        let slot = layout.groups.len();
+        layout.groups.push(GroupLayout { size: 0, source_range: None });
+        let first_record = *record_index;
 
-        // 写入数据部分
-        output.extend_from_slice(&data_to_write);
+        // 组头部与源文件一致时，逐个检查子元素是否未修改且在源文件中紧密相连
+        let source_range = layout.source_range(group.source_span, &group_header(group, group.size));
+        let mut next_offset = source_range.as_ref().map(|range| range.start + 24);
 
+        let mut size = 24u32;
+        for child in &group.children {
+            let child_range = match child {
+                GroupChild::Group(subgroup) => {
+                    let child_slot = layout.groups.len();
+                    size += Self::measure_group(subgroup, options, layout, record_index, report);
+                    layout.groups[child_slot].source_range.clone()
+                }
+                GroupChild::Record(record) => {
+                    let mut prepared = Self::prepare_record(record, options);
+                    size += 24 + prepared.data.len() as u32;
+                    if let Some(decision) = prepared.decision.take() {
+                        report.records.push(decision);
+                    }
+                    // 只缓存新压缩的数据（压缩代价高）；未压缩的数据在第二遍重新序列化
+                    let is_compressed = prepared.flags & RecordFlags::COMPRESSED.bits() != 0;
+                    if is_compressed
+                        && matches!(prepared.data, Cow::Owned(_))
+                        && layout.cached_bytes + prepared.data.len() <= layout.cache_limit
+                    {
+                        layout.cached_bytes += prepared.data.len();
+                        layout.compressed_records.insert(*record_index, prepared);
+                    }
+                    *record_index += 1;
+
+                    match next_offset {
+                        Some(_) if !record.is_modified => layout.source_range(
+                            record.source_span,
+                            &record_header(record, record.flags, record.data_size),
+                        ),
+                        _ => None,
+                    }
+                }
+            };
+
+            next_offset = match (next_offset, child_range) {
+                (Some(expected), Some(range)) if range.start == expected => Some(range.end),
+                _ => None,
+            };
+        }
+
+        match source_range {
+            Some(range) if next_offset == Some(range.end) => {
+                // 整组原样复制：第二遍不再进入子元素，丢弃其布局
+                layout.groups.truncate(slot + 1);
+                *record_index = first_record;
+                layout.groups[slot] = GroupLayout { size: range.len() as u32, source_range: Some(range) };
+            }
+            _ => layout.groups[slot].size = size,
+        }
+        layout.groups[slot].size
+    }
+
+    /// 写出记录头部和数据
+    fn write_prepared_record<W: Write + ?Sized>(
+        record: &Record,
+        prepared: &PreparedRecord,
+        writer: &mut W,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        writer.write_all(&record_header(record, prepared.flags, prepared.data.len() as u32))?;
+        writer.write_all(&prepared.data)?;
         Ok(())
     }
 
-    /// 写入组
-    pub(crate) fn write_group(&self, group: &Group, output: &mut Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
-        // 写入组头部
-        output.extend_from_slice(b"GRUP");
+    /// 第二遍：写出组（大小取自第一遍的布局信息）
+    fn write_group<'a, W: Write + ?Sized>(
+        group: &'a Group,
+        options: &WriteOptions,
+        layout: &mut WriteLayout<'a>,
+        cursor: &mut LayoutCursor,
+        writer: &mut W,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let group_layout = &layout.groups[cursor.group];
+        cursor.group += 1;
 
-        // 临时占位符，稍后计算实际大小
-        let size_pos = output.len();
-        output.extend_from_slice(&[0u8; 4]);
+        // 未修改的组直接从源文件复制
+        if let (Some(range), Some((_, source))) = (&group_layout.source_range, layout.source) {
+            writer.write_all(&source[range.clone()])?;
+            return Ok(());
+        }
 
-        output.extend_from_slice(&group.label);
-        output.extend_from_slice(&group.group_type.to_i32().to_le_bytes());
-        output.extend_from_slice(&group.timestamp.to_le_bytes());
-        output.extend_from_slice(&group.version_control_info.to_le_bytes());
-        output.extend_from_slice(&group.unknown.to_le_bytes());
+        // 写入组头部
+        writer.write_all(&group_header(group, group_layout.size))?;
 
         // 写入子元素
         for child in &group.children {
             match child {
                 GroupChild::Group(subgroup) => {
-                    self.write_group(subgroup, output)?;
+                    Self::write_group(subgroup, options, layout, cursor, writer)?;
                 }
                 GroupChild::Record(record) => {
-                    self.write_record(record, output)?;
+                    // 缓存的数据写出后即释放
+                    let prepared = layout.compressed_records.remove(&cursor.record)
+                        .unwrap_or_else(|| Self::prepare_record(record, options));
+                    Self::write_prepared_record(record, &prepared, writer)?;
+                    cursor.record += 1;
                 }
             }
         }
 
-        // 计算并写入实际大小（需要包含"GRUP"的4字节）
-        let actual_size = (output.len() - size_pos + 4) as u32;
-        let size_bytes = actual_size.to_le_bytes();
-        output[size_pos..size_pos + 4].copy_from_slice(&size_bytes);
-
         Ok(())
     }
 }
+
+/// 生成记录头部（24 字节）
+fn record_header(record: &Record, flags: u32, data_size: u32) -> [u8; 24] {
+    let mut header = [0u8; 24];
+    header[0..4].copy_from_slice(record.record_type.as_bytes());
+    header[4..8].copy_from_slice(&data_size.to_le_bytes());
+    header[8..12].copy_from_slice(&flags.to_le_bytes());
+    header[12..16].copy_from_slice(&record.form_id.to_le_bytes());
+    header[16..18].copy_from_slice(&record.timestamp.to_le_bytes());
+    header[18..20].copy_from_slice(&record.version_control_info.to_le_bytes());
+    header[20..22].copy_from_slice(&record.internal_version.to_le_bytes());
+    header[22..24].copy_from_slice(&record.unknown.to_le_bytes());
+    header
+}
+
+/// 生成组头部（24 字节）
+fn group_header(group: &Group, size: u32) -> [u8; 24] {
+    let mut header = [0u8; 24];
+    header[0..4].copy_from_slice(b"GRUP");
+    header[4..8].copy_from_slice(&size.to_le_bytes());
+    header[8..12].copy_from_slice(group.label.as_bytes());
+    header[12..16].copy_from_slice(&group.group_type.to_i32().to_le_bytes());
+    header[16..18].copy_from_slice(&group.timestamp.to_le_bytes());
+    header[18..20].copy_from_slice(&group.version_control_info.to_le_bytes());
+    header[20..24].copy_from_slice(&group.unknown.to_le_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::CompressionStrategy;
+    use crate::group::GroupType;
+    use crate::subrecord::Subrecord;
+    use crate::plugin::test_support::{builder, edid};
+
+    /// NPC_ 0x800（DATA 4000 字节）和 0x801（DATA 16 字节）
+    fn create_test_plugin() -> Plugin {
+        let mut builder = builder("Test.esp", &[]);
+        for size in [4000, 16] {
+            let npc = builder.new_record("NPC_", vec![
+                edid("Npc"),
+                Subrecord::new("DATA", (0..size).map(|i| (i % 7) as u8).collect()),
+            ]);
+            builder.add_record(npc).unwrap();
+        }
+        builder.build().unwrap()
+    }
+
+    fn write_and_reload(plugin: &Plugin, options: &WriteOptions) -> (Plugin, WriteReport) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        let report = plugin.write_to_file_with(path.clone(), options).unwrap();
+        (Plugin::load(path).unwrap(), report)
+    }
+
+    fn is_compressed(record: &Record) -> bool {
+        record.flags & RecordFlags::COMPRESSED.bits() != 0
+    }
+
+    #[test]
+    fn test_compression_policies() {
+        let plugin = create_test_plugin();
+
+        // Always：只压缩超过阈值的记录
+        let always = WriteOptions { compression: CompressionPolicy::Always { threshold: 1024 } };
+        let (compressed, report) = write_and_reload(&plugin, &always);
+        assert_eq!(report.count(CompressionAction::Compressed), 1);
+        let records = compressed.groups[0].get_records();
+        assert!(is_compressed(records[0]));
+        assert!(!is_compressed(records[1]));
+        assert_eq!(records[0].subrecords()[1].data.len(), 4000);
+
+        // Preserve：未修改的压缩记录原样写出
+        let (_, report) = write_and_reload(&compressed, &WriteOptions::default());
+        assert_eq!(report.count(CompressionAction::Kept), 1);
+        assert_eq!(report.records.len(), 1);
+
+        // Never：解压所有记录并清除压缩标志
+        let never = WriteOptions { compression: CompressionPolicy::Never };
+        let (decompressed, report) = write_and_reload(&compressed, &never);
+        assert_eq!(report.count(CompressionAction::Decompressed), 1);
+        assert!(decompressed.groups[0].get_records().iter().all(|r| !is_compressed(r)));
+        assert_eq!(decompressed.groups[0].get_records()[0].subrecords()[1].data.len(), 4000);
+    }
+
+    #[test]
+    fn test_preserve_reuses_original_settings() {
+        let mut plugin = create_test_plugin();
+        let always = WriteOptions { compression: CompressionPolicy::Always { threshold: 1024 } };
+        let (mut compressed, _) = write_and_reload(&plugin, &always);
+
+        // 本库写出的数据可以由显式检测精确复现
+        let record = compressed.groups[0].find_record_mut(0x00000800).unwrap();
+        assert!(record.detect_compression().unwrap().is_exact());
+        record.subrecords_mut()[0].set_data(b"Renamed\0".to_vec());
+        record.mark_modified();
+
+        let (reloaded, report) = write_and_reload(&compressed, &WriteOptions::default());
+        let decision = &report.records[0];
+        assert_eq!(decision.action, CompressionAction::Recompressed);
+        assert_eq!(decision.settings.unwrap().strategy, CompressionStrategy::Default);
+        assert_eq!(decision.settings.unwrap().level, 6);
+
+        let record = reloaded.groups[0].get_records()[0];
+        assert!(is_compressed(record));
+        assert_eq!(record.get_editor_id().as_deref(), Some("Renamed"));
+
+        // 头部记录不参与压缩
+        plugin.header.mark_modified();
+        let (_, report) = write_and_reload(&plugin, &WriteOptions { compression: CompressionPolicy::Always { threshold: 0 } });
+        assert!(report.records.iter().all(|decision| decision.record_type != "TES4"));
+    }
+
+    #[test]
+    fn test_streaming_write_matches_file_output() {
+        let mut plugin = create_test_plugin();
+        let mut cells = Group::new(*b"CELL", GroupType::Normal);
+        let mut block = Group::new([0; 4], GroupType::InteriorCellBlock);
+        let mut sub_block = Group::new([0; 4], GroupType::InteriorCellSubBlock);
+        sub_block.children.push(GroupChild::Record(Record::new("CELL", 0x00000900, 0, vec![])));
+        block.children.push(GroupChild::Group(Box::new(sub_block)));
+        cells.children.push(GroupChild::Group(Box::new(block)));
+        plugin.groups.push(cells);
+
+        // 写入不支持 Seek 的输出流
+        struct Sink(Vec<u8>);
+        impl Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let always = WriteOptions { compression: CompressionPolicy::Always { threshold: 1024 } };
+        let mut sink = Sink(Vec::new());
+        plugin.write_to(&mut sink, &always).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        plugin.write_to_file_with(path.clone(), &always).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), sink.0);
+
+        // 嵌套组大小正确：CELL 顶级组 = 3 个组头部 + 1 个空记录
+        let reloaded = Plugin::load(path).unwrap();
+        assert_eq!(reloaded.groups.len(), 2);
+        assert_eq!(reloaded.groups[1].size, 24 * 4);
+        assert_eq!(reloaded.count_records_and_groups(), plugin.count_records_and_groups());
+    }
+
+    /// 包含 `count` 个已修改记录（每个约 4KB）的插件
+    fn create_large_plugin(count: u32) -> Plugin {
+        let mut builder = builder("Test.esp", &[]);
+        for i in 0..count {
+            let npc = builder.new_record("NPC_", vec![
+                Subrecord::new("DATA", (0..4096u32).map(|b| ((b + i) % 13) as u8).collect()),
+            ]);
+            builder.add_record(npc).unwrap();
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_large_write_does_not_buffer_records() {
+        let plugin = create_large_plugin(2000);
+
+        // Never：第一遍只记录大小，不保留任何重新序列化的数据
+        let never = WriteOptions { compression: CompressionPolicy::Never };
+        let (layout, _) = plugin.measure(&never, COMPRESSED_CACHE_LIMIT);
+        assert!(layout.compressed_records.is_empty());
+        assert_eq!(layout.cached_bytes, 0);
+        assert_eq!(layout.groups[0].size as usize, 24 + 2000 * (24 + 6 + 4096));
+
+        let (reloaded, _) = write_and_reload(&plugin, &never);
+        assert_eq!(reloaded.groups[0].get_records().len(), 2000);
+        let (written, original) = (reloaded.groups[0].get_records()[1999], plugin.groups[0].get_records()[1999]);
+        assert_eq!(written.subrecords()[0].data, original.subrecords()[0].data);
+
+        // Always：压缩数据缓存不超过上限，超出部分在第二遍重新压缩，输出不变
+        let always = WriteOptions { compression: CompressionPolicy::Always { threshold: 0 } };
+        let limit = 16 * 1024;
+        let (layout, _) = plugin.measure(&always, limit);
+        assert!(layout.cached_bytes <= limit);
+        assert!(layout.compressed_records.len() < 2000);
+
+        let mut bounded = Vec::new();
+        plugin.write_with_cache_limit(&mut bounded, &always, limit).unwrap();
+        let mut unbounded = Vec::new();
+        plugin.write_with_cache_limit(&mut unbounded, &always, usize::MAX).unwrap();
+        assert_eq!(bounded, unbounded);
+    }
+
+    #[test]
+    fn test_unmodified_groups_copied_from_source() {
+        let mut plugin = create_test_plugin();
+        let mut cells = Group::new(*b"CELL", GroupType::Normal);
+        let mut block = Group::new([0; 4], GroupType::InteriorCellBlock);
+        block.children.push(GroupChild::Record(Record::new("CELL", 0x00000900, 0, vec![])));
+        cells.children.push(GroupChild::Group(Box::new(block)));
+        plugin.groups.push(cells);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        let always = WriteOptions { compression: CompressionPolicy::Always { threshold: 1024 } };
+        plugin.write_to_file_with(path.clone(), &always).unwrap();
+
+        let mut loaded = Plugin::load(path.clone()).unwrap();
+        let record = loaded.groups[0].find_record_mut(0x00000801).unwrap();
+        record.subrecords_mut()[0].set_data(b"Renamed\0".to_vec());
+        record.mark_modified();
+
+        // 与不使用源文件的完整序列化逐字节相同
+        let full = |plugin: &Plugin| {
+            let copy = Plugin::from_parts(path.clone(), plugin.header.clone(), plugin.groups.clone()).unwrap();
+            let mut output = Vec::new();
+            let report = copy.write_to(&mut output, &WriteOptions::default()).unwrap();
+            assert_eq!(report.copied_bytes, 0);
+            output
+        };
+        let mut output = Vec::new();
+        let report = loaded.write_to(&mut output, &WriteOptions::default()).unwrap();
+        assert_eq!(report.copied_bytes, 24 * 3);
+        assert_eq!(report.count(CompressionAction::Kept), 1);
+        assert_eq!(output, full(&loaded));
+
+        // 子元素被移除的组不能复制
+        if let GroupChild::Group(block) = &mut loaded.groups[1].children[0] {
+            block.children.clear();
+        }
+        let mut output = Vec::new();
+        let report = loaded.write_to(&mut output, &WriteOptions::default()).unwrap();
+        assert_eq!(report.copied_bytes, 0);
+        assert_eq!(output, full(&loaded));
+
+        // 覆盖保存到源文件本身
+        loaded.write_to_file(path.clone()).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), output);
+        let reloaded = Plugin::load(path).unwrap();
+        let record = reloaded.groups[0].get_records()[1];
+        assert_eq!(record.get_editor_id().as_deref(), Some("Renamed"));
+    }
+}
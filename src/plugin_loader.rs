@@ -2,7 +2,7 @@
 ///
 /// 提供自动检测和加载的便捷 API，同时保持底层 API 的灵活性。
 use std::path::PathBuf;
-use crate::{Plugin, LocalizedPluginContext};
+use crate::{Plugin, LocalizedPluginContext, ParseOptions};
 
 /// 插件加载结果
 ///
@@ -48,9 +48,25 @@ impl LoadedPlugin {
     pub fn load_auto(
         path: PathBuf,
         language: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_auto_with_options(path, language, &ParseOptions::default())
+    }
+
+    /// 按解析选项智能加载插件（见 `Plugin::load_with_options`）
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let options = ParseOptions::strings_only()?;
+    /// let loaded = LoadedPlugin::load_auto_with_options("Skyrim.esm".into(), Some("english"), &options)?;
+    /// let strings = loaded.extract_strings();
+    /// ```
+    pub fn load_auto_with_options(
+        path: PathBuf,
+        language: Option<&str>,
+        options: &ParseOptions,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         // ⚡ 性能优化：只加载一次 ESP 文件
-        let plugin = Plugin::load(path.clone())?;
+        let plugin = Plugin::load_with_options(path.clone(), options)?;
 
         // 检查是否为本地化插件
         if plugin.is_localized() {
@@ -70,7 +86,7 @@ impl LoadedPlugin {
                     eprintln!("降级为普通插件模式（字符串将显示为 StringID）");
 
                     // 重新加载 Plugin（仅在 STRING 加载失败时）
-                    let fallback_plugin = Plugin::load(path)?;
+                    let fallback_plugin = Plugin::load_with_options(path, options)?;
                     Ok(LoadedPlugin::Standard(fallback_plugin))
                 }
             }
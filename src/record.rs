@@ -1,18 +1,15 @@
-use crate::datatypes::{read_u16, read_u32, RecordFlags};
+use crate::compression::{self, CompressionSettings, DetectedCompression};
+use crate::datatypes::{read_u16, read_u32, ParseSource, RecordFlags, SharedBytes, Signature, SourceSpan};
 use crate::subrecord::Subrecord;
 use std::io::{Read, Cursor};
+use std::sync::OnceLock;
 use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
-use std::io::Write;
 
 /// 记录结构
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Record {
-    /// 记录类型（原始4字节）
-    pub record_type_bytes: [u8; 4],
-    /// 记录类型字符串（用于比较）
-    pub record_type: String,
+    /// 记录类型
+    pub record_type: Signature,
     /// 数据大小
     pub data_size: u32,
     /// 标志位（原始32位数据）
@@ -28,31 +25,106 @@ pub struct Record {
     /// 未知字段
     pub unknown: u16,
     /// 原始压缩数据（如果记录是压缩的，保存原始压缩字节）
-    pub original_compressed_data: Option<Vec<u8>>,
+    pub original_compressed_data: Option<SharedBytes>,
     /// 原始数据（用于保持压缩记录的完整性）
-    pub raw_data: Vec<u8>,
+    ///
+    /// 未压缩记录引用文件数据，压缩记录为解压后的数据；子记录数据共享同一存储。
+    /// 延迟解析的记录在解析前仍为压缩数据，因此不公开，请使用 `data()` 读取。
+    raw_data: SharedBytes,
     /// 子记录列表
-    pub subrecords: Vec<Subrecord>,
+    ///
+    /// 延迟解析的记录（`ParseOptions::lazy`）在解析前为空，因此不公开，
+    /// 请使用 `subrecords()` / `subrecords_mut()` 访问。
+    subrecords: Vec<Subrecord>,
     /// 是否已被修改（用于智能压缩处理）
     pub is_modified: bool,
+    /// 在源文件中的字节范围（仅从内存映射加载的记录有值）
+    pub source_span: Option<SourceSpan>,
+    /// 延迟解析的内容（首次访问时解压并拆分子记录）
+    deferred: Option<Box<OnceLock<ParsedContent>>>,
+}
+
+/// 延迟解析得到的记录内容
+#[derive(Debug, Clone)]
+struct ParsedContent {
+    /// 解压后的数据（解压失败时为原始压缩数据）
+    data: SharedBytes,
+    /// 子记录列表
+    subrecords: Vec<Subrecord>,
 }
 
 impl Record {
+    /// 创建新的记录
+    ///
+    /// 新记录会被标记为已修改，写入时从子记录重新序列化。
+    /// 内部版本默认为 44（Skyrim SE 表单版本）。
+    ///
+    /// # 参数
+    /// * `record_type` - 4字符记录类型（如 "WEAP"）
+    /// * `form_id` - FormID
+    /// * `flags` - 记录标志位
+    /// * `subrecords` - 子记录列表
+    ///
+    /// # Panics
+    /// `record_type` 不是恰好 4 个字节时 panic。处理外部输入时使用 `try_new`。
+    pub fn new(record_type: &str, form_id: u32, flags: u32, subrecords: Vec<Subrecord>) -> Self {
+        Self::with_signature(Signature::from_name(record_type), form_id, flags, subrecords)
+    }
+
+    /// 创建新的记录，记录类型无效时返回错误
+    pub fn try_new(record_type: &str, form_id: u32, flags: u32, subrecords: Vec<Subrecord>) -> Result<Self, String> {
+        Ok(Self::with_signature(record_type.parse()?, form_id, flags, subrecords))
+    }
+
+    /// 以签名创建新的记录（参数同 `new`）
+    pub fn with_signature(record_type: Signature, form_id: u32, flags: u32, subrecords: Vec<Subrecord>) -> Self {
+        let raw_data = SharedBytes::from(Self::serialize_subrecords(&subrecords));
+
+        Record {
+            record_type,
+            data_size: raw_data.len() as u32,
+            flags,
+            form_id,
+            timestamp: 0,
+            version_control_info: 0,
+            internal_version: 44,
+            unknown: 0,
+            original_compressed_data: None,
+            raw_data,
+            subrecords,
+            is_modified: true,
+            source_span: None,
+            deferred: None,
+        }
+    }
+
     /// 解析记录
     pub fn parse(cursor: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_in_source(cursor, None)
+    }
+
+    /// 从共享数据源解析记录：数据引用 `source` 中的切片而不复制，
+    /// 源文件为内存映射时同时记录其字节范围
+    ///
+    /// `cursor` 所读的数据必须是 `source.bytes` 的前缀。
+    pub(crate) fn parse_in_source(
+        cursor: &mut Cursor<&[u8]>,
+        source: Option<&ParseSource>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         Self::validate_header_size(cursor)?;
+        let start = cursor.position();
         
         let mut type_bytes = [0u8; 4];
         cursor.read_exact(&mut type_bytes)?;
-        let record_type = String::from_utf8_lossy(&type_bytes).into_owned();
+        let record_type = Signature(type_bytes);
         
         let data_size = read_u32(cursor)?;
-        Self::validate_data_size(data_size, &record_type)?;
+        Self::validate_data_size(data_size, record_type)?;
         
         let flags_raw_bytes = read_u32(cursor)?;
         
         #[cfg(debug_assertions)]
-        Self::debug_record_parsing(&record_type, flags_raw_bytes, cursor.position());
+        Self::debug_record_parsing(record_type, flags_raw_bytes, cursor.position());
         
         let form_id = read_u32(cursor)?;
         let timestamp = read_u16(cursor)?;
@@ -61,15 +133,46 @@ impl Record {
         let unknown = read_u16(cursor)?;
         
         #[cfg(debug_assertions)]
-        Self::debug_record_details(&record_type, form_id, data_size, timestamp, version_control_info, internal_version, unknown, flags_raw_bytes);
+        Self::debug_record_details(record_type, form_id, data_size, timestamp, version_control_info, internal_version, unknown, flags_raw_bytes);
         
         Self::validate_data_availability(cursor, data_size)?;
         
-        let mut data = vec![0u8; data_size as usize];
-        cursor.read_exact(&mut data)?;
+        let data_start = cursor.position() as usize;
+        let data_range = data_start..data_start + data_size as usize;
+        cursor.set_position(data_range.end as u64);
+        let data = match source {
+            Some(source) => SharedBytes::from_source(source.bytes.clone(), data_range),
+            None => SharedBytes::from(&cursor.get_ref()[data_range]).into_shared(),
+        };
+        let source_span = source.and_then(|source| source.source_id).map(|source_id| SourceSpan {
+            source_id,
+            offset: start,
+            len: 24 + data_size as u64,
+        });
+
+        // 延迟解析：保留文件中的数据，首次访问子记录时再解压和拆分
+        if source.is_some_and(|source| source.lazy) {
+            let is_compressed = flags_raw_bytes & RecordFlags::COMPRESSED.bits() != 0;
+            return Ok(Record {
+                record_type,
+                data_size,
+                flags: flags_raw_bytes,
+                form_id,
+                timestamp,
+                version_control_info,
+                internal_version,
+                unknown,
+                original_compressed_data: is_compressed.then(|| data.clone()),
+                raw_data: data,
+                subrecords: Vec::new(),
+                is_modified: false,
+                source_span,
+                deferred: Some(Box::default()),
+            });
+        }
         
         let (final_data, parse_subrecords, original_compressed) = 
-            Self::handle_compression(&data, flags_raw_bytes, &record_type)?;
+            Self::handle_compression(&data, flags_raw_bytes, record_type)?;
 
         let subrecords = if parse_subrecords {
             Self::parse_subrecords(&final_data)?
@@ -78,7 +181,6 @@ impl Record {
         };
 
         Ok(Record {
-            record_type_bytes: type_bytes,
             record_type,
             data_size,
             flags: flags_raw_bytes,
@@ -91,6 +193,8 @@ impl Record {
             raw_data: final_data,
             subrecords,
             is_modified: false,
+            source_span,
+            deferred: None,
         })
     }
     
@@ -103,7 +207,7 @@ impl Record {
     }
     
     /// 验证数据大小
-    fn validate_data_size(data_size: u32, record_type: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_data_size(data_size: u32, record_type: Signature) -> Result<(), Box<dyn std::error::Error>> {
         if data_size > 100_000_000 {  // 100MB限制
             return Err(format!("记录 {} 数据大小异常: {} bytes (可能数据损坏)", 
                 record_type, data_size).into());
@@ -121,8 +225,8 @@ impl Record {
     
     /// 调试记录解析信息
     #[cfg(debug_assertions)]
-    fn debug_record_parsing(record_type: &str, flags: u32, position: u64) {
-        if ["STAT", "CONT", "GLOB", "ARMO", "WEAP", "NPC_"].contains(&record_type) {
+    fn debug_record_parsing(record_type: Signature, flags: u32, position: u64) {
+        if ["STAT", "CONT", "GLOB", "ARMO", "WEAP", "NPC_"].iter().any(|t| record_type == *t) {
             println!("=== 解析记录 {} (位置: 0x{:X}) ===", record_type, position - 16);
             println!("原始标志位: 0x{:08X} ({:032b})", flags, flags);
             
@@ -135,9 +239,9 @@ impl Record {
     /// 调试记录详细信息
     #[cfg(debug_assertions)]
     #[allow(clippy::too_many_arguments)]
-    fn debug_record_details(record_type: &str, form_id: u32, data_size: u32, timestamp: u16,
+    fn debug_record_details(record_type: Signature, form_id: u32, data_size: u32, timestamp: u16,
                            version_control_info: u16, internal_version: u16, unknown: u16, flags: u32) {
-        if ["STAT", "CONT", "GLOB", "ARMO", "WEAP", "NPC_"].contains(&record_type) {
+        if ["STAT", "CONT", "GLOB", "ARMO", "WEAP", "NPC_"].iter().any(|t| record_type == *t) {
             println!("FormID: 0x{:08X}", form_id);
             println!("数据大小: {} bytes", data_size);
             println!("时间戳: {}", timestamp);
@@ -186,22 +290,23 @@ impl Record {
     
     /// 处理压缩数据
     #[allow(clippy::type_complexity)]
-    fn handle_compression(data: &[u8], flags: u32, record_type: &str) -> Result<(Vec<u8>, bool, Option<Vec<u8>>), Box<dyn std::error::Error>> {
+    fn handle_compression(data: &SharedBytes, flags: u32, record_type: Signature) -> Result<(SharedBytes, bool, Option<SharedBytes>), Box<dyn std::error::Error>> {
         if flags & RecordFlags::COMPRESSED.bits() != 0 {
             match Self::decompress_data(data) {
                 Ok(decompressed) => {
                     #[cfg(debug_assertions)]
                     println!("成功解压记录 {}: {} -> {} bytes", record_type, data.len(), decompressed.len());
                     
-                    Ok((decompressed, true, Some(data.to_vec())))
+                    // 解压数据转为共享存储，子记录引用其中的切片
+                    Ok((SharedBytes::from(decompressed).into_shared(), true, Some(data.clone())))
                 },
                 Err(e) => {
                     eprintln!("警告: 记录 {} 解压失败: {}，跳过子记录解析", record_type, e);
-                    Ok((data.to_vec(), false, Some(data.to_vec())))
+                    Ok((data.clone(), false, Some(data.clone())))
                 }
             }
         } else {
-            Ok((data.to_vec(), true, None))
+            Ok((data.clone(), true, None))
         }
     }
     
@@ -260,10 +365,10 @@ impl Record {
         Ok(())
     }
     
-    /// 解析子记录
-    fn parse_subrecords(data: &[u8]) -> Result<Vec<Subrecord>, Box<dyn std::error::Error>> {
+    /// 解析子记录（子记录数据引用 `data` 的切片）
+    fn parse_subrecords(data: &SharedBytes) -> Result<Vec<Subrecord>, Box<dyn std::error::Error>> {
         let mut subrecords = Vec::new();
-        let mut cursor = Cursor::new(data);
+        let mut cursor = Cursor::new(data.as_slice());
 
         while cursor.position() < data.len() as u64 {
             // 检查剩余字节数
@@ -291,7 +396,7 @@ impl Record {
 
             let pos_before = cursor.position();
 
-            match Subrecord::parse(&mut cursor) {
+            match Subrecord::parse_shared(&mut cursor, data) {
                 Ok(subrecord) => {
                     subrecords.push(subrecord);
                 }
@@ -322,8 +427,8 @@ impl Record {
     }
     
     /// 获取记录类型
-    pub fn get_type(&self) -> &str {
-        &self.record_type
+    pub fn get_type(&self) -> Signature {
+        self.record_type
     }
     
     /// 获取FormID
@@ -336,14 +441,72 @@ impl Record {
         RecordFlags::from_bits_truncate(self.flags)
     }
     
+    /// 获取子记录列表（延迟解析的记录在首次调用时解压并拆分）
+    pub fn subrecords(&self) -> &[Subrecord] {
+        match self.parsed_content() {
+            Some(content) => &content.subrecords,
+            None => &self.subrecords,
+        }
+    }
+
+    /// 获取可修改的子记录列表（延迟解析的记录先完成解析）
+    pub fn subrecords_mut(&mut self) -> &mut Vec<Subrecord> {
+        self.ensure_parsed();
+        &mut self.subrecords
+    }
+
+    /// 获取解压后的记录数据（解压失败时为原始压缩数据）
+    pub fn data(&self) -> &[u8] {
+        match self.parsed_content() {
+            Some(content) => &content.data,
+            None => &self.raw_data,
+        }
+    }
+
+    /// 是否已完成解析（非延迟解析的记录始终为 true）
+    pub fn is_parsed(&self) -> bool {
+        self.deferred.as_ref().is_none_or(|cell| cell.get().is_some())
+    }
+
+    /// 完成延迟解析，把解压数据和子记录写入 `raw_data` 与 `subrecords` 字段
+    pub fn ensure_parsed(&mut self) {
+        if let Some(cell) = self.deferred.take() {
+            let content = cell.into_inner().unwrap_or_else(|| self.parse_content());
+            self.raw_data = content.data;
+            self.subrecords = content.subrecords;
+        }
+    }
+
+    /// 延迟解析的内容（首次访问时解析）
+    fn parsed_content(&self) -> Option<&ParsedContent> {
+        self.deferred.as_ref().map(|cell| cell.get_or_init(|| self.parse_content()))
+    }
+
+    /// 解压并拆分子记录（失败时与解析阶段解压失败的处理相同：保留原始数据，子记录为空）
+    fn parse_content(&self) -> ParsedContent {
+        let (data, parse_subrecords, _) = match Self::handle_compression(&self.raw_data, self.flags, self.record_type) {
+            Ok(result) => result,
+            Err(_) => (self.raw_data.clone(), false, None),
+        };
+        let subrecords = if parse_subrecords {
+            Self::parse_subrecords(&data).unwrap_or_else(|e| {
+                eprintln!("警告: 记录 {} [{:08X}] 子记录解析失败: {}", self.record_type, self.form_id, e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        ParsedContent { data, subrecords }
+    }
+
     /// 查找子记录
     pub fn find_subrecord(&self, record_type: &str) -> Option<&Subrecord> {
-        self.subrecords.iter().find(|sr| sr.record_type == record_type)
+        self.subrecords().iter().find(|sr| sr.record_type == record_type)
     }
     
     /// 查找所有匹配的子记录
     pub fn find_subrecords(&self, record_type: &str) -> Vec<&Subrecord> {
-        self.subrecords.iter().filter(|sr| sr.record_type == record_type).collect()
+        self.subrecords().iter().filter(|sr| sr.record_type == record_type).collect()
     }
     
     /// 获取编辑器ID
@@ -352,28 +515,51 @@ impl Record {
             .map(|sr| String::from_utf8_lossy(&sr.data).trim_end_matches('\0').to_string())
     }
     
-    /// 重新压缩数据
-    pub fn recompress_data(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let mut subrecord_data = Vec::new();
-        for subrecord in &self.subrecords {
-            subrecord_data.extend_from_slice(&subrecord.record_type_bytes);
-            subrecord_data.extend_from_slice(&subrecord.size.to_le_bytes());
-            subrecord_data.extend_from_slice(&subrecord.data);
+    /// 序列化子记录列表（未压缩的记录数据）
+    pub fn serialize_subrecords(subrecords: &[Subrecord]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(subrecords.iter().map(|sr| sr.data.len() + 6).sum());
+        for subrecord in subrecords {
+            subrecord.write_to(&mut output);
         }
-        
-        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(&subrecord_data)?;
-        let compressed_data = encoder.finish()?;
-        
-        let mut result = Vec::new();
+        output
+    }
+
+    /// 检测原始压缩数据使用的压缩参数
+    ///
+    /// 逐一重新压缩候选参数进行比较，开销较大，仅用于诊断；写入时只按 zlib 头部推断参数。
+    /// 记录未压缩或解压失败时返回 `None`。
+    pub fn detect_compression(&self) -> Option<DetectedCompression> {
+        let original = self.original_compressed_data.as_ref()?;
+        if original.len() < 4 || (self.subrecords().is_empty() && self.data() == original.as_slice()) {
+            return None;
+        }
+        compression::detect_compression(&original[4..], self.data())
+    }
+
+    /// 以指定参数压缩当前子记录（返回带 4 字节解压大小前缀的记录数据）
+    pub fn compress_with(&self, settings: CompressionSettings) -> Vec<u8> {
+        let subrecord_data = Self::serialize_subrecords(self.subrecords());
+        let compressed_data = compression::compress(&subrecord_data, settings);
+
+        let mut result = Vec::with_capacity(compressed_data.len() + 4);
         result.extend_from_slice(&(subrecord_data.len() as u32).to_le_bytes());
         result.extend_from_slice(&compressed_data);
-        
-        Ok(result)
+        result
     }
-    
-    /// 标记为已修改
+
+    /// 重新压缩数据
+    ///
+    /// 使用按原始 zlib 头部推断的压缩参数，原本未压缩时使用默认参数。
+    pub fn recompress_data(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let settings = self.original_compressed_data.as_ref()
+            .and_then(|original| compression::header_settings(original.get(4..)?))
+            .unwrap_or_default();
+        Ok(self.compress_with(settings))
+    }
+
+    /// 标记为已修改（延迟解析的记录先完成解析）
     pub fn mark_modified(&mut self) {
+        self.ensure_parsed();
         self.is_modified = true;
     }
     
@@ -398,7 +584,7 @@ mod tests {
             0x00,                                 // 1 字节填充
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_ok(), "应该成功解析带 1 字节填充的记录");
 
         let subrecords = result.unwrap();
@@ -416,7 +602,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, // 4 字节填充
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_ok(), "应该成功解析带 4 字节填充的记录");
 
         let subrecords = result.unwrap();
@@ -432,7 +618,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // 7 字节填充
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_ok(), "应该成功解析带 7 字节填充的记录");
     }
 
@@ -444,7 +630,7 @@ mod tests {
             b't', b'e', b's', b't',
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_ok(), "应该成功解析无填充的记录");
         assert_eq!(result.unwrap().len(), 1);
     }
@@ -460,7 +646,7 @@ mod tests {
             0x00, 0x00, // 2 字节填充
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 2, "应该解析出 2 个子记录");
     }
@@ -475,7 +661,7 @@ mod tests {
             0xFF, 0xAA, // 无效的尾部字节
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_err(), "非 NULL 的尾部数据应该报错");
 
         let err_msg = result.unwrap_err().to_string();
@@ -491,7 +677,114 @@ mod tests {
             0x00, 0xFF, 0x00, // 混合填充
         ];
 
-        let result = Record::parse_subrecords(&data);
+        let result = Record::parse_subrecords(&data.clone().into());
         assert!(result.is_err(), "混合填充应该报错");
     }
-} 
\ No newline at end of file
+
+    /// 测试 XXXX 超大子记录读写往返
+    #[test]
+    fn test_xxxx_subrecord_round_trip() {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"XXXX");
+        data.extend_from_slice(&4u16.to_le_bytes());
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(b"VMAD");
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data.extend_from_slice(b"EDID");
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(b"A\0");
+
+        let subrecords = Record::parse_subrecords(&data.clone().into()).unwrap();
+        assert!(subrecords[0].large);
+        assert_eq!(subrecords[0].record_type, "VMAD");
+        assert_eq!(Record::serialize_subrecords(&subrecords), data);
+    }
+
+    /// 测试超过 65535 字节的子记录自动写出 XXXX 前缀
+    #[test]
+    fn test_oversized_subrecord_written_with_xxxx() {
+        let mut desc = vec![b'a'; 70_000];
+        desc.push(0);
+        let record = Record::new("BOOK", 0x00000800, RecordFlags::COMPRESSED.bits(), vec![
+            Subrecord::new("EDID", b"Book\0".to_vec()),
+            Subrecord::new("DESC", desc.clone()),
+        ]);
+
+        let serialized = Record::serialize_subrecords(&record.subrecords);
+        assert_eq!(&serialized[11..21], b"XXXX\x04\x00\x71\x11\x01\x00");
+        let subrecords = Record::parse_subrecords(&serialized.clone().into()).unwrap();
+        assert_eq!(subrecords.len(), 2);
+        assert_eq!(subrecords[1].data, desc);
+
+        // 重新压缩的数据同样使用 XXXX 格式
+        let compressed = record.recompress_data().unwrap();
+        assert_eq!(u32::from_le_bytes(compressed[..4].try_into().unwrap()) as usize, serialized.len());
+        let mut decompressed = Vec::new();
+        ZlibDecoder::new(&compressed[4..]).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, serialized);
+    }
+
+    /// 测试从共享数据源解析时记录与子记录数据不复制
+    #[test]
+    fn test_parse_shares_source_data() {
+        let plain = Record::new("MISC", 0x00000800, 0, vec![Subrecord::new("EDID", b"Sword\0".to_vec())]);
+        let compressed = Record::new("BOOK", 0x00000801, RecordFlags::COMPRESSED.bits(), vec![
+            Subrecord::new("EDID", b"Book\0".to_vec()),
+        ]);
+        let mut file = Vec::new();
+        for (record, data) in [
+            (&plain, plain.raw_data.to_vec()),
+            (&compressed, compressed.recompress_data().unwrap()),
+        ] {
+            file.extend_from_slice(record.record_type.as_bytes());
+            file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            file.extend_from_slice(&record.flags.to_le_bytes());
+            file.extend_from_slice(&record.form_id.to_le_bytes());
+            file.extend_from_slice(&[0; 8]);
+            file.extend_from_slice(&data);
+        }
+
+        let file = std::sync::Arc::new(file);
+        let source = ParseSource::new(None, file.clone());
+        let mut cursor = Cursor::new(file.as_slice());
+        let plain = Record::parse_in_source(&mut cursor, Some(&source)).unwrap();
+        let compressed = Record::parse_in_source(&mut cursor, Some(&source)).unwrap();
+
+        // 未压缩记录的数据指向文件缓冲区
+        let file_range = file.as_ptr_range();
+        assert!(file_range.contains(&plain.raw_data.as_ptr()));
+        assert!(file_range.contains(&plain.subrecords[0].data.as_ptr()));
+
+        // 压缩记录：原始压缩数据指向文件缓冲区，子记录指向解压数据
+        assert!(file_range.contains(&compressed.original_compressed_data.as_ref().unwrap().as_ptr()));
+        assert!(compressed.raw_data.as_ptr_range().contains(&compressed.subrecords[0].data.as_ptr()));
+        assert_eq!(compressed.get_editor_id().as_deref(), Some("Book"));
+
+        // 修改时复制为独占数据，不影响源数据
+        let mut subrecord = plain.subrecords[0].clone();
+        subrecord.data.to_mut()[0] = b'W';
+        assert!(!subrecord.data.is_shared());
+        assert_eq!(plain.get_editor_id().as_deref(), Some("Sword"));
+    }
+
+    /// 测试构造函数：无效签名与超大数据
+    #[test]
+    fn test_constructors() {
+        assert!(Record::try_new("ABC", 0x800, 0, vec![]).is_err());
+        assert!(Subrecord::try_new("EDIDX", vec![]).is_err());
+        assert_eq!(Record::try_new("WEAP", 0x800, 0, vec![]).unwrap().record_type, "WEAP");
+
+        let small = Subrecord::new("EDID", vec![0; 4]);
+        assert_eq!((small.size, small.large), (4, false));
+
+        let mut large = Subrecord::try_new("VMAD", vec![0; 70_000]).unwrap();
+        assert_eq!((large.size, large.large), (0, true));
+        large.set_data(vec![0; 8]);
+        assert_eq!((large.size, large.large), (0, true));
+
+        let mut grown = Subrecord::new("DATA", vec![0; 8]);
+        grown.set_data(vec![0; 70_000]);
+        assert_eq!((grown.size, grown.large), (0, true));
+    }
+}
@@ -1,16 +1,24 @@
 mod bsa;
+mod compare;
 mod file;
 mod io;
+mod json;
+mod lazy;
+mod multi;
 mod set;
+mod term_base;
 
 #[cfg(test)]
 mod tests;
 
-pub use file::StringFile;
+pub use compare::{extract_placeholders, PlaceholderMismatch, StringDiff, StringSetComparison};
+pub use file::{RebuildOptions, StringFile};
+pub use multi::MultiLanguageStringSet;
 pub use set::{StringFileSet, StringFileStats};
+pub use term_base::{TermBase, TermBaseOptions, TermEntry};
 
 /// Bethesda字符串文件类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
 pub enum StringFileType {
     /// 对话字符串文件
     DLSTRINGS,
@@ -52,17 +60,25 @@ pub struct StringEntry {
     /// 字符串ID
     pub id: u32,
     /// 目录条目在文件中的位置
+    #[serde(default)]
     pub directory_address: u64,
     /// 相对偏移量
+    #[serde(default)]
     pub relative_offset: u32,
     /// 绝对偏移量（字符串数据起始位置）
+    #[serde(default)]
     pub absolute_offset: u64,
     /// 字符串长度（仅对DLSTRINGS/ILSTRINGS有效）
+    #[serde(default)]
     pub length: Option<u32>,
     /// 字符串内容（UTF-8编码）
     pub content: String,
     /// 原始字节数据
+    #[serde(default)]
     pub raw_data: Vec<u8>,
+    /// 是否为新建或修改过的条目（重建时按 `content` 重新编码，否则直接写入 `raw_data`）
+    #[serde(skip)]
+    pub modified: bool,
 }
 
 impl StringEntry {
@@ -77,9 +93,62 @@ impl StringEntry {
             length: Some(raw_data.len() as u32),
             content,
             raw_data,
+            modified: true,
         }
     }
 
+    /// 是否为从文件读取且未修改的条目
+    pub fn is_original(&self) -> bool {
+        !self.modified && self.directory_address != 0
+    }
+
+    /// 按文件类型编码为写入数据区的字节（长度前缀包括空终止符）
+    ///
+    /// 未修改的条目使用原始字节，保证重建结果与源文件一致；其他条目按 `encoding` 编码 `content`，
+    /// 含有该编码无法表示的字符时返回错误。
+    pub fn encode(
+        &self,
+        file_type: &StringFileType,
+        encoding: &'static encoding_rs::Encoding,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if self.is_original() {
+            let mut data = self.raw_data.clone();
+            // 旧版本写入的长度前缀不含空终止符，原始字节中缺少的终止符补在末尾
+            if data.last() != Some(&0) {
+                data.push(0);
+            }
+            return Ok(data);
+        }
+
+        let (text, _, had_errors) = encoding.encode(&self.content);
+        if had_errors {
+            return Err(format!("字符串 {} 含有 {} 编码无法表示的字符", self.id, encoding.name()).into());
+        }
+
+        let mut data = Vec::with_capacity(text.len() + 5);
+        if file_type.has_length_prefix() {
+            data.extend_from_slice(&(text.len() as u32 + 1).to_le_bytes());
+        }
+        data.extend_from_slice(&text);
+        data.push(0);
+        Ok(data)
+    }
+
+    /// 原始字节解码后的文本（与读取文件时的解码方式一致；没有原始字节时返回 `None`）
+    pub fn raw_content(&self, file_type: &StringFileType) -> Option<String> {
+        if self.raw_data.is_empty() {
+            return None;
+        }
+        let data = if file_type.has_length_prefix() {
+            self.raw_data.get(4..)?
+        } else {
+            &self.raw_data[..]
+        };
+
+        let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+        Some(String::from_utf8_lossy(&data[..end]).to_string())
+    }
+
     /// 获取字符串的总大小（包括长度前缀和空终止符）
     pub fn get_total_size(&self, file_type: &StringFileType) -> u32 {
         // 使用content的实际字节长度，而不是raw_data，确保一致性
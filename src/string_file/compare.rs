@@ -0,0 +1,247 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::utils::is_valid_string;
+
+use super::set::StringFileSet;
+use super::StringFileType;
+
+/// 两个语言之间单个字符串的差异
+#[derive(Debug, Clone, Serialize)]
+pub struct StringDiff {
+    /// 文件类型
+    pub file_type: StringFileType,
+    /// 字符串ID
+    pub string_id: u32,
+    /// 源语言文本（源语言缺失时为 `None`）
+    pub source_text: Option<String>,
+    /// 目标语言文本（目标语言缺失时为 `None`）
+    pub target_text: Option<String>,
+}
+
+/// 占位符不一致的字符串
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaceholderMismatch {
+    /// 文件类型
+    pub file_type: StringFileType,
+    /// 字符串ID
+    pub string_id: u32,
+    /// 源语言文本
+    pub source_text: String,
+    /// 目标语言文本
+    pub target_text: String,
+    /// 源语言中有、目标语言中没有的占位符
+    pub missing: Vec<String>,
+    /// 目标语言中多出的占位符
+    pub extra: Vec<String>,
+}
+
+/// 目标语言与源语言的 STRING 文件对比结果
+#[derive(Debug, Clone, Serialize)]
+pub struct StringSetComparison {
+    /// 插件名称
+    pub plugin_name: String,
+    /// 源语言
+    pub source_language: String,
+    /// 目标语言
+    pub target_language: String,
+    /// 两种语言都存在的字符串数量
+    pub compared: usize,
+    /// 与源语言完全相同的字符串（疑似未翻译）
+    pub untranslated: Vec<StringDiff>,
+    /// 源语言中有、目标语言中缺失的字符串
+    pub missing_in_target: Vec<StringDiff>,
+    /// 目标语言中有、源语言中没有的字符串
+    pub missing_in_source: Vec<StringDiff>,
+    /// 占位符不一致的字符串
+    pub placeholder_mismatches: Vec<PlaceholderMismatch>,
+}
+
+impl StringSetComparison {
+    /// 是否没有发现任何问题
+    pub fn is_clean(&self) -> bool {
+        self.issue_count() == 0
+    }
+
+    /// 问题总数
+    pub fn issue_count(&self) -> usize {
+        self.untranslated.len()
+            + self.missing_in_target.len()
+            + self.missing_in_source.len()
+            + self.placeholder_mismatches.len()
+    }
+
+    /// 仍需翻译的字符串（未翻译、目标缺失、占位符不一致），按文件类型和 ID 排序
+    pub fn pending_ids(&self) -> BTreeSet<(StringFileType, u32)> {
+        self.untranslated
+            .iter()
+            .chain(&self.missing_in_target)
+            .map(|diff| (diff.file_type, diff.string_id))
+            .chain(self.placeholder_mismatches.iter().map(|m| (m.file_type, m.string_id)))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for StringSetComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "=== 字符串翻译对比 ===")?;
+        writeln!(f, "插件: {}", self.plugin_name)?;
+        writeln!(f, "源语言: {} / 目标语言: {}", self.source_language, self.target_language)?;
+        writeln!(f, "对比字符串: {}", self.compared)?;
+        writeln!(f, "与源语言相同: {}", self.untranslated.len())?;
+        writeln!(f, "目标语言缺失: {}", self.missing_in_target.len())?;
+        writeln!(f, "源语言缺失: {}", self.missing_in_source.len())?;
+        writeln!(f, "占位符不一致: {}", self.placeholder_mismatches.len())?;
+        for mismatch in &self.placeholder_mismatches {
+            writeln!(
+                f,
+                "  [{:?} {}] 缺少 {:?}，多出 {:?}",
+                mismatch.file_type, mismatch.string_id, mismatch.missing, mismatch.extra
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl StringFileSet {
+    /// 以当前集合为源语言，按文件类型和 ID 与目标语言集合对比
+    ///
+    /// - 内容与源语言相同的有效字符串视为未翻译（空字符串等无效字符串不计入，见 `is_valid_string`）
+    /// - 只存在于一方的字符串分别记录为目标缺失或源缺失
+    /// - 内容不同但占位符（`<Alias=...>`、`<mag>`、`%d` 等）不一致的字符串单独记录
+    pub fn compare_with(&self, target: &StringFileSet) -> StringSetComparison {
+        let mut comparison = StringSetComparison {
+            plugin_name: self.plugin_name.clone(),
+            source_language: self.language.clone(),
+            target_language: target.language.clone(),
+            compared: 0,
+            untranslated: Vec::new(),
+            missing_in_target: Vec::new(),
+            missing_in_source: Vec::new(),
+            placeholder_mismatches: Vec::new(),
+        };
+
+        for file_type in [
+            StringFileType::STRINGS,
+            StringFileType::ILSTRINGS,
+            StringFileType::DLSTRINGS,
+        ] {
+            let source_ids: BTreeSet<u32> = self.files.get(&file_type)
+                .map(|file| file.get_string_ids().into_iter().collect())
+                .unwrap_or_default();
+            let target_ids: BTreeSet<u32> = target.files.get(&file_type)
+                .map(|file| file.get_string_ids().into_iter().collect())
+                .unwrap_or_default();
+
+            for &id in source_ids.union(&target_ids) {
+                let source_text = self.get_string_by_type(file_type, id).map(|entry| entry.content.clone());
+                let target_text = target.get_string_by_type(file_type, id).map(|entry| entry.content.clone());
+
+                match (source_text, target_text) {
+                    (Some(source_text), Some(target_text)) => {
+                        comparison.compared += 1;
+                        if source_text == target_text {
+                            if is_valid_string(&source_text) {
+                                comparison.untranslated.push(StringDiff {
+                                    file_type,
+                                    string_id: id,
+                                    source_text: Some(source_text),
+                                    target_text: Some(target_text),
+                                });
+                            }
+                            continue;
+                        }
+
+                        let (missing, extra) = diff_placeholders(&source_text, &target_text);
+                        if !missing.is_empty() || !extra.is_empty() {
+                            comparison.placeholder_mismatches.push(PlaceholderMismatch {
+                                file_type,
+                                string_id: id,
+                                source_text,
+                                target_text,
+                                missing,
+                                extra,
+                            });
+                        }
+                    }
+                    (Some(source_text), None) => comparison.missing_in_target.push(StringDiff {
+                        file_type,
+                        string_id: id,
+                        source_text: Some(source_text),
+                        target_text: None,
+                    }),
+                    (None, target_text) => comparison.missing_in_source.push(StringDiff {
+                        file_type,
+                        string_id: id,
+                        source_text: None,
+                        target_text,
+                    }),
+                }
+            }
+        }
+
+        comparison
+    }
+}
+
+/// 提取文本中的占位符（按出现顺序）
+///
+/// 识别游戏标签 `<Alias=Player>`、`<mag>`、`</font>` 等（`<` 后紧跟字母或 `/`），
+/// 以及 printf 风格的 `%s`、`%d`、`%.1f` 等；`%%` 不算占位符。
+pub fn extract_placeholders(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut placeholders = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '<' if chars.get(i + 1).is_some_and(|c| c.is_ascii_alphabetic() || *c == '/') => {
+                match chars[i + 1..].iter().position(|&c| c == '>' || c == '<') {
+                    Some(len) if chars[i + 1 + len] == '>' => {
+                        placeholders.push(chars[i..i + len + 2].iter().collect());
+                        i += len + 2;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            '%' => {
+                if chars.get(i + 1) == Some(&'%') {
+                    i += 2;
+                    continue;
+                }
+                let mut end = i + 1;
+                while end < chars.len() && (chars[end].is_ascii_digit() || matches!(chars[end], '.' | '-' | '+')) {
+                    end += 1;
+                }
+                if chars.get(end).is_some_and(|c| matches!(c, 's' | 'd' | 'i' | 'u' | 'f' | 'x' | 'X' | 'c')) {
+                    placeholders.push(chars[i..=end].iter().collect());
+                    i = end + 1;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    placeholders
+}
+
+/// 对比两段文本的占位符（作为多重集合），返回 (目标中缺失的, 目标中多出的)
+fn diff_placeholders(source: &str, target: &str) -> (Vec<String>, Vec<String>) {
+    let mut extra = extract_placeholders(target);
+    let mut missing = Vec::new();
+
+    for placeholder in extract_placeholders(source) {
+        match extra.iter().position(|p| p.eq_ignore_ascii_case(&placeholder)) {
+            Some(pos) => {
+                extra.remove(pos);
+            }
+            None => missing.push(placeholder),
+        }
+    }
+
+    (missing, extra)
+}
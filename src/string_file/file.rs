@@ -2,13 +2,28 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::{Cursor, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use crate::datatypes::read_u32;
+use memmap2::Mmap;
+
+use crate::datatypes::{read_u32, SharedBytes};
 use crate::utils::EspError;
 
 use super::io::parse_filename;
+use super::lazy::LazyEntries;
 use super::{StringEntry, StringFileStats, StringFileType};
 
+/// STRING 文件重建选项
+#[derive(Debug, Clone, Default)]
+pub struct RebuildOptions {
+    /// 内容相同的字符串共用同一个数据偏移（默认只保留源文件中已有的共用关系）
+    pub deduplicate: bool,
+    /// 新建或修改过的字符串使用的编码（encoding_rs 标签，如 "windows-1252"；`None` 为 UTF-8）
+    ///
+    /// 未修改的字符串始终写入原始字节。
+    pub encoding: Option<String>,
+}
+
 /// Bethesda字符串文件解析器
 #[derive(Debug, Clone)]
 pub struct StringFile {
@@ -21,7 +36,12 @@ pub struct StringFile {
     /// 关联的插件名称
     pub plugin_name: String,
     /// 字符串条目映射（ID -> StringEntry）
+    ///
+    /// 延迟加载的文件（见 `open_mapped`）只在这里保存修改过或新增的条目，
+    /// 读取请使用 `get_string()` 或 `iter()`。
     pub entries: HashMap<u32, StringEntry>,
+    /// 延迟解码的原始条目（`entries` 中的同 ID 条目优先）
+    pub(crate) base: Option<LazyEntries>,
 }
 
 impl StringFile {
@@ -42,9 +62,84 @@ impl StringFile {
             language,
             plugin_name,
             entries,
+            base: None,
+        })
+    }
+
+    /// 创建空的字符串文件（使用与 `from_bytes` 相同的虚拟路径）
+    pub fn empty(plugin_name: String, language: String, file_type: StringFileType) -> Self {
+        let path = PathBuf::from(format!(
+            "<memory>:{}_{}.{}",
+            plugin_name,
+            language,
+            file_type.to_extension()
+        ));
+
+        StringFile {
+            path,
+            file_type,
+            language,
+            plugin_name,
+            entries: HashMap::new(),
+            base: None,
+        }
+    }
+
+    /// 以内存映射方式打开字符串文件
+    ///
+    /// 只读取目录，字符串在首次访问时才解码，适合官方主文件等条目很多、只查询少量字符串的场景。
+    /// 修改、删除和 `rebuild` 与完整加载的文件行为一致。
+    pub fn open_mapped(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let (plugin_name, language, file_type) = parse_filename(&path)?;
+
+        if !path.exists() {
+            return Err(format!("字符串文件不存在: {:?}", path).into());
+        }
+
+        let file = fs::File::open(&path)?;
+        let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+        let len = mmap.len();
+        let data = SharedBytes::from_source(mmap, 0..len);
+
+        Ok(StringFile {
+            path,
+            file_type,
+            language,
+            plugin_name,
+            entries: HashMap::new(),
+            base: Some(LazyEntries::new(data, file_type)?),
+        })
+    }
+
+    /// 从共享字节数据创建延迟解码的字符串文件实例（参数同 `from_bytes`）
+    pub fn from_shared(
+        data: SharedBytes,
+        plugin_name: String,
+        language: String,
+        file_type: StringFileType,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = PathBuf::from(format!(
+            "<memory>:{}_{}.{}",
+            plugin_name,
+            language,
+            file_type.to_extension()
+        ));
+
+        Ok(StringFile {
+            path,
+            file_type,
+            language,
+            plugin_name,
+            entries: HashMap::new(),
+            base: Some(LazyEntries::new(data.into_shared(), file_type)?),
         })
     }
 
+    /// 是否延迟解码（由 `open_mapped` 或 `from_shared` 创建）
+    pub fn is_lazy(&self) -> bool {
+        self.base.is_some()
+    }
+
     /// 从内存字节数组创建字符串文件实例
     ///
     /// # 参数
@@ -74,6 +169,7 @@ impl StringFile {
             language,
             plugin_name,
             entries,
+            base: None,
         })
     }
 
@@ -82,69 +178,12 @@ impl StringFile {
         data: &[u8],
         file_type: &StringFileType,
     ) -> Result<HashMap<u32, StringEntry>, Box<dyn std::error::Error>> {
-        if data.len() < 8 {
-            return Err(EspError::InvalidFormat.into());
-        }
-
-        let mut cursor = Cursor::new(&data[..]);
-
-        // 读取文件头（8字节）
-        let string_count = read_u32(&mut cursor)?;
-        let _data_size = read_u32(&mut cursor)?;
-
-        if string_count == 0 {
-            return Ok(HashMap::new());
-        }
-
-        // 计算字符串数据的起始位置
-        let string_data_start = 8 + (string_count * 8) as u64;
-
-        // 读取目录条目
-        let mut entries = HashMap::new();
-        #[cfg(debug_assertions)]
-        let mut skipped_count = 0;
+        let directory = read_directory(data)?;
 
-        for i in 0..string_count {
-            let directory_address = 8 + (i * 8) as u64;
-            cursor.seek(SeekFrom::Start(directory_address))?;
-
-            let string_id = read_u32(&mut cursor)?;
-            let relative_offset = read_u32(&mut cursor)?;
-            let absolute_offset = string_data_start + relative_offset as u64;
-
-            if absolute_offset >= data.len() as u64 {
-                #[cfg(debug_assertions)]
-                {
-                    skipped_count += 1;
-                }
-                continue; // 跳过无效的偏移量
-            }
-
-            // 读取字符串数据
-            cursor.seek(SeekFrom::Start(absolute_offset))?;
-            let (content, raw_data, length) = Self::read_string_data(&mut cursor, file_type, data)?;
-
-            let entry = StringEntry {
-                id: string_id,
-                directory_address,
-                relative_offset,
-                absolute_offset,
-                length,
-                content,
-                raw_data,
-            };
-
-            entries.insert(string_id, entry);
-        }
-
-        #[cfg(debug_assertions)]
-        if skipped_count > 0 {
-            println!(
-                "[parse_bytes] 警告：跳过了 {} 个无效偏移量的字符串（文件头声明{}个，实际解析{}个）",
-                skipped_count,
-                string_count,
-                entries.len()
-            );
+        let mut entries = HashMap::with_capacity(directory.len());
+        for entry in &directory {
+            let entry = decode_entry(data, file_type, entry)?;
+            entries.insert(entry.id, entry);
         }
 
         Ok(entries)
@@ -208,65 +247,88 @@ impl StringFile {
 
     /// 获取字符串条目
     pub fn get_string(&self, id: u32) -> Option<&StringEntry> {
-        self.entries.get(&id)
+        self.entries.get(&id).or_else(|| self.base.as_ref()?.get(id))
+    }
+
+    /// 遍历所有字符串条目（顺序不固定）
+    pub fn iter(&self) -> impl Iterator<Item = &StringEntry> + '_ {
+        let base = self.base.iter()
+            .flat_map(|base| base.iter())
+            .filter(|entry| !self.entries.contains_key(&entry.id));
+        self.entries.values().chain(base)
     }
 
     /// 获取所有字符串ID
     pub fn get_string_ids(&self) -> Vec<u32> {
         let mut ids: Vec<u32> = self.entries.keys().cloned().collect();
-        ids.sort();
+        if let Some(base) = &self.base {
+            ids.extend(base.ids());
+            ids.sort();
+            ids.dedup();
+        } else {
+            ids.sort();
+        }
         ids
     }
 
     /// 获取字符串数量
     pub fn count(&self) -> usize {
-        self.entries.len()
+        match &self.base {
+            Some(base) => self.entries.len() + base.ids().filter(|id| !self.entries.contains_key(id)).count(),
+            None => self.entries.len(),
+        }
     }
 
     /// 检查是否为空
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+        self.count() == 0
     }
 
     /// 获取文件统计信息
     pub fn get_stats(&self) -> StringFileStats {
-        let total_content_size: usize = self.entries.values().map(|entry| entry.content.len()).sum();
+        let string_count = self.count();
+        let total_content_size: usize = self.iter().map(|entry| entry.content.len()).sum();
 
-        let total_raw_size: usize = self.entries.values().map(|entry| entry.raw_data.len()).sum();
+        let total_raw_size: usize = self.iter().map(|entry| entry.raw_data.len()).sum();
 
         StringFileStats {
             plugin_name: self.plugin_name.clone(),
             language: self.language.clone(),
             file_type: self.file_type,
-            string_count: self.entries.len(),
+            string_count,
             total_content_size,
             total_raw_size,
-            average_string_length: if self.entries.is_empty() {
+            average_string_length: if string_count == 0 {
                 0.0
             } else {
-                total_content_size as f64 / self.entries.len() as f64
+                total_content_size as f64 / string_count as f64
             },
         }
     }
 
     /// 查找包含指定文本的字符串
     pub fn find_strings_containing(&self, text: &str) -> Vec<&StringEntry> {
-        self.entries
-            .values()
+        self.iter()
             .filter(|entry| entry.content.contains(text))
             .collect()
     }
 
     /// 更新字符串内容
     pub fn update_string(&mut self, id: u32, new_content: String) -> Result<(), EspError> {
-        if let Some(entry) = self.entries.get_mut(&id) {
-            entry.content = new_content.clone();
-            entry.raw_data = new_content.as_bytes().to_vec();
-            entry.length = Some(entry.raw_data.len() as u32);
-            Ok(())
-        } else {
-            Err(EspError::InvalidFormat)
-        }
+        let entry = match self.entries.entry(id) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(slot) => {
+                // 延迟解码的条目先复制到 entries 再修改
+                let original = self.base.as_ref().and_then(|base| base.get(id)).ok_or(EspError::InvalidFormat)?;
+                slot.insert(original.clone())
+            }
+        };
+
+        entry.content = new_content.clone();
+        entry.raw_data = new_content.as_bytes().to_vec();
+        entry.length = Some(entry.raw_data.len() as u32);
+        entry.modified = true;
+        Ok(())
     }
 
     /// 批量更新字符串
@@ -279,7 +341,7 @@ impl StringFile {
 
     /// 添加新字符串
     pub fn add_string(&mut self, id: u32, content: String) -> Result<(), EspError> {
-        if self.entries.contains_key(&id) {
+        if self.get_string(id).is_some() {
             return Err(EspError::InvalidFormat);
         }
 
@@ -290,80 +352,205 @@ impl StringFile {
 
     /// 删除字符串
     pub fn remove_string(&mut self, id: u32) -> Option<StringEntry> {
-        self.entries.remove(&id)
+        let removed = self.entries.remove(&id);
+        let original = self.base.as_mut().and_then(|base| base.remove(id));
+        removed.or(original)
     }
 
-    /// 重建STRING文件的二进制数据
+    /// 重建STRING文件的二进制数据（使用默认选项，见 `rebuild_with`）
     pub fn rebuild(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.rebuild_with(&RebuildOptions::default())
+    }
+
+    /// 按选项重建STRING文件的二进制数据
+    ///
+    /// - 目录保持源文件中的顺序，新增的字符串按 ID 排在最后
+    /// - 未修改的字符串写入原始字节，并保持原来共用同一偏移的关系
+    /// - 修改过的字符串写在原来的位置附近，不再与其他 ID 共用
+    ///
+    /// 未做任何修改的文件重建结果与源文件逐字节一致。
+    pub fn rebuild_with(&self, options: &RebuildOptions) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         use crate::datatypes::write_u32;
         use std::io::Write;
 
-        let mut buffer = Vec::new();
+        let encoding = match &options.encoding {
+            Some(label) => encoding_rs::Encoding::for_label(label.as_bytes())
+                .ok_or_else(|| format!("不支持的编码: {}", label))?,
+            None => encoding_rs::UTF_8,
+        };
 
-        // 1. 写入文件头（8字节）
-        // 字符串数量
-        let count = self.entries.len() as u32;
-        write_u32(&mut buffer, count)?;
-
-        // 计算数据区总大小
-        let data_size: u32 = self
-            .entries
-            .values()
-            .map(|e| e.get_total_size(&self.file_type))
-            .sum();
-        write_u32(&mut buffer, data_size)?;
+        let mut buffer = Vec::new();
 
-        // 2. 准备排序的ID列表和目录条目
-        let mut ids: Vec<u32> = self.entries.keys().cloned().collect();
-        ids.sort();
+        // 目录顺序：源文件中的条目按目录位置，新增条目按 ID（解码失败的延迟条目不写入）
+        let mut entries: Vec<&StringEntry> = self
+            .get_string_ids()
+            .into_iter()
+            .filter_map(|id| self.get_string(id))
+            .collect();
+        entries.sort_by_key(|entry| (entry.directory_address == 0, entry.directory_address, entry.id));
 
         #[cfg(debug_assertions)]
-        println!("[rebuild] 准备写入 {} 个字符串", ids.len());
-
-        // 计算每个字符串的偏移量
-        let mut offset = 0u32;
-        let mut directory_entries = Vec::new();
-
-        for id in &ids {
-            directory_entries.push((*id, offset));
-            let entry = &self.entries[id];
-            let size = entry.get_total_size(&self.file_type);
-            offset += size;
+        println!("[rebuild] 准备写入 {} 个字符串", entries.len());
+
+        // 1. 分配数据块：(排序位置, 编码后的字节)
+        let mut blobs: Vec<(u64, Vec<u8>)> = Vec::new();
+        let mut entry_blobs = Vec::with_capacity(entries.len());
+        let mut by_original_offset: HashMap<u32, usize> = HashMap::new();
+        let mut by_content: HashMap<Vec<u8>, usize> = HashMap::new();
+
+        for entry in &entries {
+            // 源文件中的条目按原偏移排列，新增条目排在最后
+            let position = if entry.directory_address == 0 { u64::MAX } else { entry.relative_offset as u64 };
+            let data = entry.encode(&self.file_type, encoding)?;
+
+            let blob = if options.deduplicate {
+                match by_content.get(&data) {
+                    Some(&blob) => blob,
+                    None => {
+                        blobs.push((position, data.clone()));
+                        by_content.insert(data, blobs.len() - 1);
+                        blobs.len() - 1
+                    }
+                }
+            } else if entry.is_original() {
+                *by_original_offset.entry(entry.relative_offset).or_insert_with(|| {
+                    blobs.push((position, data));
+                    blobs.len() - 1
+                })
+            } else {
+                blobs.push((position, data));
+                blobs.len() - 1
+            };
+            entry_blobs.push(blob);
         }
 
-        // 3. 写入目录条目（每个8字节：ID + 偏移量）
-        for (id, offset) in &directory_entries {
-            write_u32(&mut buffer, *id)?;
-            write_u32(&mut buffer, *offset)?;
-        }
+        // 2. 计算数据块偏移（同一位置按分配顺序排列）
+        let mut order: Vec<usize> = (0..blobs.len()).collect();
+        order.sort_by_key(|&blob| blobs[blob].0);
 
-        // 4. 写入字符串数据
-        for id in &ids {
-            let entry = &self.entries[id];
+        let mut offsets = vec![0u32; blobs.len()];
+        let mut data_size = 0u32;
+        for &blob in &order {
+            offsets[blob] = data_size;
+            data_size += blobs[blob].1.len() as u32;
+        }
 
-            if self.file_type.has_length_prefix() {
-                // DLSTRINGS/ILSTRINGS: 长度前缀 + 内容 + null终止符
-                let length = entry.content.len() as u32;
-                write_u32(&mut buffer, length)?;
-            }
+        // 3. 写入文件头（8字节）：字符串数量 + 数据区总大小
+        write_u32(&mut buffer, entries.len() as u32)?;
+        write_u32(&mut buffer, data_size)?;
 
-            // 字符串内容（UTF-8编码）
-            buffer.write_all(entry.content.as_bytes())?;
+        // 4. 写入目录条目（每个8字节：ID + 偏移量）
+        for (entry, blob) in entries.iter().zip(&entry_blobs) {
+            write_u32(&mut buffer, entry.id)?;
+            write_u32(&mut buffer, offsets[*blob])?;
+        }
 
-            // null终止符
-            buffer.push(0);
+        // 5. 写入字符串数据
+        for &blob in &order {
+            buffer.write_all(&blobs[blob].1)?;
         }
 
         #[cfg(debug_assertions)]
-        println!("[rebuild] 写入完成，总大小 {} 字节", buffer.len());
+        println!("[rebuild] 写入完成，总大小 {} 字节（{} 个数据块）", buffer.len(), blobs.len());
 
         Ok(buffer)
     }
 
     /// 写入到文件
+    ///
+    /// 先写入临时文件再替换，可以直接覆盖 `open_mapped` 打开的源文件。
     pub fn write_to_file(&self, path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         let data = self.rebuild()?;
-        fs::write(path, data)?;
-        Ok(())
+        crate::io::esp_io::write_file_atomically(&path, &mut |writer| {
+            writer.write_all(&data)?;
+            Ok(())
+        })
+    }
+}
+
+/// 字符串目录条目
+#[derive(Debug, Clone, Copy)]
+pub(super) struct DirectoryEntry {
+    /// 字符串ID
+    pub(super) id: u32,
+    /// 目录条目在文件中的位置
+    pub(super) directory_address: u64,
+    /// 相对偏移量
+    pub(super) relative_offset: u32,
+    /// 绝对偏移量（字符串数据起始位置）
+    pub(super) absolute_offset: u64,
+}
+
+/// 读取文件头和目录（按文件中的顺序，跳过偏移量越界的条目）
+pub(super) fn read_directory(data: &[u8]) -> Result<Vec<DirectoryEntry>, Box<dyn std::error::Error>> {
+    if data.len() < 8 {
+        return Err(EspError::InvalidFormat.into());
     }
+
+    let mut cursor = Cursor::new(data);
+
+    // 读取文件头（8字节）
+    let string_count = read_u32(&mut cursor)?;
+    let _data_size = read_u32(&mut cursor)?;
+
+    // 计算字符串数据的起始位置
+    let string_data_start = 8 + string_count as u64 * 8;
+
+    // 读取目录条目
+    let mut directory = Vec::with_capacity((string_count as usize).min(data.len() / 8));
+    #[cfg(debug_assertions)]
+    let mut skipped_count = 0;
+
+    for i in 0..string_count as u64 {
+        let directory_address = 8 + i * 8;
+        cursor.seek(SeekFrom::Start(directory_address))?;
+
+        let id = read_u32(&mut cursor)?;
+        let relative_offset = read_u32(&mut cursor)?;
+        let absolute_offset = string_data_start + relative_offset as u64;
+
+        if absolute_offset >= data.len() as u64 {
+            #[cfg(debug_assertions)]
+            {
+                skipped_count += 1;
+            }
+            continue; // 跳过无效的偏移量
+        }
+
+        directory.push(DirectoryEntry { id, directory_address, relative_offset, absolute_offset });
+    }
+
+    #[cfg(debug_assertions)]
+    if skipped_count > 0 {
+        println!(
+            "[parse_bytes] 警告：跳过了 {} 个无效偏移量的字符串（文件头声明{}个，实际解析{}个）",
+            skipped_count,
+            string_count,
+            directory.len()
+        );
+    }
+
+    Ok(directory)
+}
+
+/// 解码目录条目指向的字符串
+pub(super) fn decode_entry(
+    data: &[u8],
+    file_type: &StringFileType,
+    entry: &DirectoryEntry,
+) -> Result<StringEntry, Box<dyn std::error::Error>> {
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(entry.absolute_offset);
+    let (content, raw_data, length) = StringFile::read_string_data(&mut cursor, file_type, data)?;
+
+    Ok(StringEntry {
+        id: entry.id,
+        directory_address: entry.directory_address,
+        relative_offset: entry.relative_offset,
+        absolute_offset: entry.absolute_offset,
+        length,
+        content,
+        raw_data,
+        modified: false,
+    })
 }
@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+use super::{StringEntry, StringFile, StringFileType};
+
+/// STRING 文件的 JSON 表示
+///
+/// 支持 `--parse-strings` 输出的条目数组，以及简单的 `{"ID": "文本"}` 映射（ID 可以是十进制或 `0x` 开头的十六进制）。
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringsJson {
+    Entries(Vec<StringEntry>),
+    Map(BTreeMap<String, String>),
+}
+
+impl StringsJson {
+    /// 转为条目列表（映射形式的条目为新建条目）
+    fn into_entries(self, file_type: &StringFileType) -> Result<Vec<StringEntry>, Box<dyn std::error::Error>> {
+        match self {
+            StringsJson::Entries(entries) => Ok(entries
+                .into_iter()
+                .map(|mut entry| {
+                    // 内容与原始字节不一致说明已被编辑，重建时按内容重新编码
+                    entry.modified = entry.raw_content(file_type).as_deref() != Some(entry.content.as_str());
+                    entry
+                })
+                .collect()),
+            StringsJson::Map(map) => map
+                .into_iter()
+                .map(|(id, content)| Ok(StringEntry::new(parse_string_id(&id)?, content)))
+                .collect(),
+        }
+    }
+}
+
+/// 解析 JSON 中的字符串 ID
+fn parse_string_id(text: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    let text = text.trim();
+    let id = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    id.map_err(|_| format!("无效的字符串ID: {}", text).into())
+}
+
+impl StringFile {
+    /// 从 JSON 创建字符串文件（`--parse-strings` 的逆操作）
+    ///
+    /// 条目数组中内容未被编辑的条目保留原始字节和目录位置，重建结果与原文件一致。
+    ///
+    /// # 示例
+    /// ```rust,ignore
+    /// let json = std::fs::read_to_string("Mod_chinese.json")?;
+    /// let file = StringFile::from_json(&json, "Mod".into(), "chinese".into(), StringFileType::DLSTRINGS)?;
+    /// file.write_to_file("Mod_chinese.DLSTRINGS".into())?;
+    /// ```
+    pub fn from_json(
+        json: &str,
+        plugin_name: String,
+        language: String,
+        file_type: StringFileType,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let parsed: StringsJson = serde_json::from_str(json)?;
+
+        let mut entries = HashMap::new();
+        for entry in parsed.into_entries(&file_type)? {
+            if entries.insert(entry.id, entry).is_some() {
+                #[cfg(debug_assertions)]
+                eprintln!("警告: JSON 中的字符串ID重复，使用最后一个");
+            }
+        }
+
+        let mut file = StringFile::empty(plugin_name, language, file_type);
+        file.entries = entries;
+        Ok(file)
+    }
+
+    /// 将 JSON 中的字符串合并到当前文件
+    ///
+    /// 已有的 ID 更新内容（内容相同的不视为修改），不存在的 ID 作为新字符串添加。
+    ///
+    /// # 返回
+    /// (更新的数量, 新增的数量)
+    pub fn merge_json(&mut self, json: &str) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+        let parsed: StringsJson = serde_json::from_str(json)?;
+
+        let mut updated = 0;
+        let mut added = 0;
+        for entry in parsed.into_entries(&self.file_type)? {
+            match self.get_string(entry.id) {
+                Some(existing) if existing.content == entry.content => {}
+                Some(_) => {
+                    self.update_string(entry.id, entry.content)?;
+                    updated += 1;
+                }
+                None => {
+                    self.add_string(entry.id, entry.content)?;
+                    added += 1;
+                }
+            }
+        }
+
+        Ok((updated, added))
+    }
+}
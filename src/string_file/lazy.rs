@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use crate::datatypes::SharedBytes;
+
+use super::file::{decode_entry, read_directory, DirectoryEntry};
+use super::{StringEntry, StringFileType};
+
+/// 按需解码的字符串条目
+///
+/// 只在加载时读取目录（按 ID 排序），字符串在首次访问时解码并缓存。
+/// 数据通常是内存映射文件，克隆只增加引用计数。
+#[derive(Clone)]
+pub(crate) struct LazyEntries {
+    /// 整个字符串文件的数据
+    data: SharedBytes,
+    /// 文件类型
+    file_type: StringFileType,
+    /// 目录条目（按 ID 排序，ID 唯一）
+    directory: Vec<DirectoryEntry>,
+    /// 已解码的条目（与 `directory` 一一对应，解码失败为 `None`）
+    decoded: Vec<OnceLock<Option<StringEntry>>>,
+    /// 已删除的 ID
+    removed: HashSet<u32>,
+}
+
+impl LazyEntries {
+    /// 读取目录
+    pub(crate) fn new(data: SharedBytes, file_type: StringFileType) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut directory = read_directory(&data)?;
+
+        // 重复的 ID 以目录中最后一个为准（与完整解析时后插入的覆盖先插入的一致）
+        directory.reverse();
+        directory.sort_by_key(|entry| entry.id);
+        directory.dedup_by_key(|entry| entry.id);
+
+        let decoded = directory.iter().map(|_| OnceLock::new()).collect();
+        Ok(LazyEntries { data, file_type, directory, decoded, removed: HashSet::new() })
+    }
+
+    /// 获取字符串条目（首次访问时解码）
+    pub(crate) fn get(&self, id: u32) -> Option<&StringEntry> {
+        if self.removed.contains(&id) {
+            return None;
+        }
+        let index = self.directory.binary_search_by_key(&id, |entry| entry.id).ok()?;
+        self.get_at(index)
+    }
+
+    /// 删除字符串条目，返回被删除的条目
+    pub(crate) fn remove(&mut self, id: u32) -> Option<StringEntry> {
+        let entry = self.get(id).cloned()?;
+        self.removed.insert(id);
+        Some(entry)
+    }
+
+    /// 按 ID 顺序遍历未删除的条目（解码失败的条目被跳过）
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &StringEntry> + '_ {
+        (0..self.directory.len())
+            .filter(|&index| !self.removed.contains(&self.directory[index].id))
+            .filter_map(|index| self.get_at(index))
+    }
+
+    /// 按 ID 顺序遍历未删除的条目 ID（不解码）
+    pub(crate) fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.directory.iter()
+            .map(|entry| entry.id)
+            .filter(|id| !self.removed.contains(id))
+    }
+
+    fn get_at(&self, index: usize) -> Option<&StringEntry> {
+        self.decoded[index]
+            .get_or_init(|| match decode_entry(&self.data, &self.file_type, &self.directory[index]) {
+                Ok(entry) => Some(entry),
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("警告: 无法解码字符串 {}: {}", self.directory[index].id, _e);
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+impl std::fmt::Debug for LazyEntries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyEntries")
+            .field("file_type", &self.file_type)
+            .field("data_len", &self.data.len())
+            .field("directory_len", &self.directory.len())
+            .field("decoded", &self.decoded.iter().filter(|entry| entry.get().is_some()).count())
+            .field("removed", &self.removed.len())
+            .finish()
+    }
+}
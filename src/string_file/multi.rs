@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::bsa::BsaStringsProvider;
+
+use super::file::StringFile;
+use super::set::StringFileSet;
+use super::{StringEntry, StringFileType};
+
+/// 多语言字符串文件集合（每种语言一个 `StringFileSet`）
+///
+/// 语言标识统一为小写（如 `english`、`chinese`），按字母顺序排列。
+#[derive(Debug, Clone)]
+pub struct MultiLanguageStringSet {
+    /// 插件名称
+    pub plugin_name: String,
+    /// 语言 -> 字符串文件集合
+    pub sets: BTreeMap<String, StringFileSet>,
+}
+
+impl MultiLanguageStringSet {
+    /// 创建空的多语言集合
+    pub fn new(plugin_name: String) -> Self {
+        MultiLanguageStringSet {
+            plugin_name,
+            sets: BTreeMap::new(),
+        }
+    }
+
+    /// 列出目录中该插件存在的所有语言（匹配 `<plugin>_<lang>.*STRINGS`，大小写不敏感）
+    pub fn discover_languages(directory: &Path, plugin_name: &str) -> Vec<String> {
+        let names = list_file_names(directory);
+        group_by_language(names.iter().map(String::as_str), plugin_name)
+            .into_keys()
+            .collect()
+    }
+
+    /// 以内存映射方式加载目录中该插件的所有语言
+    ///
+    /// 无法读取的文件跳过并输出警告，不影响其他文件和语言。
+    pub fn load_from_directory(
+        directory: &Path,
+        plugin_name: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut multi = MultiLanguageStringSet::new(plugin_name.to_string());
+        let names = list_file_names(directory);
+
+        for (language, files) in group_by_language(names.iter().map(String::as_str), plugin_name) {
+            let mut set = StringFileSet::new(plugin_name.to_string(), language.clone());
+            for (file_type, name) in files {
+                match StringFile::open_mapped(directory.join(name)) {
+                    Ok(string_file) => {
+                        set.files.insert(file_type, string_file);
+                    }
+                    Err(e) => eprintln!("警告: 跳过无法加载的字符串文件 {:?}: {}", directory.join(name), e),
+                }
+            }
+            if !set.files.is_empty() {
+                multi.sets.insert(language, set);
+            }
+        }
+
+        Ok(multi)
+    }
+
+    /// 从插件对应的 BSA 归档中加载所有语言（无法加载的语言跳过并输出警告）
+    pub fn load_from_bsa(
+        plugin_path: &Path,
+        plugin_name: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let provider = BsaStringsProvider::open_for_plugin(plugin_path)?;
+        let names = provider.list_strings_files();
+
+        let mut multi = MultiLanguageStringSet::new(plugin_name.to_string());
+        for language in group_by_language(names.iter().map(String::as_str), plugin_name).into_keys() {
+            match StringFileSet::load_from_bsa(plugin_path, plugin_name, &language) {
+                Ok(set) => {
+                    multi.sets.insert(language, set);
+                }
+                Err(e) => eprintln!("警告: 跳过 BSA 中无法加载的语言 {}: {}", language, e),
+            }
+        }
+
+        Ok(multi)
+    }
+
+    /// 合并另一个集合中本集合尚未包含的语言（已有语言保持不变）
+    pub fn merge_missing(&mut self, other: MultiLanguageStringSet) {
+        for (language, set) in other.sets {
+            self.sets.entry(language).or_insert(set);
+        }
+    }
+
+    /// 已加载的语言列表
+    pub fn languages(&self) -> Vec<&str> {
+        self.sets.keys().map(String::as_str).collect()
+    }
+
+    /// 获取指定语言的字符串文件集合（语言大小写不敏感）
+    pub fn get(&self, language: &str) -> Option<&StringFileSet> {
+        self.sets.get(&language.to_lowercase())
+    }
+
+    /// 是否未加载任何语言
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// 查找指定字符串在各语言中的条目（缺失该字符串的语言不出现在结果中）
+    pub fn get_entries(&self, file_type: StringFileType, id: u32) -> BTreeMap<&str, &StringEntry> {
+        self.sets
+            .iter()
+            .filter_map(|(language, set)| {
+                set.get_string_by_type(file_type, id)
+                    .map(|entry| (language.as_str(), entry))
+            })
+            .collect()
+    }
+}
+
+/// 列出目录下的文件名（目录不存在或不可读时返回空列表）
+fn list_file_names(directory: &Path) -> Vec<String> {
+    let Ok(read_dir) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+/// 按语言归类属于该插件的字符串文件名（可带归档内目录前缀）
+///
+/// 同一语言和类型出现多次时保留第一个。
+fn group_by_language<'a>(
+    names: impl Iterator<Item = &'a str>,
+    plugin_name: &str,
+) -> BTreeMap<String, HashMap<StringFileType, &'a str>> {
+    let mut languages: BTreeMap<String, HashMap<StringFileType, &'a str>> = BTreeMap::new();
+
+    for name in names {
+        let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+        let Some((stem, extension)) = file_name.rsplit_once('.') else {
+            continue;
+        };
+        let Some(file_type) = StringFileType::from_extension(extension) else {
+            continue;
+        };
+        let Some((plugin, language)) = stem.rsplit_once('_') else {
+            continue;
+        };
+        if language.is_empty() || !plugin.eq_ignore_ascii_case(plugin_name) {
+            continue;
+        }
+
+        languages
+            .entry(language.to_lowercase())
+            .or_default()
+            .entry(file_type)
+            .or_insert(name);
+    }
+
+    languages
+}
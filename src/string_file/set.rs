@@ -152,6 +152,31 @@ impl StringFileSet {
         Ok(set)
     }
 
+    /// 以内存映射方式加载指定目录下的所有字符串文件（字符串在首次访问时解码，见 `StringFile::open_mapped`）
+    pub fn load_from_directory_mapped(
+        directory: &Path,
+        plugin_name: &str,
+        language: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut set = StringFileSet::new(plugin_name.to_string(), language.to_string());
+
+        for file_type in [
+            StringFileType::STRINGS,
+            StringFileType::ILSTRINGS,
+            StringFileType::DLSTRINGS,
+        ] {
+            for filepath in build_filename_variants(directory, plugin_name, language, file_type) {
+                if filepath.exists() {
+                    let string_file = StringFile::open_mapped(filepath)?;
+                    set.files.insert(file_type, string_file);
+                    break;
+                }
+            }
+        }
+
+        Ok(set)
+    }
+
     /// 获取指定类型的字符串文件
     pub fn get_file(&self, file_type: &StringFileType) -> Option<&StringFile> {
         self.files.get(file_type)
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::is_valid_string;
+
+use super::set::StringFileSet;
+use super::StringFileType;
+
+/// 名称术语的默认最大长度（字符数）
+const DEFAULT_NAME_MAX_LENGTH: usize = 64;
+
+/// 术语库生成选项
+#[derive(Debug, Clone, Default)]
+pub struct TermBaseOptions {
+    /// 只保留名称：STRINGS 文件中的字符串（配合插件使用时只保留 FULL 字段）
+    pub names_only: bool,
+    /// 源语言文本的最大字符数，超过的条目不收录
+    pub max_length: Option<usize>,
+}
+
+impl TermBaseOptions {
+    /// 只收录简短名称（物品、地点、法术等 FULL 名称）的预设
+    pub fn names() -> Self {
+        TermBaseOptions {
+            names_only: true,
+            max_length: Some(DEFAULT_NAME_MAX_LENGTH),
+        }
+    }
+
+    /// 源语言文本是否符合长度限制
+    pub(crate) fn accepts_length(&self, text: &str) -> bool {
+        self.max_length.is_none_or(|max| text.chars().count() <= max)
+    }
+}
+
+/// 术语库条目（同一 StringID 在两种语言中的文本）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermEntry {
+    /// 文件类型
+    pub file_type: StringFileType,
+    /// 字符串ID
+    pub string_id: u32,
+    /// 源语言文本
+    pub source: String,
+    /// 目标语言文本
+    pub target: String,
+    /// 引用该字符串的记录类型（按插件生成时可用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_type: Option<String>,
+    /// 引用该字符串的记录 EDID（按插件生成时可用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor_id: Option<String>,
+}
+
+/// 双语术语库 / 翻译记忆
+///
+/// 由同一插件两种语言的官方 STRING 文件按 ID 对齐生成，相同的原文/译文对只收录一次。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermBase {
+    /// 源语言
+    pub source_language: String,
+    /// 目标语言
+    pub target_language: String,
+    /// 术语条目
+    pub entries: Vec<TermEntry>,
+}
+
+impl TermBase {
+    /// 创建空术语库
+    pub fn new(source_language: String, target_language: String) -> Self {
+        TermBase {
+            source_language,
+            target_language,
+            entries: Vec::new(),
+        }
+    }
+
+    /// 按文件类型和 ID 对齐两种语言的 STRING 文件
+    ///
+    /// 两种语言都存在且都是有效字符串的 ID 才会收录；
+    /// `names_only` 时只对齐 STRINGS 文件（不区分字段类型，需要精确到 FULL 请使用 `Plugin::build_term_base`）。
+    pub fn align(source: &StringFileSet, target: &StringFileSet, options: &TermBaseOptions) -> Self {
+        let mut term_base = TermBase::new(source.language.clone(), target.language.clone());
+        let mut seen = HashSet::new();
+
+        let file_types: &[StringFileType] = if options.names_only {
+            &[StringFileType::STRINGS]
+        } else {
+            &[StringFileType::STRINGS, StringFileType::ILSTRINGS, StringFileType::DLSTRINGS]
+        };
+
+        for &file_type in file_types {
+            let Some(source_file) = source.get_file(&file_type) else {
+                continue;
+            };
+            for id in source_file.get_string_ids() {
+                term_base.push_aligned(source, target, file_type, id, options, &mut seen);
+            }
+        }
+
+        term_base
+    }
+
+    /// 对齐单个字符串并加入术语库，返回是否收录（内部辅助方法）
+    pub(crate) fn push_aligned(
+        &mut self,
+        source: &StringFileSet,
+        target: &StringFileSet,
+        file_type: StringFileType,
+        string_id: u32,
+        options: &TermBaseOptions,
+        seen: &mut HashSet<(String, String)>,
+    ) -> Option<&mut TermEntry> {
+        let source_text = &source.get_string_by_type(file_type, string_id)?.content;
+        let target_text = &target.get_string_by_type(file_type, string_id)?.content;
+
+        if !is_valid_string(source_text) || !is_valid_string(target_text) || !options.accepts_length(source_text) {
+            return None;
+        }
+        if !seen.insert((source_text.clone(), target_text.clone())) {
+            return None;
+        }
+
+        self.entries.push(TermEntry {
+            file_type,
+            string_id,
+            source: source_text.clone(),
+            target: target_text.clone(),
+            record_type: None,
+            editor_id: None,
+        });
+        self.entries.last_mut()
+    }
+
+    /// 条目数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 查找原文对应的所有译文（同一原文可能有多个官方译法）
+    pub fn lookup(&self, source: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.source == source)
+            .map(|entry| entry.target.as_str())
+            .collect()
+    }
+
+    /// 导出为制表符分隔的术语表（首行为语言名，文本中的制表符和换行替换为空格）
+    pub fn to_tsv(&self) -> String {
+        let clean = |text: &str| text.replace(['\t', '\r', '\n'], " ");
+        let mut tsv = format!("{}\t{}\n", self.source_language, self.target_language);
+        for entry in &self.entries {
+            tsv.push_str(&clean(&entry.source));
+            tsv.push('\t');
+            tsv.push_str(&clean(&entry.target));
+            tsv.push('\n');
+        }
+        tsv
+    }
+
+    /// 保存术语库：`.tsv` / `.txt` 扩展名写出术语表，其余写出 JSON
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let is_tsv = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv") || ext.eq_ignore_ascii_case("txt"));
+
+        let content = if is_tsv { self.to_tsv() } else { serde_json::to_string_pretty(self)? };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
@@ -17,6 +17,7 @@ fn create_test_string_file() -> StringFile {
         plugin_name: "TestMod".to_string(),
         language: "english".to_string(),
         entries,
+        base: None,
     }
 }
 
@@ -93,6 +94,7 @@ fn test_rebuild_dlstrings() {
         plugin_name: "TestMod".to_string(),
         language: "chinese".to_string(),
         entries,
+        base: None,
     };
 
     let result = file.rebuild();
@@ -268,3 +270,292 @@ fn test_string_file_set_from_memory() {
 
     println!("✓ StringFileSet::from_memory 测试通过！");
 }
+
+#[test]
+fn test_open_mapped_lazy_decoding() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("TestMod_english.DLSTRINGS");
+
+    let mut entries = HashMap::new();
+    for id in 1..=50 {
+        entries.insert(id, StringEntry::new(id, format!("对话 {}", id)));
+    }
+    let eager = StringFile {
+        path: file_path.clone(),
+        file_type: StringFileType::DLSTRINGS,
+        plugin_name: "TestMod".to_string(),
+        language: "english".to_string(),
+        entries,
+        base: None,
+    };
+    eager.write_to_file(file_path.clone()).unwrap();
+
+    let mut lazy = StringFile::open_mapped(file_path.clone()).unwrap();
+    assert!(lazy.is_lazy());
+    assert!(lazy.entries.is_empty());
+    assert_eq!(lazy.count(), 50);
+    assert_eq!(lazy.get_string_ids(), eager.get_string_ids());
+    assert_eq!(lazy.get_string(7).unwrap().content, "对话 7");
+    assert!(lazy.get_string(999).is_none());
+    assert_eq!(lazy.rebuild().unwrap(), eager.rebuild().unwrap());
+
+    // 修改、新增和删除后与完整加载的文件结果一致
+    let mut loaded = StringFile::new(file_path.clone()).unwrap();
+    for file in [&mut lazy, &mut loaded] {
+        file.update_string(7, "已修改".to_string()).unwrap();
+        file.add_string(100, "新增".to_string()).unwrap();
+        assert!(file.add_string(8, "重复".to_string()).is_err());
+        assert_eq!(file.remove_string(9).unwrap().content, "对话 9");
+        assert!(file.remove_string(9).is_none());
+        assert!(file.update_string(9, "已删除".to_string()).is_err());
+    }
+    assert_eq!(lazy.entries.len(), 2);
+    assert_eq!(lazy.count(), loaded.count());
+    assert_eq!(lazy.iter().count(), 50);
+    assert_eq!(lazy.rebuild().unwrap(), loaded.rebuild().unwrap());
+
+    // 覆盖正在映射的源文件
+    lazy.write_to_file(file_path.clone()).unwrap();
+    assert_eq!(lazy.get_string(8).unwrap().content, "对话 8");
+    let reloaded = StringFile::new(file_path).unwrap();
+    assert_eq!(reloaded.get_string(7).unwrap().content, "已修改");
+    assert_eq!(reloaded.get_string(100).unwrap().content, "新增");
+    assert!(reloaded.get_string(9).is_none());
+}
+
+/// 构造 DLSTRINGS 数据：目录不按 ID 排序，ID 3 与 ID 1 共用偏移，ID 4 与 ID 2 内容相同
+fn shared_offset_dlstrings() -> Vec<u8> {
+    let strings: [&[u8]; 3] = [b"Shared\0", b"Other\0", b"Other\0"];
+    let directory = [(5u32, 0u32), (1, 11), (3, 11), (2, 22), (4, 32)];
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    data.extend_from_slice(&42u32.to_le_bytes());
+    for (id, offset) in directory {
+        data.extend_from_slice(&id.to_le_bytes());
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(&7u32.to_le_bytes());
+    data.extend_from_slice(b"Fifth\0\0");
+    for string in strings {
+        data.extend_from_slice(&(string.len() as u32).to_le_bytes());
+        data.extend_from_slice(string);
+    }
+    data
+}
+
+/// 读取重建结果的目录（ID -> 偏移）
+fn rebuilt_directory(data: &[u8]) -> Vec<(u32, u32)> {
+    let count = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+    (0..count)
+        .map(|i| {
+            let at = 8 + i * 8;
+            (
+                u32::from_le_bytes(data[at..at + 4].try_into().unwrap()),
+                u32::from_le_bytes(data[at + 4..at + 8].try_into().unwrap()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_rebuild_preserves_layout_and_shared_offsets() {
+    let original = shared_offset_dlstrings();
+    let load = || {
+        StringFile::from_bytes(&original, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS)
+            .unwrap()
+    };
+
+    // 未修改的文件逐字节一致（完整加载与延迟加载相同）
+    let mut file = load();
+    assert_eq!(file.rebuild().unwrap(), original);
+    let lazy = StringFile::from_shared(original.clone().into(), "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    assert_eq!(lazy.rebuild().unwrap(), original);
+
+    // 修改未共用的字符串：其余共用关系保持，目录顺序不变
+    file.update_string(5, "Fifth string".to_string()).unwrap();
+    let rebuilt = file.rebuild().unwrap();
+    let directory = rebuilt_directory(&rebuilt);
+    assert_eq!(directory.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![5, 1, 3, 2, 4]);
+    assert_eq!(directory[1].1, directory[2].1);
+    let reloaded = StringFile::from_bytes(&rebuilt, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    assert_eq!(reloaded.get_string(5).unwrap().content, "Fifth string");
+    assert_eq!(reloaded.get_string(3).unwrap().content, "Shared");
+    assert_eq!(reloaded.get_string(5).unwrap().length, Some(13));
+
+    // 修改共用偏移中的一个：另一个保留原始数据
+    file.update_string(3, "Changed".to_string()).unwrap();
+    let rebuilt = file.rebuild().unwrap();
+    let directory = rebuilt_directory(&rebuilt);
+    assert_ne!(directory[1].1, directory[2].1);
+    let reloaded = StringFile::from_bytes(&rebuilt, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    assert_eq!(reloaded.get_string(1).unwrap().content, "Shared");
+    assert_eq!(reloaded.get_string(3).unwrap().content, "Changed");
+
+    // 去重：内容相同的字符串共用偏移
+    let file = load();
+    let deduplicated = file.rebuild_with(&RebuildOptions { deduplicate: true, ..RebuildOptions::default() }).unwrap();
+    let directory = rebuilt_directory(&deduplicated);
+    assert_eq!(directory[3].1, directory[4].1);
+    assert_eq!(deduplicated.len(), original.len() - 10);
+    let reloaded = StringFile::from_bytes(&deduplicated, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    assert_eq!(reloaded.get_string(4).unwrap().content, "Other");
+}
+
+#[test]
+fn test_json_roundtrip_and_merge() {
+    let original = shared_offset_dlstrings();
+    let file = StringFile::from_bytes(&original, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS)
+        .unwrap();
+
+    // --parse-strings 的输出未经编辑时重建结果逐字节一致
+    let json = serde_json::to_string(&file.iter().collect::<Vec<_>>()).unwrap();
+    let rebuilt = StringFile::from_json(&json, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    assert_eq!(rebuilt.rebuild().unwrap(), original);
+
+    // 编辑过的条目按内容重新编码
+    let edited = json.replace("\"content\":\"Fifth\"", "\"content\":\"第五\"");
+    let rebuilt = StringFile::from_json(&edited, "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    let reloaded = StringFile::from_bytes(&rebuilt.rebuild().unwrap(), "TestMod".to_string(), "english".to_string(), StringFileType::DLSTRINGS).unwrap();
+    assert_eq!(reloaded.get_string(5).unwrap().content, "第五");
+    assert_eq!(reloaded.get_string(1).unwrap().content, "Shared");
+
+    // 简单映射（十进制或十六进制 ID）
+    let map = r#"{"1": "Iron Sword", "0x10": "Steel Dagger"}"#;
+    let built = StringFile::from_json(map, "TestMod".to_string(), "english".to_string(), StringFileType::STRINGS).unwrap();
+    assert_eq!(built.get_string_ids(), vec![1, 16]);
+    let reloaded = StringFile::from_bytes(&built.rebuild().unwrap(), "TestMod".to_string(), "english".to_string(), StringFileType::STRINGS).unwrap();
+    assert_eq!(reloaded.get_string(16).unwrap().content, "Steel Dagger");
+    assert!(StringFile::from_json(r#"{"abc": "x"}"#, "TestMod".to_string(), "english".to_string(), StringFileType::STRINGS).is_err());
+
+    // 合并到已有文件
+    let mut merged = file.clone();
+    let (updated, added) = merged.merge_json(r#"{"1": "Shared", "2": "Changed", "7": "New"}"#).unwrap();
+    assert_eq!((updated, added), (1, 1));
+    assert_eq!(merged.get_string(2).unwrap().content, "Changed");
+    assert_eq!(merged.get_string(7).unwrap().content, "New");
+    assert_eq!(merged.count(), 6);
+}
+
+#[test]
+fn test_rebuild_with_encoding() {
+    let mut file = create_test_string_file();
+    file.update_string(1, "Épée".to_string()).unwrap();
+
+    let options = RebuildOptions { encoding: Some("windows-1252".to_string()), ..RebuildOptions::default() };
+    let data = file.rebuild_with(&options).unwrap();
+    assert!(data.windows(5).any(|window| window == b"\xC9p\xE9e\0"));
+
+    file.update_string(2, "铁剑".to_string()).unwrap();
+    assert!(file.rebuild_with(&options).is_err());
+    let options = RebuildOptions { encoding: Some("unknown".to_string()), ..RebuildOptions::default() };
+    assert!(file.rebuild_with(&options).is_err());
+}
+
+#[test]
+fn test_compare_string_sets() {
+    let set_with = |language: &str, texts: &[(u32, &str)]| {
+        let mut file = StringFile::empty("TestMod".to_string(), language.to_string(), StringFileType::STRINGS);
+        for (id, text) in texts {
+            file.add_string(*id, text.to_string()).unwrap();
+        }
+        let mut set = StringFileSet::new("TestMod".to_string(), language.to_string());
+        set.add_file(StringFileType::STRINGS, file);
+        set
+    };
+
+    let english = set_with("english", &[
+        (1, "Iron Sword"),
+        (2, "Deals <mag> damage to <Alias=Target>"),
+        (3, "Level %d"),
+        (4, ""),
+        (5, "Only in English"),
+    ]);
+    let german = set_with("german", &[
+        (1, "Iron Sword"),
+        (2, "Verursacht <mag> Schaden"),
+        (3, "Stufe %d"),
+        (4, ""),
+        (6, "Nur auf Deutsch"),
+    ]);
+
+    let comparison = english.compare_with(&german);
+    assert_eq!(comparison.compared, 4);
+    assert_eq!(comparison.untranslated.len(), 1);
+    assert_eq!(comparison.untranslated[0].string_id, 1);
+    assert_eq!(comparison.missing_in_target[0].string_id, 5);
+    assert_eq!(comparison.missing_in_source[0].string_id, 6);
+    assert_eq!(comparison.placeholder_mismatches.len(), 1);
+    assert_eq!(comparison.placeholder_mismatches[0].missing, vec!["<Alias=Target>".to_string()]);
+    assert_eq!(
+        comparison.pending_ids().into_iter().collect::<Vec<_>>(),
+        vec![(StringFileType::STRINGS, 1), (StringFileType::STRINGS, 2), (StringFileType::STRINGS, 5)]
+    );
+
+    assert_eq!(
+        extract_placeholders("<font color='#FF0000'>%.1f%%</font> a < b"),
+        vec!["<font color='#FF0000'>", "%.1f", "</font>"]
+    );
+}
+
+#[test]
+fn test_term_base_alignment() {
+    let set_with = |language: &str, strings: &[(u32, &str)], dlstrings: &[(u32, &str)]| {
+        let mut set = StringFileSet::new("Skyrim".to_string(), language.to_string());
+        for (file_type, texts) in [(StringFileType::STRINGS, strings), (StringFileType::DLSTRINGS, dlstrings)] {
+            let mut file = StringFile::empty("Skyrim".to_string(), language.to_string(), file_type);
+            for (id, text) in texts {
+                file.add_string(*id, text.to_string()).unwrap();
+            }
+            set.add_file(file_type, file);
+        }
+        set
+    };
+
+    let english = set_with(
+        "english",
+        &[(1, "Iron Sword"), (2, "Whiterun"), (3, "Iron Sword"), (4, "Only English")],
+        &[(10, "A long description of the iron sword.")],
+    );
+    let german = set_with(
+        "german",
+        &[(1, "Eisenschwert"), (2, "Weißlauf"), (3, "Eisenschwert")],
+        &[(10, "Eine lange Beschreibung des Eisenschwerts.")],
+    );
+
+    // 相同的原文/译文对只收录一次，缺少译文的 ID 不收录
+    let all = TermBase::align(&english, &german, &TermBaseOptions::default());
+    assert_eq!(all.len(), 3);
+    assert_eq!(all.lookup("Iron Sword"), vec!["Eisenschwert"]);
+
+    let names = TermBase::align(&english, &german, &TermBaseOptions::names());
+    assert_eq!(names.len(), 2);
+    assert!(names.entries.iter().all(|entry| entry.file_type == StringFileType::STRINGS));
+
+    let short = TermBase::align(&english, &german, &TermBaseOptions { names_only: true, max_length: Some(8) });
+    assert_eq!(short.lookup("Whiterun"), vec!["Weißlauf"]);
+    assert!(short.lookup("Iron Sword").is_empty());
+
+    assert_eq!(names.to_tsv(), "english\tgerman\nIron Sword\tEisenschwert\nWhiterun\tWeißlauf\n");
+}
+
+#[test]
+fn test_multi_language_load_skips_unreadable_file() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut english = StringFile::empty("TestMod".to_string(), "english".to_string(), StringFileType::STRINGS);
+    english.add_string(1, "Sword".to_string()).unwrap();
+    english.write_to_file(temp_dir.path().join("TestMod_english.STRINGS")).unwrap();
+    let mut german = StringFile::empty("TestMod".to_string(), "german".to_string(), StringFileType::STRINGS);
+    german.add_string(1, "Schwert".to_string()).unwrap();
+    german.write_to_file(temp_dir.path().join("TestMod_german.STRINGS")).unwrap();
+
+    // 截断的文件：german 的 DLSTRINGS 和整个 french 语言
+    std::fs::write(temp_dir.path().join("TestMod_german.DLSTRINGS"), [1, 0]).unwrap();
+    std::fs::write(temp_dir.path().join("TestMod_french.STRINGS"), [1, 0]).unwrap();
+
+    let multi = MultiLanguageStringSet::load_from_directory(temp_dir.path(), "TestMod").unwrap();
+    assert_eq!(multi.languages(), vec!["english", "german"]);
+    assert_eq!(multi.get("German").unwrap().files.len(), 1);
+    assert_eq!(multi.get_entries(StringFileType::STRINGS, 1)["german"].content, "Schwert");
+}
@@ -1,3 +1,4 @@
+use crate::datatypes::Signature;
 use std::collections::HashMap;
 
 /// 字符串路由器 trait
@@ -10,9 +11,9 @@ pub trait StringRouter: Send + Sync + std::fmt::Debug {
     /// - `record_type`: 记录类型（如 "WEAP", "ARMO" 等）
     ///
     /// # 返回
-    /// - `Some(&[String])`: 该记录类型支持的子记录类型列表
+    /// - `Some(&[Signature])`: 该记录类型支持的子记录类型列表
     /// - `None`: 该记录类型不包含字符串或不支持
-    fn get_string_subrecord_types(&self, record_type: &str) -> Option<&[String]>;
+    fn get_string_subrecord_types(&self, record_type: Signature) -> Option<&[Signature]>;
 
     /// 检查某个记录类型的子记录类型是否支持字符串
     ///
@@ -23,10 +24,9 @@ pub trait StringRouter: Send + Sync + std::fmt::Debug {
     /// # 返回
     /// - `true`: 该组合支持字符串
     /// - `false`: 该组合不支持字符串
-    fn supports_strings(&self, record_type: &str, subrecord_type: &str) -> bool {
+    fn supports_strings(&self, record_type: Signature, subrecord_type: Signature) -> bool {
         self.get_string_subrecord_types(record_type)
-            .map(|types| types.iter().any(|t| t == subrecord_type))
-            .unwrap_or(false)
+            .is_some_and(|types| types.contains(&subrecord_type))
     }
 }
 
@@ -35,7 +35,7 @@ pub trait StringRouter: Send + Sync + std::fmt::Debug {
 /// 使用 string_records.json 中的数据提供路由功能
 #[derive(Debug)]
 pub struct DefaultStringRouter {
-    routes: HashMap<String, Vec<String>>,
+    routes: HashMap<Signature, Vec<Signature>>,
 }
 
 impl DefaultStringRouter {
@@ -43,7 +43,7 @@ impl DefaultStringRouter {
     ///
     /// # 参数
     /// - `routes`: 记录类型到子记录类型列表的映射
-    pub fn new(routes: HashMap<String, Vec<String>>) -> Self {
+    pub fn new(routes: HashMap<Signature, Vec<Signature>>) -> Self {
         Self { routes }
     }
 
@@ -52,14 +52,20 @@ impl DefaultStringRouter {
     /// # 错误
     /// 如果 JSON 解析失败，返回错误
     pub fn from_embedded_data() -> Result<Self, Box<dyn std::error::Error>> {
-        let routes = super::load_string_records()?;
+        let mut routes = HashMap::new();
+        for (record_type, subrecord_types) in super::load_string_records()? {
+            let subrecord_types = subrecord_types.iter()
+                .map(|subrecord_type| subrecord_type.parse())
+                .collect::<Result<Vec<Signature>, String>>()?;
+            routes.insert(record_type.parse::<Signature>()?, subrecord_types);
+        }
         Ok(Self::new(routes))
     }
 }
 
 impl StringRouter for DefaultStringRouter {
-    fn get_string_subrecord_types(&self, record_type: &str) -> Option<&[String]> {
-        self.routes.get(record_type).map(|v| v.as_slice())
+    fn get_string_subrecord_types(&self, record_type: Signature) -> Option<&[Signature]> {
+        self.routes.get(&record_type).map(|v| v.as_slice())
     }
 }
 
@@ -67,11 +73,15 @@ impl StringRouter for DefaultStringRouter {
 mod tests {
     use super::*;
 
+    const WEAP: Signature = Signature::from_name("WEAP");
+    const FULL: Signature = Signature::from_name("FULL");
+    const DESC: Signature = Signature::from_name("DESC");
+
     fn create_test_router() -> DefaultStringRouter {
         let mut routes = HashMap::new();
-        routes.insert("WEAP".to_string(), vec!["FULL".to_string(), "DESC".to_string()]);
-        routes.insert("ARMO".to_string(), vec!["FULL".to_string(), "DESC".to_string()]);
-        routes.insert("NPC_".to_string(), vec!["FULL".to_string(), "SHRT".to_string()]);
+        routes.insert(WEAP, vec![FULL, DESC]);
+        routes.insert(Signature::from_name("ARMO"), vec![FULL, DESC]);
+        routes.insert(Signature::from_name("NPC_"), vec![FULL, Signature::from_name("SHRT")]);
         DefaultStringRouter::new(routes)
     }
 
@@ -80,12 +90,12 @@ mod tests {
         let router = create_test_router();
 
         // 已知类型
-        let weap_types = router.get_string_subrecord_types("WEAP");
+        let weap_types = router.get_string_subrecord_types(WEAP);
         assert!(weap_types.is_some());
         assert_eq!(weap_types.unwrap(), &["FULL", "DESC"]);
 
         // 未知类型
-        let unknown = router.get_string_subrecord_types("UNKN");
+        let unknown = router.get_string_subrecord_types(Signature::from_name("UNKN"));
         assert!(unknown.is_none());
     }
 
@@ -94,13 +104,13 @@ mod tests {
         let router = create_test_router();
 
         // 支持的组合
-        assert!(router.supports_strings("WEAP", "FULL"));
-        assert!(router.supports_strings("WEAP", "DESC"));
-        assert!(router.supports_strings("NPC_", "SHRT"));
+        assert!(router.supports_strings(WEAP, FULL));
+        assert!(router.supports_strings(WEAP, DESC));
+        assert!(router.supports_strings(Signature::from_name("NPC_"), Signature::from_name("SHRT")));
 
         // 不支持的组合
-        assert!(!router.supports_strings("WEAP", "XXXX"));
-        assert!(!router.supports_strings("UNKN", "FULL"));
+        assert!(!router.supports_strings(WEAP, Signature::from_name("XXXX")));
+        assert!(!router.supports_strings(Signature::from_name("UNKN"), FULL));
     }
 
     #[test]
@@ -110,8 +120,8 @@ mod tests {
 
         let router = router.unwrap();
         // 验证一些已知的记录类型
-        assert!(router.supports_strings("WEAP", "FULL"));
-        assert!(router.supports_strings("BOOK", "CNAM"));
-        assert!(router.supports_strings("QUST", "NNAM"));
+        assert!(router.supports_strings(WEAP, FULL));
+        assert!(router.supports_strings(Signature::from_name("BOOK"), Signature::from_name("CNAM")));
+        assert!(router.supports_strings(Signature::from_name("QUST"), Signature::from_name("NNAM")));
     }
 }
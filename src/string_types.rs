@@ -1,4 +1,23 @@
+use std::collections::BTreeMap;
+
 use serde::{Serialize, Deserialize};
+use std::fmt::Display;
+
+use crate::string_file::StringFileType;
+
+/// 生成字符串唯一键
+///
+/// 格式：{editor_id}|{form_id}|{record_type} {subrecord_type}|{index}。
+/// 提取、应用翻译和多语言处理都通过此函数构造键，保证格式一致。
+pub(crate) fn unique_key(
+    editor_id: Option<&str>,
+    form_id: &str,
+    record_type: impl Display,
+    subrecord_type: impl Display,
+    index: i32,
+) -> String {
+    format!("{}|{}|{} {}|{}", editor_id.unwrap_or(""), form_id, record_type, subrecord_type, index)
+}
 
 /// 提取的字符串结构
 ///
@@ -59,11 +78,41 @@ impl ExtractedString {
     /// 格式：{editor_id}|{form_id}|{record_type} {subrecord_type}|{index}
     /// 所有字段都包含 index，确保完全唯一性
     pub fn get_unique_key(&self) -> String {
-        format!("{}|{}|{}|{}",
-            self.editor_id.as_deref().unwrap_or(""),
-            self.form_id,
-            self.get_string_type(),
-            self.index
-        )
+        unique_key(self.editor_id.as_deref(), &self.form_id, &self.record_type, &self.subrecord_type, self.index)
     }
-} 
\ No newline at end of file
+} 
+/// 多语言并列提取的字符串（每个字符串一行，每种语言一列）
+///
+/// `texts` 只包含 STRING 文件中存在该字符串的语言，缺失的语言不出现，
+/// 便于审阅已有翻译或选择非英语的源语言。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiLanguageString {
+    /// EDID字段(编辑器ID)
+    pub editor_id: Option<String>,
+    /// 完整FormID (包含主文件)
+    pub form_id: String,
+    /// 记录类型
+    pub record_type: String,
+    /// 子记录类型
+    pub subrecord_type: String,
+    /// 子记录索引（与 `ExtractedString::index` 一致）
+    pub index: i32,
+    /// 字符串所在的 STRING 文件类型
+    pub file_type: StringFileType,
+    /// StringID
+    pub string_id: u32,
+    /// 语言 -> 文本
+    pub texts: BTreeMap<String, String>,
+}
+
+impl MultiLanguageString {
+    /// 获取指定语言的文本（语言大小写不敏感）
+    pub fn text(&self, language: &str) -> Option<&str> {
+        self.texts.get(&language.to_lowercase()).map(String::as_str)
+    }
+
+    /// 生成唯一标识符（与 `ExtractedString::get_unique_key` 格式一致）
+    pub fn get_unique_key(&self) -> String {
+        unique_key(self.editor_id.as_deref(), &self.form_id, &self.record_type, &self.subrecord_type, self.index)
+    }
+}
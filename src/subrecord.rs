@@ -1,22 +1,68 @@
-use crate::datatypes::{read_u16, read_u32};
+use crate::datatypes::{read_u16, read_u32, SharedBytes, Signature};
 use std::io::{Read, Cursor};
+use std::ops::Range;
 
 /// 子记录结构
 #[derive(Debug, Clone)]
 pub struct Subrecord {
-    /// 4字符记录类型（原始字节）
-    pub record_type_bytes: [u8; 4],
-    /// 4字符记录类型（字符串，用于比较）
-    pub record_type: String,
-    /// 数据大小
+    /// 子记录类型
+    pub record_type: Signature,
+    /// 数据大小（XXXX 超大子记录为 0）
     pub size: u16,
-    /// 原始数据
-    pub data: Vec<u8>,
+    /// 原始数据（解析自文件时与所属记录共享存储）
+    pub data: SharedBytes,
+    /// 是否以 XXXX 超大子记录格式读取（写回时保持 XXXX 前缀）
+    pub large: bool,
 }
 
 impl Subrecord {
+    /// 创建新的子记录
+    ///
+    /// 数据超过 65535 字节时标记为 XXXX 超大子记录（大小字段为 0）。
+    ///
+    /// # 参数
+    /// * `record_type` - 4字符子记录类型（如 "EDID"、"FULL"）
+    /// * `data` - 子记录数据
+    ///
+    /// # Panics
+    /// `record_type` 不是恰好 4 个字节时 panic。处理外部输入时使用 `try_new`。
+    pub fn new(record_type: &str, data: Vec<u8>) -> Self {
+        Self::with_signature(Signature::from_name(record_type), data)
+    }
+
+    /// 创建新的子记录，子记录类型无效时返回错误
+    pub fn try_new(record_type: &str, data: Vec<u8>) -> Result<Self, String> {
+        Ok(Self::with_signature(record_type.parse()?, data))
+    }
+
+    /// 以签名创建新的子记录
+    pub fn with_signature(record_type: Signature, data: Vec<u8>) -> Self {
+        let mut subrecord = Subrecord {
+            record_type,
+            size: 0,
+            data: SharedBytes::default(),
+            large: false,
+        };
+        subrecord.set_data(data);
+        subrecord
+    }
+
     /// 解析子记录（包括 XXXX 超大子记录）
     pub fn parse(cursor: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = *cursor.get_ref();
+        Self::parse_with(cursor, &|range| SharedBytes::from(&bytes[range]))
+    }
+
+    /// 解析子记录，数据引用 `source` 的切片（游标所读的数据即 `source` 本身）
+    pub(crate) fn parse_shared(cursor: &mut Cursor<&[u8]>, source: &SharedBytes) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_with(cursor, &|range| source.slice(range))
+    }
+
+    /// 解析子记录，数据部分由 `data_of` 根据字节范围生成
+    fn parse_with(
+        cursor: &mut Cursor<&[u8]>,
+        data_of: &dyn Fn(Range<usize>) -> SharedBytes,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // 检查是否有足够的数据读取头部
         if cursor.position() + 6 > cursor.get_ref().len() as u64 {
             return Err("Insufficient data for subrecord header".into());
@@ -27,13 +73,13 @@ impl Subrecord {
         // 读取记录类型 (4字节)
         let mut type_bytes = [0u8; 4];
         cursor.read_exact(&mut type_bytes)?;
-        let record_type = String::from_utf8_lossy(&type_bytes).into_owned();
+        let record_type = Signature(type_bytes);
 
         // 读取数据大小 (2字节)
         let size = read_u16(cursor)?;
 
         // 🔧 特殊处理：XXXX 超大子记录
-        if record_type == "XXXX" {
+        if record_type == Signature::XXXX {
             // XXXX 的 size 必须是 4
             if size != 4 {
                 return Err(format!("XXXX subrecord size should be 4, got {}", size).into());
@@ -51,7 +97,7 @@ impl Subrecord {
             // 读取后续 subrecord 的头部
             let mut next_type_bytes = [0u8; 4];
             cursor.read_exact(&mut next_type_bytes)?;
-            let next_type = String::from_utf8_lossy(&next_type_bytes).into_owned();
+            let next_type = Signature(next_type_bytes);
 
             let next_size = read_u16(cursor)?;
             if next_size != 0 {
@@ -65,8 +111,7 @@ impl Subrecord {
             eprintln!("  后续子记录类型: {}", next_type);
 
             // 读取实际数据
-            let mut data = vec![0u8; field_size as usize];
-            cursor.read_exact(&mut data)?;
+            let data = Self::take_data(cursor, field_size as usize, data_of)?;
 
             #[cfg(debug_assertions)]
             eprintln!("  ✓ XXXX 子记录解析成功");
@@ -75,10 +120,10 @@ impl Subrecord {
             // 注意：size 字段用 u16，但实际大小可能超过 65535
             // 我们将其设置为 0 作为标记，实际大小由 data.len() 决定
             Ok(Subrecord {
-                record_type_bytes: next_type_bytes,
                 record_type: next_type,
                 size: 0,  // 标记为 XXXX 子记录
                 data,
+                large: true,
             })
         } else {
             // 普通子记录处理
@@ -97,21 +142,63 @@ impl Subrecord {
             }
 
             // 读取数据
-            let mut data = vec![0u8; size as usize];
-            cursor.read_exact(&mut data)?;
+            let data = Self::take_data(cursor, size as usize, data_of)?;
 
             Ok(Subrecord {
-                record_type_bytes: type_bytes,
                 record_type,
                 size,
                 data,
+                large: false,
             })
         }
     }
     
+    /// 从游标当前位置取出 `len` 字节数据并前移游标
+    fn take_data(
+        cursor: &mut Cursor<&[u8]>,
+        len: usize,
+        data_of: &dyn Fn(Range<usize>) -> SharedBytes,
+    ) -> Result<SharedBytes, Box<dyn std::error::Error>> {
+        let start = cursor.position() as usize;
+        let end = start.checked_add(len).filter(|end| *end <= cursor.get_ref().len())
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+        cursor.set_position(end as u64);
+        Ok(data_of(start..end))
+    }
+
+    /// 替换数据（同步 size 字段，数据超过 65535 字节时改为 XXXX 超大子记录）
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        match u16::try_from(data.len()) {
+            Ok(size) if !self.large => self.size = size,
+            _ => {
+                self.size = 0;
+                self.large = true;
+            }
+        }
+        self.data = data.into();
+    }
+
+    /// 序列化子记录
+    ///
+    /// 数据超过 65535 字节或原本以 XXXX 格式读取时，写出 XXXX 前缀
+    /// （XXXX 携带 32 位真实大小，后续子记录的大小字段为 0）。
+    pub fn write_to(&self, output: &mut Vec<u8>) {
+        if self.large || self.data.len() > u16::MAX as usize {
+            output.extend_from_slice(b"XXXX");
+            output.extend_from_slice(&4u16.to_le_bytes());
+            output.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+            output.extend_from_slice(self.record_type.as_bytes());
+            output.extend_from_slice(&0u16.to_le_bytes());
+        } else {
+            output.extend_from_slice(self.record_type.as_bytes());
+            output.extend_from_slice(&(self.data.len() as u16).to_le_bytes());
+        }
+        output.extend_from_slice(&self.data);
+    }
+
     /// 获取子记录类型
-    pub fn get_type(&self) -> &str {
-        &self.record_type
+    pub fn get_type(&self) -> Signature {
+        self.record_type
     }
     
     /// 获取数据
@@ -120,7 +207,7 @@ impl Subrecord {
     }
     
     /// 检查是否为字符串类型的子记录
-    pub fn is_string_type(&self, string_types: &[String]) -> bool {
-        string_types.iter().any(|t| t == &self.record_type)
+    pub fn is_string_type(&self, string_types: &[Signature]) -> bool {
+        string_types.contains(&self.record_type)
     }
 } 
\ No newline at end of file
@@ -346,8 +346,8 @@ fn count_special_records(
     for child in &group.children {
         match child {
             GroupChild::Record(record) => {
-                if SpecialRecordHandler::MULTI_FIELD_TYPES.contains(&record.record_type.as_str()) {
-                    *counts.entry(record.record_type.clone()).or_insert(0) += 1;
+                if SpecialRecordHandler::MULTI_FIELD_TYPES.iter().any(|t| record.record_type == *t) {
+                    *counts.entry(record.record_type.to_string()).or_insert(0) += 1;
                 }
             }
             GroupChild::Group(nested) => {